@@ -6,7 +6,10 @@ use std::{
 
 use thiserror::Error;
 
-use crate::{helpers::Role, report::InvalidReportError, sharding::ShardIndex, task::JoinError};
+use crate::{
+    helpers::Role, protocol::RecordId, report::InvalidReportError, sharding::ShardIndex,
+    task::JoinError,
+};
 
 /// An error raised by the IPA protocol.
 ///
@@ -73,6 +76,16 @@ pub enum Error {
     DZKPValidationFailed,
     #[error("Inconsistent shares")]
     InconsistentShares,
+    #[error("reshared values are inconsistent across helpers")]
+    ReshareValidationFailed,
+    #[error("record id overflow: {start} + {count} exceeds the maximum record id")]
+    RecordIdOverflow { start: RecordId, count: usize },
+    #[error("input row {row} belongs to a user whose rows are not adjacent")]
+    UngroupedInput { row: usize },
+    #[error("unsupported serialization format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("shares of row {row} come from helpers {roles:?}, expected one from each helper")]
+    InconsistentHelperSet { row: usize, roles: [Role; 3] },
 }
 
 impl Default for Error {
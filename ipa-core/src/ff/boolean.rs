@@ -3,7 +3,7 @@ use generic_array::GenericArray;
 use typenum::U1;
 
 use crate::{
-    ff::{Field, PrimeField, Serializable, U128Conversions},
+    ff::{field::order_from_u128, Field, PrimeField, Serializable, U128Conversions},
     impl_shared_value_common,
     protocol::{
         context::{dzkp_field::DZKPCompatibleField, dzkp_validator::SegmentEntry},
@@ -159,6 +159,12 @@ impl Field for Boolean {
     const NAME: &'static str = "Boolean";
 
     const ONE: Boolean = Boolean(true);
+
+    const ORDER: [u8; 32] = order_from_u128(2);
+
+    fn one_minus(&self) -> Self {
+        !*self
+    }
 }
 
 impl U128Conversions for Boolean {
@@ -251,4 +257,14 @@ mod test {
         let a = rng.gen::<Boolean>();
         assert_ne!(a, !a);
     }
+
+    #[test]
+    fn one_minus() {
+        use crate::{ff::Field, secret_sharing::SharedValue};
+
+        assert_eq!(Boolean::ZERO, Boolean::ONE.one_minus());
+        assert_eq!(Boolean::ONE, Boolean::ZERO.one_minus());
+        let a = thread_rng().gen::<Boolean>();
+        assert_eq!(a, a.one_minus().one_minus());
+    }
 }
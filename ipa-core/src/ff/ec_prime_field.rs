@@ -37,6 +37,32 @@ impl Fp25519 {
         assert_ne!(*self, Fp25519::ZERO);
         Fp25519(self.0.invert())
     }
+
+    /// Conversion from big-endian bytes. [`Serializable`] and the `[u8; 32]` conversions use
+    /// little-endian encoding, this is provided for interop with systems that encode scalars
+    /// big-endian.
+    ///
+    /// Values are reduced modulo the field order.
+    #[must_use]
+    pub fn from_be_bytes(mut bytes: [u8; 32]) -> Fp25519 {
+        bytes.reverse();
+        Fp25519::from(bytes)
+    }
+
+    /// Canonical big-endian encoding, the reverse of what [`Serializable`] writes.
+    #[must_use]
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = self.0.to_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    /// Conversion from an unsigned integer that preserves its numeric value, e.g. for breakdown
+    /// indices. Unlike the test-only `From<u64>`, the integer is not hashed.
+    #[must_use]
+    pub fn from_int<T: Into<u64>>(v: T) -> Fp25519 {
+        Fp25519(Scalar::from(v.into()))
+    }
 }
 
 ///trait for secret sharing
@@ -151,6 +177,22 @@ impl From<Scalar> for Fp25519 {
     }
 }
 
+/// Conversion from little-endian bytes into Fp25519
+///
+/// Values are reduced modulo the field order.
+impl From<[u8; 32]> for Fp25519 {
+    fn from(s: [u8; 32]) -> Self {
+        Fp25519(Scalar::from_bytes_mod_order(s))
+    }
+}
+
+/// Canonical little-endian encoding of Fp25519
+impl From<Fp25519> for [u8; 32] {
+    fn from(s: Fp25519) -> Self {
+        s.0.to_bytes()
+    }
+}
+
 /// Conversion from BA256 into Fp25519
 ///
 /// Values are reduced modulo the field order.
@@ -163,6 +205,18 @@ impl From<BA256> for Fp25519 {
     }
 }
 
+impl BA256 {
+    /// Packs the 32 canonical bytes of `s` into a `BA256` verbatim. This is always valid,
+    /// because a reduced scalar is less than `2^256`. Converting the result back with
+    /// `Fp25519::from` yields `s` again.
+    #[must_use]
+    pub fn from_scalar_bytes(s: &Fp25519) -> Self {
+        let mut buf = GenericArray::default();
+        s.serialize(&mut buf);
+        BA256::deserialize_infallible(&buf)
+    }
+}
+
 ///conversion from and to unsigned integers, preserving entropy, for testing purposes only
 #[cfg(test)]
 macro_rules! sc_hash_impl {
@@ -217,6 +271,24 @@ impl Field for Fp25519 {
     const NAME: &'static str = "Fp25519";
 
     const ONE: Fp25519 = Fp25519::ONE;
+
+    /// Order of the scalar group, `2^252 + 27742317777372353535851937790883648493`.
+    const ORDER: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde,
+        0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x10,
+    ];
+
+    fn is_canonical_encoding(bytes: &GenericArray<u8, U32>) -> bool {
+        Scalar::from_canonical_bytes((*bytes).into())
+            .is_some()
+            .into()
+    }
+
+    /// Scalars are always reduced and compare in constant time.
+    fn eq_reduced(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
 }
 
 impl FromRandom for Fp25519 {
@@ -262,7 +334,7 @@ mod test {
     use typenum::U32;
 
     use crate::{
-        ff::{ec_prime_field::Fp25519, Serializable},
+        ff::{boolean_array::BA256, ec_prime_field::Fp25519, Field, Serializable},
         secret_sharing::SharedValue,
     };
 
@@ -279,6 +351,28 @@ mod test {
         assert_eq!(input, output);
     }
 
+    ///test that packing scalar bytes into BA256 and reducing them back is the identity
+    #[test]
+    fn scalar_bytes_round_trip() {
+        let mut rng = thread_rng();
+        let mut order_minus_one = Fp25519::ORDER;
+        order_minus_one[0] -= 1;
+        let scalars = [
+            Fp25519::ZERO,
+            Fp25519::ONE,
+            Fp25519::deserialize_infallible(&order_minus_one.into()),
+            rng.gen(),
+            rng.gen(),
+        ];
+        for s in scalars {
+            let ba = BA256::from_scalar_bytes(&s);
+            let mut buf = GenericArray::<u8, U32>::default();
+            ba.serialize(&mut buf);
+            assert_eq!(<[u8; 32]>::from(s), <[u8; 32]>::from(buf));
+            assert_eq!(s, Fp25519::from(ba));
+        }
+    }
+
     ///test simple arithmetics to check that `curve25519_dalek` is used correctly
     #[test]
     fn simple_arithmetics_25519() {
@@ -325,4 +419,102 @@ mod test {
         let ia = a.invert();
         assert_eq!(a * ia, Fp25519(Scalar::ONE));
     }
+
+    ///test conversion to and from fixed size byte arrays
+    #[test]
+    fn bytes_25519() {
+        let mut rng = thread_rng();
+        let input = rng.gen::<Fp25519>();
+        let bytes = <[u8; 32]>::from(input);
+        assert_eq!(input, Fp25519::from(bytes));
+
+        let mut buf: GenericArray<u8, U32> = [0u8; 32].into();
+        input.serialize(&mut buf);
+        assert_eq!(buf.as_slice(), bytes.as_slice());
+
+        // values exceeding the field order are reduced. The lowest byte of the field order is not
+        // 0xFF, so incrementing it on `order - 1` gives the encoding of the order itself.
+        let mut order = <[u8; 32]>::from(-Fp25519::ONE);
+        order[0] += 1;
+        assert_eq!(Fp25519::ZERO, Fp25519::from(order));
+    }
+
+    ///test big-endian conversions against the little-endian serialization
+    #[test]
+    fn be_bytes_25519() {
+        let mut rng = thread_rng();
+        let input = rng.gen::<Fp25519>();
+        assert_eq!(input, Fp25519::from_be_bytes(input.to_be_bytes()));
+
+        let mut buf: GenericArray<u8, U32> = [0u8; 32].into();
+        input.serialize(&mut buf);
+        let mut reversed = <[u8; 32]>::from(buf);
+        reversed.reverse();
+        assert_eq!(input.to_be_bytes(), reversed);
+        assert_eq!(
+            Fp25519::deserialize_infallible(&buf),
+            Fp25519::from_be_bytes(reversed)
+        );
+
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        assert_eq!(Fp25519::ONE, Fp25519::from_be_bytes(one));
+    }
+
+    #[test]
+    fn from_bits_25519() {
+        assert_eq!(
+            Fp25519::from_int(5u8),
+            Fp25519::from_bits(&[true, false, true])
+        );
+
+        let order_bits = Fp25519::ORDER
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .collect::<Vec<_>>();
+        assert_eq!(256, order_bits.len());
+        assert_eq!(Fp25519::ZERO, Fp25519::from_bits(&order_bits));
+        assert_eq!(Fp25519::from([0xff; 32]), Fp25519::from_bits(&[true; 256]));
+    }
+
+    ///test that the reported order matches the one `curve25519_dalek` reduces by
+    #[test]
+    fn order_25519() {
+        assert_eq!(Fp25519::ZERO, Fp25519::from(Fp25519::ORDER));
+        let mut order_minus_one = Fp25519::ORDER;
+        order_minus_one[0] -= 1;
+        assert_eq!(-Fp25519::ONE, Fp25519::from(order_minus_one));
+    }
+
+    ///test that conversion from integers preserves their numeric value
+    #[test]
+    fn from_int_25519() {
+        assert_eq!(
+            Fp25519::from_int(5u32) + Fp25519::from_int(3u32),
+            Fp25519::from_int(8u32)
+        );
+        assert_eq!(
+            Fp25519::from_int(7u8) * Fp25519::from_int(6u16),
+            Fp25519::from_int(42u64)
+        );
+        assert_eq!(Fp25519::from_int(0u64), Fp25519::ZERO);
+        assert_eq!(Fp25519::from_int(1u8), Fp25519::ONE);
+        // no reduction happens for values that are smaller than the field order
+        let two_pow_32 = Fp25519::from_int(u32::MAX) + Fp25519::ONE;
+        assert_eq!(
+            Fp25519::from_int(u64::MAX) + Fp25519::ONE,
+            two_pow_32 * two_pow_32
+        );
+    }
+
+    ///test that only values below the order are canonical
+    #[test]
+    fn canonical_encoding_25519() {
+        let mut order_minus_one = Fp25519::ORDER;
+        order_minus_one[0] -= 1;
+        assert!(Fp25519::is_canonical_encoding(&[0u8; 32].into()));
+        assert!(Fp25519::is_canonical_encoding(&order_minus_one.into()));
+        assert!(!Fp25519::is_canonical_encoding(&Fp25519::ORDER.into()));
+        assert!(!Fp25519::is_canonical_encoding(&[0xff; 32].into()));
+    }
 }
@@ -1,12 +1,16 @@
 use std::{
+    cmp::Ordering,
     fmt::Debug,
     ops::{Mul, MulAssign},
 };
 
+use generic_array::GenericArray;
 use serde::{Deserialize, Serialize};
-use typenum::{U1, U4, U8};
+use typenum::{Unsigned, U1, U4, U8};
 
 use crate::{
+    error::{Error, LengthError},
+    ff::Serializable,
     protocol::prss::FromRandom,
     secret_sharing::{Block, FieldVectorizable, SharedValue, Vectorizable},
 };
@@ -40,6 +44,118 @@ pub trait Field:
 
     /// Multiplicative identity element
     const ONE: Self;
+
+    /// Number of elements in the field, as a little-endian integer. For prime fields this is the
+    /// modulus, for `Fp25519` it is the order of the curve25519 scalar group.
+    const ORDER: [u8; 32];
+
+    /// Deserializes a field value from a slice whose length is only known at runtime, unlike
+    /// [`Serializable::deserialize`] that relies on the buffer size being a type invariant.
+    ///
+    /// ## Errors
+    /// If `bytes` is not exactly as long as the serialized representation of this field, or
+    /// if the bytes do not represent a valid field value.
+    fn try_from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let expected = <Self as Serializable>::Size::USIZE;
+        if bytes.len() != expected {
+            return Err(LengthError {
+                expected,
+                actual: bytes.len(),
+            }
+            .into());
+        }
+
+        Self::deserialize(GenericArray::from_slice(bytes)).map_err(|e| Error::ParseError(e.into()))
+    }
+
+    /// Checks whether `bytes` is the canonical encoding of a field value, i.e. a little-endian
+    /// integer less than [`Self::ORDER`](Field::ORDER). Deserialization of some fields reduces
+    /// its input, so strict parsers can use this to reject encodings that would otherwise be
+    /// silently accepted.
+    #[must_use]
+    fn is_canonical_encoding(bytes: &GenericArray<u8, <Self as Serializable>::Size>) -> bool {
+        let mut padded = [0u8; 32];
+        padded[..bytes.len()].copy_from_slice(bytes);
+        padded.iter().rev().cmp(Self::ORDER.iter().rev()) == Ordering::Less
+    }
+
+    /// Compares the canonical values of `self` and `other`. Unlike `==`, this does not rely on
+    /// both values being stored in reduced form: multiplying by one reduces them first. Fields
+    /// with constant-time equality, like `Fp25519`, override it to use that instead.
+    #[must_use]
+    fn eq_reduced(&self, other: &Self) -> bool {
+        *self * Self::ONE == *other * Self::ONE
+    }
+
+    /// Assembles a field value from its bits, least significant bit first, computing
+    /// `sum(bit_i * 2^i)` by repeated doubling. Inputs longer than the field size are reduced
+    /// modulo [`Self::ORDER`](Field::ORDER).
+    #[must_use]
+    fn from_bits(bits: &[bool]) -> Self {
+        bits.iter().rev().fold(Self::ZERO, |acc, &bit| {
+            let acc = acc + acc;
+            if bit {
+                acc + Self::ONE
+            } else {
+                acc
+            }
+        })
+    }
+
+    /// Returns the canonical value of this field element as `u128`.
+    ///
+    /// ## Errors
+    /// If the value does not fit into `u128`. This never happens for fields of 128 bits or less.
+    fn try_to_u128(&self) -> Result<u128, Error> {
+        let mut buf = GenericArray::default();
+        self.serialize(&mut buf);
+        if buf.iter().skip(16).any(|&b| b != 0) {
+            return Err(Error::FieldValueTruncation(format!(
+                "{} value {self:?} does not fit into u128",
+                Self::NAME
+            )));
+        }
+        let mut low = [0u8; 16];
+        let len = std::cmp::min(buf.len(), low.len());
+        low[..len].copy_from_slice(&buf[..len]);
+        Ok(u128::from_le_bytes(low))
+    }
+
+    /// Returns the canonical value of this field element as `u64`.
+    ///
+    /// ## Errors
+    /// If the value does not fit into `u64`. This never happens for fields of 64 bits or less.
+    fn try_to_u64(&self) -> Result<u64, Error> {
+        self.try_to_u128()
+            .ok()
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or_else(|| {
+                Error::FieldValueTruncation(format!(
+                    "{} value {self:?} does not fit into u64",
+                    Self::NAME
+                ))
+            })
+    }
+
+    /// Computes `1 - self`. Fields where this has a cheaper form, like complement for
+    /// [`Boolean`](crate::ff::boolean::Boolean), override it.
+    #[must_use]
+    fn one_minus(&self) -> Self {
+        Self::ONE - *self
+    }
+}
+
+/// Little-endian encoding of a field order that fits into `u128`, see [`Field::ORDER`].
+#[must_use]
+pub(crate) const fn order_from_u128(order: u128) -> [u8; 32] {
+    let le = order.to_le_bytes();
+    let mut bytes = [0u8; 32];
+    let mut i = 0;
+    while i < le.len() {
+        bytes[i] = le[i];
+        i += 1;
+    }
+    bytes
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -49,3 +165,166 @@ pub enum FieldType {
     Fp31,
     Fp32BitPrime,
 }
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use crate::{
+        error::{Error, LengthError},
+        ff::{
+            boolean::Boolean, ec_prime_field::Fp25519, Field, Fp31, Fp32BitPrime, PrimeField,
+            U128Conversions,
+        },
+        rand::{thread_rng, Rng},
+        secret_sharing::SharedValue,
+    };
+
+    #[test]
+    fn canonical_encoding() {
+        let fp31 = |b: u8| Fp31::is_canonical_encoding(&[b].into());
+        assert!(fp31(0));
+        assert!(fp31(30));
+        assert!(!fp31(31));
+        assert!(!fp31(u8::MAX));
+
+        let fp32 = |v: u32| Fp32BitPrime::is_canonical_encoding(&v.to_le_bytes().into());
+        assert!(fp32(Fp32BitPrime::PRIME - 1));
+        assert!(!fp32(Fp32BitPrime::PRIME));
+        assert!(!fp32(u32::MAX));
+
+        assert!(Boolean::is_canonical_encoding(&[1].into()));
+        assert!(!Boolean::is_canonical_encoding(&[2].into()));
+    }
+
+    #[test]
+    fn eq_reduced() {
+        let a = Fp31::truncate_from(40_u8);
+        let b = Fp31::truncate_from(5_u8) + Fp31::truncate_from(4_u8);
+        let c = Fp31::truncate_from(3_u8) * Fp31::truncate_from(3_u8);
+        assert!(a.eq_reduced(&b));
+        assert!(a.eq_reduced(&c));
+        assert!(!a.eq_reduced(&Fp31::ONE));
+
+        let x = Fp25519::from(Scalar::from(7_u8));
+        let y = Fp25519::from(Scalar::from(3_u8)) + Fp25519::from(Scalar::from(4_u8));
+        assert!(x.eq_reduced(&y));
+        assert!(!x.eq_reduced(&Fp25519::ONE));
+    }
+
+    #[test]
+    fn to_integer() {
+        assert_eq!(
+            42,
+            Fp32BitPrime::truncate_from(42_u8).try_to_u128().unwrap()
+        );
+        assert_eq!(11, Fp31::truncate_from(42_u8).try_to_u64().unwrap());
+        assert_eq!(
+            u128::from(Fp32BitPrime::PRIME - 1),
+            Fp32BitPrime::truncate_from(Fp32BitPrime::PRIME - 1)
+                .try_to_u128()
+                .unwrap()
+        );
+        assert_eq!(1, Boolean::ONE.try_to_u128().unwrap());
+
+        let x = Fp25519::from(Scalar::from(u64::MAX));
+        assert_eq!(u128::from(u64::MAX), x.try_to_u128().unwrap());
+        assert_eq!(u64::MAX, x.try_to_u64().unwrap());
+        let big = x + Fp25519::ONE;
+        assert_eq!(u128::from(u64::MAX) + 1, big.try_to_u128().unwrap());
+        assert!(matches!(
+            big.try_to_u64(),
+            Err(Error::FieldValueTruncation(_))
+        ));
+
+        let huge = Fp25519::from(Scalar::from(u128::MAX)) + Fp25519::ONE;
+        assert!(matches!(
+            huge.try_to_u128(),
+            Err(Error::FieldValueTruncation(_))
+        ));
+    }
+
+    #[test]
+    fn one_minus() {
+        let mut rng = thread_rng();
+        assert_eq!(Fp31::ZERO, Fp31::ONE.one_minus());
+        assert_eq!(
+            Fp31::truncate_from(30_u8),
+            Fp31::truncate_from(2_u8).one_minus()
+        );
+        for _ in 0..10 {
+            let x = rng.gen::<Fp31>();
+            assert_eq!(x, x.one_minus().one_minus());
+            let y = rng.gen::<Fp32BitPrime>();
+            assert_eq!(y, y.one_minus().one_minus());
+        }
+    }
+
+    #[test]
+    fn from_bits() {
+        assert_eq!(
+            Fp31::truncate_from(5_u8),
+            Fp31::from_bits(&[true, false, true])
+        );
+        assert_eq!(Fp31::ZERO, Fp31::from_bits(&[]));
+        // 0b11111 = 31 and 0b100000 = 32 reduce mod 31
+        assert_eq!(Fp31::ZERO, Fp31::from_bits(&[true; 5]));
+        assert_eq!(
+            Fp31::ONE,
+            Fp31::from_bits(&[false, false, false, false, false, true])
+        );
+        assert_eq!(
+            Fp32BitPrime::truncate_from(u32::MAX),
+            Fp32BitPrime::from_bits(&[true; 32])
+        );
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        assert_eq!(
+            Fp31::truncate_from(7_u8),
+            Fp31::try_from_bytes(&[7]).unwrap()
+        );
+        assert_eq!(
+            Fp32BitPrime::ONE,
+            Fp32BitPrime::try_from_bytes(&[1, 0, 0, 0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn try_from_bytes_short() {
+        assert!(matches!(
+            Fp32BitPrime::try_from_bytes(&[1, 0, 0]),
+            Err(Error::LengthError(LengthError {
+                expected: 4,
+                actual: 3
+            }))
+        ));
+        assert!(matches!(
+            Fp31::try_from_bytes(&[]),
+            Err(Error::LengthError(LengthError {
+                expected: 1,
+                actual: 0
+            }))
+        ));
+    }
+
+    #[test]
+    fn try_from_bytes_long() {
+        assert!(matches!(
+            Fp32BitPrime::try_from_bytes(&[1, 0, 0, 0, 0]),
+            Err(Error::LengthError(LengthError {
+                expected: 4,
+                actual: 5
+            }))
+        ));
+    }
+
+    #[test]
+    fn try_from_bytes_invalid_value() {
+        assert!(matches!(
+            Fp31::try_from_bytes(&[31]),
+            Err(Error::ParseError(_))
+        ));
+    }
+}
@@ -189,6 +189,23 @@ macro_rules! bit_array_impl {
                 const NAME: &'static str = stringify!($field);
 
                 const ONE: Self = Self($one);
+
+                const ORDER: [u8; 32] = crate::ff::field::order_from_u128(1 << $bits);
+
+                /// Bits are the coefficients of the polynomial representation, so values that
+                /// fit are the same as [`U128Conversions::truncate_from`], and longer inputs are
+                /// reduced modulo the field polynomial.
+                fn from_bits(bits: &[bool]) -> Self {
+                    let x = Self::truncate_from(2_u128);
+                    bits.iter().rev().fold(Self::ZERO, |acc, &bit| {
+                        let acc = acc * x;
+                        if bit {
+                            acc + Self::ONE
+                        } else {
+                            acc
+                        }
+                    })
+                }
             }
 
             impl U128Conversions for $name {
@@ -523,6 +540,23 @@ macro_rules! bit_array_impl {
                     assert_eq!($name::truncate_from(max_plus_one).0, one);
                 }
 
+                #[test]
+                pub fn from_bits() {
+                    let v = thread_rng().gen::<u128>() & MASK;
+                    let bits = (0..<$name>::BITS)
+                        .map(|i| (v >> i) & 1 == 1)
+                        .collect::<Vec<_>>();
+                    assert_eq!($name::truncate_from(v), <$name as Field>::from_bits(&bits));
+
+                    let mut overflow = vec![false; <$name>::BITS as usize];
+                    overflow.push(true);
+                    let top = $name::truncate_from(1_u128 << (<$name>::BITS - 1));
+                    assert_eq!(
+                        top * $name::truncate_from(2_u128),
+                        <$name as Field>::from_bits(&overflow)
+                    );
+                }
+
                 #[test]
                 pub fn index() {
                     let s = $name::try_from(1_u128).unwrap();
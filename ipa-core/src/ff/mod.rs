@@ -13,6 +13,7 @@ mod prime_field;
 use std::{
     borrow::Borrow,
     convert::Infallible,
+    io,
     ops::{Add, AddAssign, Sub, SubAssign},
 };
 
@@ -22,9 +23,12 @@ use generic_array::{ArrayLength, GenericArray};
 #[cfg(any(test, feature = "weak-field"))]
 pub use prime_field::Fp31;
 pub use prime_field::{Fp32BitPrime, Fp61BitPrime, PrimeField};
+use typenum::Unsigned;
 
 use crate::{
-    error::UnwrapInfallible, protocol::prss::FromRandomU128, secret_sharing::BitDecomposed,
+    error::{Error as CoreError, LengthError, UnwrapInfallible},
+    protocol::prss::FromRandomU128,
+    secret_sharing::BitDecomposed,
 };
 
 #[derive(Debug, thiserror::Error, PartialEq, Eq)]
@@ -58,6 +62,13 @@ pub trait U128Conversions: FromRandomU128 + TryFrom<u128, Error = crate::error::
 }
 
 /// Trait for items that have fixed-byte length representation.
+///
+/// This is the encoding used for everything sent between helpers, there is no text or
+/// self-describing format on that path. Values take the minimal number of bytes their type
+/// needs, e.g. an `Fp31` value is a single byte and a replicated share of it is two. Shares that
+/// leave the helper can pick between this form and JSON with [`Codec`].
+///
+/// [`Codec`]: crate::secret_sharing::replicated::semi_honest::Codec
 pub trait Serializable: Sized {
     /// Required number of bytes to store this message on disk/network
     type Size: ArrayLength;
@@ -93,6 +104,136 @@ pub trait Serializable: Sized {
     }
 }
 
+/// Helpers for working with buffers that hold many [`Serializable`] values back to back. These
+/// are implemented once for every serializable type and are not meant to be overridden.
+pub trait SerializableExt: Serializable {
+    /// Number of bytes `rows` instances of this type occupy when serialized back to back, or
+    /// `None` if it does not fit into `usize`.
+    #[must_use]
+    fn total_serialized_size(rows: usize) -> Option<usize> {
+        rows.checked_mul(Self::Size::USIZE)
+    }
+
+    /// Number of instances of this type held by a buffer of `buf_len` bytes, so readers can
+    /// allocate space for all of them before deserializing the buffer.
+    ///
+    /// ## Errors
+    /// If `buf_len` is not a multiple of the serialized size. The error reports lengths in bytes,
+    /// expecting the buffer to be padded to the next whole row.
+    fn row_count(buf_len: usize) -> Result<usize, LengthError> {
+        let size = Self::Size::USIZE;
+        if buf_len % size == 0 {
+            Ok(buf_len / size)
+        } else {
+            Err(LengthError {
+                expected: buf_len.next_multiple_of(size),
+                actual: buf_len,
+            })
+        }
+    }
+
+    /// Serializes all `rows` back to back into a buffer that is allocated with the exact size
+    /// up front.
+    ///
+    /// ## Panics
+    /// If the total size does not fit into `usize`.
+    fn serialize_all<'a, I>(rows: I) -> Vec<u8>
+    where
+        Self: 'a,
+        I: IntoIterator<Item = &'a Self>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let rows = rows.into_iter();
+        let mut buf = vec![0u8; Self::total_serialized_size(rows.len()).unwrap()];
+        for (row, chunk) in rows.zip(buf.chunks_exact_mut(Self::Size::USIZE)) {
+            row.serialize(GenericArray::from_mut_slice(chunk));
+        }
+
+        buf
+    }
+
+    /// Serializes `rows` into `w` one at a time, going through a scratch buffer that fits a
+    /// single row. Unlike [`Self::serialize_all`], this does not need the whole result set in
+    /// memory. Returns the number of bytes written.
+    ///
+    /// ## Errors
+    /// If writing to `w` fails.
+    fn write_rows<W, I, B>(rows: I, w: &mut W) -> io::Result<usize>
+    where
+        W: io::Write,
+        I: IntoIterator<Item = B>,
+        B: Borrow<Self>,
+    {
+        let mut buf = GenericArray::default();
+        let mut written = 0;
+        for row in rows {
+            row.borrow().serialize(&mut buf);
+            w.write_all(&buf)?;
+            written += buf.len();
+        }
+
+        Ok(written)
+    }
+}
+
+impl<T: Serializable> SerializableExt for T {}
+
+/// Row types whose serialized buffers leave the helper. Buffers start with a format version, so
+/// the serialized form of each row type can evolve independently.
+pub trait VersionedSerializable: Serializable {
+    /// Format version written by [`Self::serialize_versioned`]. It needs to change whenever the
+    /// serialized form of the row does.
+    const SERIALIZATION_VERSION: u8;
+
+    /// Same as [`SerializableExt::serialize_all`], but the buffer starts with a byte holding
+    /// [`Self::SERIALIZATION_VERSION`].
+    ///
+    /// ## Panics
+    /// If the total size does not fit into `usize`.
+    fn serialize_versioned<'a, I>(rows: I) -> Vec<u8>
+    where
+        Self: 'a,
+        I: IntoIterator<Item = &'a Self>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let mut buf = vec![Self::SERIALIZATION_VERSION];
+        buf.extend(Self::serialize_all(rows));
+
+        buf
+    }
+
+    /// Reads rows written by [`Self::serialize_versioned`].
+    ///
+    /// ## Errors
+    /// [`UnsupportedVersion`] if `buf` was written in a different format version of this row
+    /// type, a length error if it is empty or does not hold a whole number of rows, and a parse
+    /// error if any of the rows fails to deserialize.
+    ///
+    /// [`UnsupportedVersion`]: crate::error::Error::UnsupportedVersion
+    fn deserialize_versioned(buf: &[u8]) -> Result<Vec<Self>, CoreError> {
+        let Some((&version, rows)) = buf.split_first() else {
+            return Err(LengthError {
+                expected: 1,
+                actual: 0,
+            }
+            .into());
+        };
+        if version != Self::SERIALIZATION_VERSION {
+            return Err(CoreError::UnsupportedVersion(version));
+        }
+
+        let mut r = Vec::with_capacity(Self::row_count(rows.len())?);
+        for chunk in rows.chunks_exact(Self::Size::USIZE) {
+            r.push(
+                Self::deserialize(GenericArray::from_slice(chunk))
+                    .map_err(|e| CoreError::ParseError(e.into()))?,
+            );
+        }
+
+        Ok(r)
+    }
+}
+
 pub trait ArrayAccess {
     type Output;
     type Iter<'a>: ExactSizeIterator<Item = Self::Output> + Send
@@ -2,7 +2,7 @@ use std::{fmt::Display, mem};
 
 use generic_array::GenericArray;
 
-use super::Field;
+use super::{field::order_from_u128, Field};
 use crate::{
     const_assert,
     ff::{Serializable, U128Conversions},
@@ -87,6 +87,8 @@ macro_rules! field_impl {
             const NAME: &'static str = stringify!($field);
 
             const ONE: Self = $field(1);
+
+            const ORDER: [u8; 32] = order_from_u128($prime);
         }
 
         impl U128Conversions for $field {
@@ -316,6 +318,26 @@ macro_rules! field_impl {
                 assert_eq!($field::ZERO, $field::ZERO * $field::ONE);
             }
 
+            #[test]
+            fn order() {
+                let (low, high) = $field::ORDER.split_at(16);
+                assert_eq!(
+                    u128::from($field::PRIME),
+                    u128::from_le_bytes(low.try_into().unwrap())
+                );
+                assert!(high.iter().all(|&b| b == 0));
+            }
+
+            #[test]
+            fn eq_reduced() {
+                // arithmetic never stores the prime itself, it reduces to zero
+                let not_reduced = $field($field::PRIME);
+                assert_ne!($field::ZERO, not_reduced);
+                assert!(not_reduced.eq_reduced(&$field::ZERO));
+                assert!($field::ZERO.eq_reduced(&not_reduced));
+                assert!(!not_reduced.eq_reduced(&$field::ONE));
+            }
+
             proptest! {
 
                 #[test]
@@ -370,6 +392,13 @@ mod fp31 {
             x += Fp31(2);
             assert_eq!(Fp31(3), x);
         }
+
+        #[test]
+        fn fp31_order() {
+            let mut expected = [0u8; 32];
+            expected[0] = 31;
+            assert_eq!(expected, Fp31::ORDER);
+        }
     }
 }
 
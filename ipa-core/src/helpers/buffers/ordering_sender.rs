@@ -40,6 +40,11 @@ struct State {
     write_ready: Option<Waker>,
     /// Another entity to wake when the buffer is read from.
     stream_ready: Option<Waker>,
+    /// Set when someone is waiting for the buffer to be drained, see [`OrderingSender::flush`].
+    flush_requested: bool,
+    /// Entities waiting for the buffer to be drained. There can be more than one, as every
+    /// channel owner may flush concurrently.
+    flush_ready: Vec<Waker>,
 }
 
 impl State {
@@ -51,6 +56,8 @@ impl State {
             closed: false,
             write_ready: None,
             stream_ready: None,
+            flush_requested: false,
+            flush_ready: Vec::new(),
         }
     }
 
@@ -107,11 +114,17 @@ impl State {
     }
 
     fn take(&mut self, cx: &Context<'_>) -> Poll<Vec<u8>> {
-        if self.written > 0 && (self.written + self.spare >= self.buf.len() || self.closed) {
+        if self.written > 0
+            && (self.written + self.spare >= self.buf.len() || self.closed || self.flush_requested)
+        {
             let v = self.buf[..self.written].to_vec();
             self.written = 0;
+            self.flush_requested = false;
 
             Self::wake(&mut self.write_ready);
+            for w in self.flush_ready.drain(..) {
+                w.wake();
+            }
             Poll::Ready(v)
         } else {
             Self::save_waker(&mut self.stream_ready, cx);
@@ -119,6 +132,29 @@ impl State {
         }
     }
 
+    fn flush(&mut self, cx: &Context<'_>) -> Poll<()> {
+        if self.request_flush() {
+            if !self.flush_ready.iter().any(|w| w.will_wake(cx.waker())) {
+                self.flush_ready.push(cx.waker().clone());
+            }
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    }
+
+    /// Returns `true` if there is data for the stream to take.
+    fn request_flush(&mut self) -> bool {
+        if self.written == 0 {
+            self.flush_requested = false;
+            false
+        } else {
+            self.flush_requested = true;
+            Self::wake(&mut self.stream_ready);
+            true
+        }
+    }
+
     fn close(&mut self) {
         debug_assert!(!self.closed);
         self.closed = true;
@@ -343,6 +379,23 @@ impl OrderingSender {
         Close { i, sender: self }
     }
 
+    /// Makes all the data written so far available to the stream, even if the write threshold
+    /// has not been reached yet. The future this method returns resolves once the stream has
+    /// taken everything from the buffer. Messages written while this future is pending are
+    /// flushed too.
+    pub fn flush(&self) -> Flush<'_> {
+        Flush { sender: self }
+    }
+
+    /// Makes all the data written so far available to the stream, like [`Self::flush`] does,
+    /// but does not wait for the stream to take it.
+    ///
+    /// ## Panics
+    /// If the underlying mutex is poisoned or locked by the same thread.
+    pub fn request_flush(&self) {
+        self.state.lock().unwrap().request_flush();
+    }
+
     /// Returns `true` if this sender is closed for writes.
     ///
     /// ## Panics
@@ -351,6 +404,11 @@ impl OrderingSender {
         self.state.lock().unwrap().closed
     }
 
+    /// Returns the index of the next message that this sender expects to write or close at.
+    pub fn next_index(&self) -> usize {
+        self.next.load(Acquire)
+    }
+
     /// Perform the next `send` or `close` operation.
     fn next_op<F>(&self, i: usize, cx: &Context<'_>, f: F) -> Poll<()>
     where
@@ -496,6 +554,19 @@ impl<'s> Future for Close<'s> {
     }
 }
 
+/// A future for flushing an `OrderingSender`.
+pub struct Flush<'s> {
+    sender: &'s OrderingSender,
+}
+
+impl<'s> Future for Flush<'s> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.sender.state.lock().unwrap().flush(cx)
+    }
+}
+
 /// An `OrderingSender` as a `Stream`.
 ///
 /// This is a little odd in that it can be misused by creating multiple streams
@@ -522,11 +593,14 @@ mod test {
         iter::zip,
         num::NonZeroUsize,
         pin::{pin, Pin},
+        sync::atomic::{AtomicBool, Ordering},
+        task::Context,
     };
 
     use futures::{
         future::{join, join3, join_all, poll_immediate, try_join_all},
         stream::StreamExt,
+        task::{waker, ArcWake},
         Future, FutureExt,
     };
     use generic_array::GenericArray;
@@ -592,6 +666,68 @@ mod test {
         });
     }
 
+    /// Flushing makes partially filled buffer available to the stream.
+    #[test]
+    fn send_flush_recv() {
+        run(|| async {
+            let input = Fp31::truncate_from(7_u128);
+            let sender = sender();
+            sender.send(0, input).await;
+            let (_, output) = join(sender.flush(), sender.as_stream().next()).await;
+            let output = Fp31::deserialize_unchecked(GenericArray::from_slice(&output.unwrap()));
+            assert_eq!(input, output);
+            // nothing left to flush
+            assert_eq!(Some(()), sender.flush().now_or_never());
+            assert!(!sender.is_closed());
+        });
+    }
+
+    /// Requesting a flush does not wait for the stream, but the data becomes available to it.
+    #[test]
+    fn request_flush() {
+        run(|| async {
+            let input = Fp31::truncate_from(7_u128);
+            let sender = sender();
+            sender.send(0, input).await;
+            sender.request_flush();
+            let output = sender.as_stream().next().now_or_never().flatten().unwrap();
+            let output = Fp31::deserialize_unchecked(GenericArray::from_slice(&output));
+            assert_eq!(input, output);
+        });
+    }
+
+    /// Every pending flush is woken when the buffer is drained, not just the last one to ask.
+    #[test]
+    fn concurrent_flush() {
+        struct Woken(AtomicBool);
+
+        impl ArcWake for Woken {
+            fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
+                arc_self.0.store(true, Ordering::Relaxed);
+            }
+        }
+
+        run(|| async {
+            let sender = sender();
+            sender.send(0, Fp31::truncate_from(7_u128)).await;
+
+            let woken = [(); 2].map(|()| std::sync::Arc::new(Woken(AtomicBool::new(false))));
+            let mut flushes = [sender.flush(), sender.flush()];
+            for (flush, woken) in zip(&mut flushes, &woken) {
+                let waker = waker(std::sync::Arc::clone(woken));
+                assert!(Pin::new(flush)
+                    .poll(&mut Context::from_waker(&waker))
+                    .is_pending());
+            }
+
+            assert!(sender.as_stream().next().await.is_some());
+            assert!(woken.iter().all(|w| w.0.load(Ordering::Relaxed)));
+            for flush in flushes {
+                assert_eq!(Some(()), flush.now_or_never());
+            }
+        });
+    }
+
     #[test]
     #[should_panic(expected = "attempt to write/close at index 2 twice")]
     fn double_send() {
@@ -1,4 +1,5 @@
 use std::{
+    iter,
     marker::PhantomData,
     mem::take,
     num::NonZeroUsize,
@@ -6,7 +7,10 @@ use std::{
     task::{Context, Poll},
 };
 
-use futures::{task::Waker, Future, Stream};
+use futures::{
+    task::{waker, ArcWake, Waker},
+    Future, Stream,
+};
 use generic_array::GenericArray;
 use typenum::Unsigned;
 
@@ -31,13 +35,62 @@ pub enum Error {
     DeserializeFailed(#[from] DeserializeError),
     #[error(transparent)]
     EndOfStream(#[from] EndOfStreamError),
+    #[error("Received a {size} byte payload while waiting for {record_id:?}")]
+    MessageTooLarge { record_id: RecordId, size: usize },
+    #[error("Stream ended while waiting for {0:?}")]
+    Closed(RecordId),
+    #[error("Stream was interrupted while waiting for {record_id:?}: {inner}")]
+    Interrupted {
+        record_id: RecordId,
+        inner: BoxError,
+    },
+}
+
+/// A chunk of data pulled from the stream behind an [`UnorderedReceiver`]. Streams that can
+/// recover from errors, like the connection to a peer that reconnects, yield the error instead
+/// of data and carry on after it.
+pub trait Chunk {
+    type Data: AsRef<[u8]>;
+
+    /// ## Errors
+    /// If the stream was interrupted instead of yielding data.
+    fn into_data(self) -> Result<Self::Data, BoxError>;
+}
+
+impl Chunk for Vec<u8> {
+    type Data = Self;
+
+    fn into_data(self) -> Result<Self::Data, BoxError> {
+        Ok(self)
+    }
+}
+
+impl<T: AsRef<[u8]> + ?Sized> Chunk for &T {
+    type Data = Self;
+
+    fn into_data(self) -> Result<Self::Data, BoxError> {
+        Ok(self)
+    }
+}
+
+impl<T: AsRef<[u8]>> Chunk for Result<T, BoxError> {
+    type Data = T;
+
+    fn into_data(self) -> Result<Self::Data, BoxError> {
+        self
+    }
 }
 
 /// A future for receiving item `i` from an `UnorderedReceiver`.
+///
+/// This future is cancellation safe. All of the shared state is updated while holding the lock,
+/// within a single call to `poll`, and the read cursor only advances when a message is returned.
+/// Data pulled from the stream by a receiver that gets dropped stays buffered, and the next
+/// receiver for the same record picks it up, so no message is lost or delivered twice.
 pub struct Receiver<S, C, M>
 where
     S: Stream<Item = C> + Send,
-    C: AsRef<[u8]>,
+    C: Chunk,
     M: Message,
 {
     i: usize,
@@ -48,7 +101,7 @@ where
 impl<S, C, M> Future for Receiver<S, C, M>
 where
     S: Stream<Item = C> + Send,
-    C: AsRef<[u8]>,
+    C: Chunk,
     M: Message,
 {
     type Output = Result<M, Error>;
@@ -58,6 +111,8 @@ where
         let mut recv = this.shared_state.lock().unwrap();
         if recv.is_next(this.i) {
             recv.poll_next(cx)
+        } else if let Some(e) = recv.lost::<M>(this.i) {
+            Poll::Ready(Err(e))
         } else {
             recv.add_waker(this.i, cx.waker());
             Poll::Pending
@@ -65,6 +120,28 @@ where
     }
 }
 
+/// A future that resolves once the stream behind an [`UnorderedReceiver`] has ended, see
+/// [`UnorderedReceiver::closed`].
+pub struct Closed<S, C>
+where
+    S: Stream<Item = C>,
+    C: Chunk,
+{
+    shared_state: Arc<Mutex<OperatingState<S, C>>>,
+}
+
+impl<S, C> Future for Closed<S, C>
+where
+    S: Stream<Item = C> + Send,
+    C: Chunk,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.shared_state.lock().unwrap().poll_closed(cx)
+    }
+}
+
 /// Saved unread data from a received chunk.
 #[derive(Default)]
 struct Spare {
@@ -85,6 +162,11 @@ impl Spare {
         }
     }
 
+    /// Number of bytes that were not read yet.
+    fn len(&self) -> usize {
+        self.buf.len() - self.offset
+    }
+
     /// Replace the stored value with the given slice.
     fn replace(&mut self, v: &[u8]) {
         self.offset = 0;
@@ -124,10 +206,23 @@ impl Spare {
     }
 }
 
+/// The stream only keeps the waker it was polled with last. When a [`Closed`] future polls the
+/// stream, it uses a waker that also wakes the receiver for the next record, so that receiver
+/// does not lose the stream to it. Receivers poll the stream with their own waker.
+struct StreamWakers(Vec<Waker>);
+
+impl ArcWake for StreamWakers {
+    fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
+        for w in &arc_self.0 {
+            w.wake_by_ref();
+        }
+    }
+}
+
 pub struct OperatingState<S, C>
 where
     S: Stream<Item = C>,
-    C: AsRef<[u8]>,
+    C: Chunk,
 {
     /// The stream we're reading from.
     stream: Pin<Box<S>>,
@@ -169,13 +264,35 @@ where
     overflow_wakers: Vec<(Waker, usize)>,
     #[cfg(not(feature = "stall-detection"))]
     overflow_wakers: Vec<Waker>,
+    /// Size of the last message read from this stream. Only used to tell how many records
+    /// are sitting in `spare`.
+    #[cfg(feature = "stall-detection")]
+    msg_size: Option<usize>,
+    /// Payloads larger than this are rejected before they are copied into `spare`.
+    max_payload_size: Option<NonZeroUsize>,
+    /// Size of the first payload that was larger than `max_payload_size`. Data that follows it
+    /// can't be aligned with records anymore, so once this is set, every receive that can't be
+    /// served from `spare` fails.
+    rejected: Option<usize>,
+    /// Set once the stream has ended. Records that are not in `spare` at that point will
+    /// never arrive.
+    closed: bool,
+    /// A chunk that a [`Closed`] future pulled from the stream, while looking for its end. It
+    /// is handed to the receivers before anything else is read from the stream, so there is
+    /// never more than one chunk pulled ahead of them.
+    peeked: Option<C>,
+    /// Wakers of [`Closed`] futures that wait for `peeked` to be consumed or for the stream.
+    close_wakers: Vec<Waker>,
+    /// Waker of the receiver for `next`, if it found the stream pending. [`Closed`] futures
+    /// wake it when they take the stream over, see [`StreamWakers`].
+    next_waker: Option<Waker>,
     _marker: PhantomData<C>,
 }
 
 impl<S, C> OperatingState<S, C>
 where
     S: Stream<Item = C> + Send,
-    C: AsRef<[u8]>,
+    C: Chunk,
 {
     /// Determine whether `i` is the next record that we expect to receive.
     fn is_next(&self, i: usize) -> bool {
@@ -212,6 +329,115 @@ where
         }
     }
 
+    /// Returns the error for receiving `i`, if it can no longer be received, because the stream
+    /// has ended or a payload was rejected before delivering it. The record for `next` is not
+    /// covered here, `poll_next` reports it.
+    fn lost<M: Message>(&self, i: usize) -> Option<Error> {
+        if i <= self.next || self.spare.len() >= (i - self.next + 1) * M::Size::USIZE {
+            None
+        } else if let Some(size) = self.rejected {
+            Some(Error::MessageTooLarge {
+                record_id: RecordId::from(i),
+                size,
+            })
+        } else if self.closed {
+            Some(Error::Closed(RecordId::from(i)))
+        } else {
+            None
+        }
+    }
+
+    /// Mark the stream as ended and wake everyone waiting on it, so that receives for records
+    /// that will never arrive fail instead of hanging.
+    fn close(&mut self) {
+        self.closed = true;
+        self.wake_all();
+    }
+
+    /// Stop reading from the stream after a payload of `size` bytes was rejected, and wake
+    /// everyone waiting on it, so that their receives fail.
+    fn reject(&mut self, size: usize) {
+        self.rejected.get_or_insert(size);
+        self.wake_all();
+    }
+
+    fn wake_all(&mut self) {
+        for w in self.wakers.iter_mut().filter_map(Option::take) {
+            w.wake();
+        }
+        #[cfg(feature = "stall-detection")]
+        for (w, _) in take(&mut self.overflow_wakers) {
+            w.wake();
+        }
+        #[cfg(not(feature = "stall-detection"))]
+        for w in take(&mut self.overflow_wakers) {
+            w.wake();
+        }
+        for w in take(&mut self.close_wakers) {
+            w.wake();
+        }
+        if let Some(w) = self.next_waker.take() {
+            w.wake();
+        }
+    }
+
+    /// Poll the stream for the receiver of `next`. A [`Closed`] future that polled the stream
+    /// before no longer gets woken by it, so it is woken whenever something is pulled from the
+    /// stream here.
+    fn poll_stream(&mut self, cx: &mut Context<'_>) -> Poll<Option<C>> {
+        let r = self.stream.as_mut().poll_next(cx);
+        if r.is_pending() {
+            if let Some(w) = self.next_waker.as_mut() {
+                w.clone_from(cx.waker());
+            } else {
+                self.next_waker = Some(cx.waker().clone());
+            }
+        } else {
+            self.next_waker = None;
+            for w in take(&mut self.close_wakers) {
+                w.wake();
+            }
+        }
+        r
+    }
+
+    /// Look for the end of the stream. Unread data is never buffered here: at most one chunk is
+    /// pulled from the stream and kept in `peeked`, until the receivers consume it. Only then
+    /// the stream is polled again, so the peer's backlog stays in the stream.
+    fn poll_closed(&mut self, cx: &Context<'_>) -> Poll<()> {
+        if self.closed || self.rejected.is_some() {
+            return Poll::Ready(());
+        }
+        if self.peeked.is_none() {
+            let wakers = iter::once(cx.waker().clone())
+                .chain(self.next_waker.clone())
+                .collect();
+            let stream_waker = waker(std::sync::Arc::new(StreamWakers(wakers)));
+            match self
+                .stream
+                .as_mut()
+                .poll_next(&mut Context::from_waker(&stream_waker))
+            {
+                Poll::Pending => {}
+                Poll::Ready(Some(chunk)) => {
+                    self.peeked = Some(chunk);
+                    // The receiver for `next` may be waiting on the stream for this chunk.
+                    if let Some(w) = self.next_waker.take() {
+                        w.wake();
+                    }
+                }
+                Poll::Ready(None) => {
+                    self.close();
+                    return Poll::Ready(());
+                }
+            }
+        }
+        if !self.close_wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            self.close_wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
     /// Wake the waker from the next future, if the next receiver has been polled.
     fn wake_next(&mut self) {
         self.next += 1;
@@ -236,17 +462,58 @@ where
     /// the future for the next message is polled.
     fn poll_next<M: Message>(&mut self, cx: &mut Context<'_>) -> Poll<Result<M, Error>> {
         self.max_polled_idx = std::cmp::max(self.max_polled_idx, Some(self.next));
+        #[cfg(feature = "stall-detection")]
+        {
+            self.msg_size = Some(M::Size::USIZE);
+        }
         if let Some(m) = self.spare.read() {
             self.wake_next();
             return Poll::Ready(m.map_err(|e| DeserializeError::new::<M>(self.next, e).into()));
         }
+        if let Some(size) = self.rejected {
+            return Poll::Ready(Err(Error::MessageTooLarge {
+                record_id: RecordId::from(self.next),
+                size,
+            }));
+        }
+        if self.closed {
+            return Poll::Ready(Err(EndOfStreamError(RecordId::from(self.next)).into()));
+        }
 
         loop {
-            match self.stream.as_mut().poll_next(cx) {
-                Poll::Pending => {
-                    return Poll::Pending;
+            let chunk = if let Some(chunk) = self.peeked.take() {
+                // `Closed` futures wait for this chunk to be consumed, before polling the stream.
+                for w in take(&mut self.close_wakers) {
+                    w.wake();
                 }
-                Poll::Ready(Some(b)) => {
+                Poll::Ready(Some(chunk))
+            } else {
+                self.poll_stream(cx)
+            };
+            match chunk {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(chunk)) => {
+                    // The stream goes on after an interruption, so only this receive fails.
+                    let b = match chunk.into_data() {
+                        Ok(b) => b,
+                        Err(inner) => {
+                            return Poll::Ready(Err(Error::Interrupted {
+                                record_id: RecordId::from(self.next),
+                                inner,
+                            }))
+                        }
+                    };
+                    let size = b.as_ref().len();
+                    if self
+                        .max_payload_size
+                        .is_some_and(|limit| size > limit.get())
+                    {
+                        self.reject(size);
+                        return Poll::Ready(Err(Error::MessageTooLarge {
+                            record_id: RecordId::from(self.next),
+                            size,
+                        }));
+                    }
                     if let Some(m) = self.spare.extend(b.as_ref()) {
                         self.wake_next();
                         return Poll::Ready(
@@ -255,7 +522,8 @@ where
                     }
                 }
                 Poll::Ready(None) => {
-                    return Poll::Ready(Err(EndOfStreamError(RecordId::from(self.next)).into()))
+                    self.close();
+                    return Poll::Ready(Err(EndOfStreamError(RecordId::from(self.next)).into()));
                 }
             }
         }
@@ -279,6 +547,17 @@ where
             // include `self.next` if it was ever polled
             .chain(self.max_polled_idx.into_iter().filter(|v| *v == self.next))
     }
+
+    /// Records that were pulled from the stream but not requested yet. Data is pulled in chunks,
+    /// so a chunk that completes record `next` may also carry the records that follow it.
+    #[cfg(feature = "stall-detection")]
+    fn buffered(&self) -> std::ops::Range<usize> {
+        let count = self
+            .msg_size
+            .filter(|&sz| sz > 0)
+            .map_or(0, |sz| (self.spare.buf.len() - self.spare.offset) / sz);
+        self.next..self.next + count
+    }
 }
 
 /// Take an ordered stream of bytes and make messages from that stream
@@ -286,7 +565,7 @@ where
 pub struct UnorderedReceiver<S, C>
 where
     S: Stream<Item = C>,
-    C: AsRef<[u8]>,
+    C: Chunk,
 {
     inner: Arc<Mutex<OperatingState<S, C>>>,
 }
@@ -295,7 +574,7 @@ where
 impl<S, C> UnorderedReceiver<S, C>
 where
     S: Stream<Item = C> + Send,
-    C: AsRef<[u8]>,
+    C: Chunk,
 {
     /// Wrap a stream for unordered reading.
     ///
@@ -317,11 +596,29 @@ where
                 spare: Spare::default(),
                 wakers,
                 overflow_wakers: Vec::new(),
+                #[cfg(feature = "stall-detection")]
+                msg_size: None,
+                max_payload_size: None,
+                rejected: None,
+                closed: false,
+                peeked: None,
+                close_wakers: Vec::new(),
+                next_waker: None,
                 _marker: PhantomData,
             })),
         }
     }
 
+    /// Rejects payloads pulled from the stream that are larger than `limit` bytes. A payload
+    /// may carry several records. Rejected payloads are dropped without being buffered. Records
+    /// that follow them can no longer be aligned, so every receive that is not already buffered
+    /// fails after that, including the ones that are pending.
+    #[must_use]
+    pub fn with_max_payload_size(self, limit: Option<NonZeroUsize>) -> Self {
+        self.inner.lock().unwrap().max_payload_size = limit;
+        self
+    }
+
     /// Receive from the stream at index `i`.
     ///
     /// # Panics
@@ -337,6 +634,18 @@ where
         }
     }
 
+    /// Returns a future that resolves once the stream has ended, or can't be read anymore
+    /// because a payload was rejected. It polls the stream itself, so the end of the stream is
+    /// noticed even if nobody is waiting for the next record. It does not buffer unread data
+    /// though: if the peer sent records that were not received yet, the end is only noticed
+    /// once they are. After that, receives for records that did not arrive fail, including the
+    /// ones that are already pending.
+    pub fn closed(&self) -> Closed<S, C> {
+        Closed {
+            shared_state: Arc::clone(&self.inner),
+        }
+    }
+
     #[cfg(feature = "stall-detection")]
     pub fn waiting(&self) -> Vec<usize> {
         let state = self.inner.lock().unwrap();
@@ -346,12 +655,18 @@ where
 
         r
     }
+
+    /// Indices of the records that were received from the peer but not consumed yet.
+    #[cfg(feature = "stall-detection")]
+    pub fn buffered(&self) -> Vec<usize> {
+        self.inner.lock().unwrap().buffered().collect()
+    }
 }
 
 impl<S, C> Clone for UnorderedReceiver<S, C>
 where
     S: Stream<Item = C> + Send,
-    C: AsRef<[u8]>,
+    C: Chunk,
 {
     fn clone(&self) -> Self {
         Self {
@@ -384,15 +699,17 @@ mod test {
     use typenum::Unsigned;
 
     use crate::{
+        error::BoxError,
         ff::{Fp31, Fp32BitPrime, Serializable, U128Conversions},
-        helpers::buffers::unordered_receiver::UnorderedReceiver,
+        helpers::buffers::unordered_receiver::{Chunk, Error, UnorderedReceiver},
+        protocol::RecordId,
     };
 
     fn receiver<I, T>(it: I) -> UnorderedReceiver<impl Stream<Item = T>, T>
     where
         I: IntoIterator<Item = T> + 'static,
         I::IntoIter: Send,
-        T: AsRef<[u8]> + 'static,
+        T: Chunk + 'static,
     {
         // Use a small capacity so that we can overflow it easily.
         let capacity = NonZeroUsize::new(3).unwrap();
@@ -578,4 +895,140 @@ mod test {
             }
         });
     }
+
+    /// A payload above the limit fails the pending receive and is not kept around. Nothing
+    /// after it can be received, even if it fits.
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn payload_too_large() {
+        use futures::FutureExt;
+
+        const DATA: &[&[u8]] = &[&[18], &[1, 2, 3, 4, 5], &[6]];
+        let recv = receiver(DATA).with_max_payload_size(NonZeroUsize::new(4));
+        let f: Fp31 = recv.recv(0_usize).now_or_never().unwrap().unwrap();
+        assert_eq!(f, Fp31::try_from(18).unwrap());
+
+        let mut pending = Box::pin(recv.recv::<Fp31, _>(2_usize));
+        assert!(pending.as_mut().now_or_never().is_none());
+
+        let results = [
+            (1, recv.recv::<Fp31, _>(1_usize).now_or_never().unwrap()),
+            (1, recv.recv::<Fp31, _>(1_usize).now_or_never().unwrap()),
+            (2, pending.now_or_never().unwrap()),
+        ];
+        for (expected, result) in results {
+            let err = result.unwrap_err();
+            let Error::MessageTooLarge { record_id, size } = err else {
+                panic!("unexpected error: {err:?}");
+            };
+            assert_eq!((RecordId::from(expected), 5), (record_id, size));
+        }
+        assert!(recv.inner.lock().unwrap().spare.buf.is_empty());
+    }
+
+    /// Waiting for the end of the stream does not pull the peer's backlog into memory: no more
+    /// than one chunk is taken from the stream ahead of the receivers.
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn closed_with_backlog() {
+        use futures::FutureExt;
+
+        const COUNT: usize = 10_000;
+        let data = (0..COUNT)
+            .map(|i| vec![u8::try_from(i % 31).unwrap()])
+            .collect::<Vec<_>>();
+        let recv = receiver(data);
+        let mut closed = Box::pin(recv.closed());
+
+        for i in 0..COUNT {
+            assert!(closed.as_mut().now_or_never().is_none());
+            {
+                let state = recv.inner.lock().unwrap();
+                assert_eq!(0, state.spare.len());
+                assert!(state.peeked.is_some());
+            }
+            let f: Fp31 = recv.recv(i).now_or_never().unwrap().unwrap();
+            assert_eq!(Fp31::truncate_from(u128::try_from(i % 31).unwrap()), f);
+        }
+        closed.now_or_never().unwrap();
+        assert!(matches!(
+            recv.recv::<Fp31, _>(COUNT).now_or_never().unwrap(),
+            Err(Error::EndOfStream(_))
+        ));
+    }
+
+    /// An interruption fails the receive that is waiting for data. The stream carries on after
+    /// it, so receiving the same record again picks up the data that follows.
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn interrupted() {
+        use futures::FutureExt;
+
+        let recv = receiver(vec![
+            Ok(vec![18]),
+            Err(BoxError::from("connection reset")),
+            Ok(vec![7]),
+        ]);
+        let f: Fp31 = recv.recv(0_usize).now_or_never().unwrap().unwrap();
+        assert_eq!(f, Fp31::try_from(18).unwrap());
+
+        let err = recv
+            .recv::<Fp31, _>(1_usize)
+            .now_or_never()
+            .unwrap()
+            .unwrap_err();
+        let Error::Interrupted { record_id, .. } = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(RecordId::from(1), record_id);
+
+        let f: Fp31 = recv.recv(1_usize).now_or_never().unwrap().unwrap();
+        assert_eq!(f, Fp31::try_from(7).unwrap());
+    }
+
+    /// Drop receive futures at random points and make sure that every message is still delivered
+    /// exactly once, in order.
+    #[test]
+    #[cfg(not(feature = "shuttle"))]
+    fn cancellation() {
+        use futures::{channel::mpsc, FutureExt};
+
+        const COUNT: usize = 10;
+        const SZ: usize = <<Fp32BitPrime as Serializable>::Size as Unsigned>::USIZE;
+
+        let mut rng = crate::rand::thread_rng();
+        let values = (0..COUNT)
+            .map(|_| rng.gen::<Fp32BitPrime>())
+            .collect::<Vec<_>>();
+        let mut encoded = vec![0; COUNT * SZ];
+        for (v, buf) in values.iter().zip(encoded.chunks_exact_mut(SZ)) {
+            v.serialize(GenericArray::from_mut_slice(buf));
+        }
+
+        let (tx, rx) = mpsc::unbounded();
+        let recv = UnorderedReceiver::new(Box::pin(rx), NonZeroUsize::new(3).unwrap());
+        let mut received = Vec::with_capacity(COUNT);
+        // chunks do not line up with records, so some of them only fill the buffer
+        for chunk in encoded.chunks(SZ - 1) {
+            tx.unbounded_send(chunk.to_vec()).unwrap();
+            // a receiver that is further ahead gets polled and dropped
+            if received.len() + 1 < COUNT {
+                assert!(recv
+                    .recv::<Fp32BitPrime, _>(received.len() + 1)
+                    .now_or_never()
+                    .is_none());
+            }
+            while rng.gen::<bool>() {
+                match recv.recv::<Fp32BitPrime, _>(received.len()).now_or_never() {
+                    Some(v) => received.push(v.unwrap()),
+                    None => break,
+                }
+            }
+        }
+        while received.len() < COUNT {
+            received.push(recv.recv(received.len()).now_or_never().unwrap().unwrap());
+        }
+
+        assert_eq!(values, received);
+    }
 }
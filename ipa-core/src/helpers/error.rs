@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 use crate::{
+    error::BoxError,
     helpers::{
         buffers::{DeserializeError, EndOfStreamError},
         ChannelId, TotalRecords, TransportIdentity,
@@ -27,4 +28,60 @@ pub enum Error<I: TransportIdentity> {
         channel_id: ChannelId<I>,
         total_records: TotalRecords,
     },
+    #[error(
+        "record ID {record_id:?} cannot be sent to {channel_id:?}: channel is closed for sending"
+    )]
+    SendAfterClose {
+        record_id: RecordId,
+        channel_id: ChannelId<I>,
+    },
+    #[error(
+        "record ID {record_id:?} cannot be sent to {channel_id:?}: helper can't send to itself"
+    )]
+    SelfSend {
+        record_id: RecordId,
+        channel_id: ChannelId<I>,
+    },
+    #[error("cannot receive record ID {record_id:?} from {channel_id:?}: {limit} receives are already pending")]
+    TooManyPendingReceives {
+        record_id: RecordId,
+        channel_id: ChannelId<I>,
+        limit: usize,
+    },
+    #[error("cannot receive record ID {record_id:?}: peer sent a {size} byte payload, above the configured limit")]
+    MessageTooLarge { record_id: RecordId, size: usize },
+    #[error("query aborted while receiving from {channel_id:?}: {reason}")]
+    Aborted {
+        channel_id: ChannelId<I>,
+        reason: String,
+    },
+    #[error("cannot receive record ID {record_id:?}: {channel_id:?} was closed by the peer before sending it")]
+    PeerClosed {
+        record_id: RecordId,
+        channel_id: ChannelId<I>,
+    },
+    #[error("cannot use {channel_id:?}: gateway is shut down")]
+    ShutDown { channel_id: ChannelId<I> },
+    #[error("receive of record ID {record_id:?} from {channel_id:?} was interrupted: {inner}")]
+    Interrupted {
+        record_id: RecordId,
+        channel_id: ChannelId<I>,
+        inner: BoxError,
+    },
+}
+
+impl<I: TransportIdentity> Error<I> {
+    /// Returns `true` if the operation that failed with this error may succeed when it is
+    /// attempted again, because the channel is still usable. A receive that was turned down
+    /// because too many receives are in flight succeeds once some of them complete, and one
+    /// that was interrupted by the transport, e.g. while it reconnects to the peer, picks up the
+    /// data that arrives after the interruption. Other errors that come from the stream, like the
+    /// peer closing it, are permanent, because a closed stream is never reopened.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::TooManyPendingReceives { .. } | Self::Interrupted { .. }
+        )
+    }
 }
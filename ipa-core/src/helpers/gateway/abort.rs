@@ -0,0 +1,144 @@
+use std::{convert::Infallible, future::Future};
+
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture, Shared},
+    FutureExt,
+};
+use generic_array::GenericArray;
+use typenum::{Unsigned, U64};
+
+use crate::{
+    ff::Serializable,
+    helpers::{gateway::receive::UR, MpcMessage, Role},
+    protocol::{step::Step, RecordId},
+    sync::{Arc, Mutex},
+};
+
+/// Step of the control channel that helpers use to tell each other that they aborted the query.
+/// It hangs off the root gate, so it does not collide with protocol steps. Every helper listens
+/// on it for the lifetime of its gateway.
+pub(crate) struct AbortStep;
+
+impl AsRef<str> for AbortStep {
+    fn as_ref(&self) -> &str {
+        "abort"
+    }
+}
+
+impl Step for AbortStep {}
+
+/// The only message ever sent on the [`AbortStep`] channel. It carries the reason the sender
+/// aborted with, truncated to 64 bytes.
+#[derive(Debug)]
+pub(super) struct AbortReason(GenericArray<u8, U64>);
+
+impl AbortReason {
+    pub fn new(reason: &str) -> Self {
+        let mut len = reason.len().min(U64::USIZE);
+        while !reason.is_char_boundary(len) {
+            len -= 1;
+        }
+        let mut buf = GenericArray::default();
+        buf[..len].copy_from_slice(&reason.as_bytes()[..len]);
+
+        Self(buf)
+    }
+
+    fn to_str(&self) -> String {
+        let len = self.0.iter().position(|&b| b == 0).unwrap_or(U64::USIZE);
+        String::from_utf8_lossy(&self.0[..len]).into_owned()
+    }
+}
+
+impl Serializable for AbortReason {
+    type Size = U64;
+    type DeserializationError = Infallible;
+
+    fn serialize(&self, buf: &mut GenericArray<u8, Self::Size>) {
+        buf.copy_from_slice(&self.0);
+    }
+
+    fn deserialize(buf: &GenericArray<u8, Self::Size>) -> Result<Self, Self::DeserializationError> {
+        Ok(Self(buf.clone()))
+    }
+}
+
+impl MpcMessage for AbortReason {}
+
+/// Shared between the gateway and all of its receiving ends. Once triggered, every receive that is
+/// pending or started afterwards resolves with [`Error::Aborted`] instead of waiting for data that
+/// may never arrive.
+///
+/// The signal is triggered either locally, by [`Self::abort`], or by one of the peers sending an
+/// [`AbortReason`] on the control channel.
+///
+/// [`Error::Aborted`]: crate::helpers::Error::Aborted
+#[derive(Clone)]
+pub(super) struct AbortSignal {
+    tx: Arc<Mutex<Option<oneshot::Sender<Arc<str>>>>>,
+    rx: Shared<BoxFuture<'static, Arc<str>>>,
+}
+
+impl Default for AbortSignal {
+    fn default() -> Self {
+        Self::new(future::pending())
+    }
+}
+
+impl AbortSignal {
+    /// Creates a signal that is also triggered when `peers` resolves.
+    pub fn new<F: Future<Output = Arc<str>> + Send + 'static>(peers: F) -> Self {
+        let (tx, rx) = oneshot::channel();
+        let local = async move {
+            match rx.await {
+                Ok(reason) => reason,
+                Err(oneshot::Canceled) => future::pending().await,
+            }
+        };
+        Self {
+            tx: Arc::new(Mutex::new(Some(tx))),
+            rx: future::select(local.boxed(), peers.boxed())
+                .map(|either| either.factor_first().0)
+                .boxed()
+                .shared(),
+        }
+    }
+
+    /// Triggers the signal. Only the first reason is kept, subsequent calls have no effect, and
+    /// neither do calls made after a peer aborted. Returns `true` if this call triggered the signal.
+    pub fn abort(&self, reason: &str) -> bool {
+        if self.rx.peek().is_some() {
+            return false;
+        }
+        if let Some(tx) = self.tx.lock().unwrap().take() {
+            // receivers hold a clone of this signal, so there is always someone listening
+            let _ = tx.send(Arc::from(reason));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Resolves with the abort reason once the signal is triggered, stays pending otherwise.
+    pub fn aborted(&self) -> impl Future<Output = Arc<str>> {
+        self.rx.clone()
+    }
+}
+
+/// Resolves once one of the peers sends an [`AbortReason`] on the given control channels. A peer
+/// that closes its control channel finished the query without aborting it.
+pub(super) fn peer_aborts(channels: [(Role, UR); 2]) -> impl Future<Output = Arc<str>> + Send {
+    future::select_all(channels.map(|(peer, rx)| {
+        async move {
+            match rx.recv::<AbortReason, _>(RecordId::FIRST).await {
+                Ok(reason) => {
+                    Arc::from(format!("{peer:?} aborted the query: {}", reason.to_str()).as_str())
+                }
+                Err(_) => future::pending().await,
+            }
+        }
+        .boxed()
+    }))
+    .map(|(reason, _, _)| reason)
+}
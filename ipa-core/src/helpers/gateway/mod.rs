@@ -1,11 +1,21 @@
+mod abort;
 mod receive;
+mod rounds;
 mod send;
 #[cfg(feature = "stall-detection")]
 pub(super) mod stall_detection;
+mod startup;
 mod transport;
 
-use std::num::NonZeroUsize;
+use std::{
+    collections::BTreeSet, convert::Infallible, fmt::Debug, marker::PhantomData, num::NonZeroUsize,
+};
+
+use generic_array::GenericArray;
 
+pub(crate) use abort::AbortStep;
+#[cfg(feature = "stall-detection")]
+pub use receive::ChannelSnapshot;
 pub(super) use receive::{MpcReceivingEnd, ShardReceivingEnd};
 pub(super) use send::SendingEnd;
 #[cfg(feature = "stall-detection")]
@@ -13,17 +23,24 @@ pub(super) use stall_detection::InstrumentedGateway;
 pub use transport::RoleResolvingTransport;
 
 use crate::{
+    ff::Serializable,
     helpers::{
         buffers::UnorderedReceiver,
         gateway::{
-            receive::{GatewayReceivers, ShardReceiveStream, UR},
+            abort::{peer_aborts, AbortReason, AbortSignal},
+            receive::{GatewayReceivers, PendingReceives, ShardReceiveStream, UR},
+            rounds::RoundCounter,
             send::GatewaySenders,
+            startup::StartupCheck,
             transport::Transports,
         },
-        HelperChannelId, LogErrors, Message, MpcMessage, RecordsStream, Role, RoleAssignment,
+        Direction, HelperChannelId, Message, MpcMessage, RecordsStream, Role, RoleAssignment,
         ShardChannelId, TotalRecords, Transport,
     },
-    protocol::QueryId,
+    protocol::{
+        step::{Gate, StepNarrow},
+        QueryId, RecordId,
+    },
     sharding::ShardIndex,
     sync::{Arc, Mutex},
 };
@@ -48,6 +65,20 @@ pub type ShardTransportImpl = crate::net::HttpShardTransport;
 pub type MpcTransportError = <MpcTransportImpl as Transport>::Error;
 
 /// Gateway into IPA Network infrastructure. It allows helpers send and receive messages.
+///
+/// There is no background event loop behind the gateway. Channels are created lazily on the
+/// calling task on top of the transports the gateway was created with. The gateway stays alive as
+/// long as those transports are connected and, if configured, it passed its startup health check
+/// (see [`Self::is_alive`]). Once it is not alive, e.g. because the network was torn down,
+/// channels can no longer be opened. Every send and receive on a channel requested after that
+/// fails with [`Error::ShutDown`] instead of never delivering anything, and the `try_` variants
+/// of the channel getters report it right away.
+///
+/// Channels are identified by peer and gate, and the gateway opens them on the transport under its
+/// query id. Several queries served over the same transports must use gateways with distinct query
+/// ids, otherwise their channels collide on the same gate (see [`QueryId`]).
+///
+/// [`Error::ShutDown`]: crate::helpers::Error::ShutDown
 pub struct Gateway {
     config: GatewayConfig,
     transports: Transports<RoleResolvingTransport, ShardTransportImpl>,
@@ -58,12 +89,40 @@ pub struct Gateway {
     inner: State,
 }
 
+/// Serialized form of a single `M`, used to forward messages between channels without
+/// deserializing and serializing them again.
+struct RawMessage<M: Message>(GenericArray<u8, M::Size>, PhantomData<fn() -> M>);
+
+impl<M: Message> Debug for RawMessage<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RawMessage({:?})", self.0.as_slice())
+    }
+}
+
+impl<M: Message> Serializable for RawMessage<M> {
+    type Size = M::Size;
+    type DeserializationError = Infallible;
+
+    fn serialize(&self, buf: &mut GenericArray<u8, Self::Size>) {
+        buf.copy_from_slice(&self.0);
+    }
+
+    fn deserialize(buf: &GenericArray<u8, Self::Size>) -> Result<Self, Self::DeserializationError> {
+        Ok(Self(buf.clone(), PhantomData))
+    }
+}
+
 #[derive(Default)]
 pub struct State {
     mpc_senders: GatewaySenders<Role>,
     mpc_receivers: GatewayReceivers<Role, UR>,
     shard_senders: GatewaySenders<ShardIndex>,
     shard_receivers: GatewayReceivers<ShardIndex, ShardReceiveStream>,
+    /// Only set if [`GatewayConfig::abortable`] is.
+    abort: Option<AbortSignal>,
+    pending_receives: PendingReceives,
+    rounds: RoundCounter,
+    startup: StartupCheck,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -75,8 +134,45 @@ pub struct GatewayConfig {
     /// Time to wait before checking gateway progress. If no progress has been made between
     /// checks, the gateway is considered to be stalled and will create a report with outstanding
     /// send/receive requests
+    ///
+    /// A peer stream that never yields, e.g. because the network is misconfigured, surfaces here
+    /// as a receive request that makes no progress. Use [`Self::startup_deadline`] to shut down
+    /// the gateway when that happens.
     #[cfg(feature = "stall-detection")]
     pub progress_check_interval: std::time::Duration,
+
+    /// Maximum number of MPC receives that can be pending at the same time, across all channels.
+    /// Receives beyond this limit fail with [`Error::TooManyPendingReceives`] instead of being
+    /// queued, which guards against protocols that request far more records than they can
+    /// consume. Unlimited if not set.
+    ///
+    /// [`Error::TooManyPendingReceives`]: crate::helpers::Error::TooManyPendingReceives
+    pub max_pending_receives: Option<NonZeroUsize>,
+
+    /// Maximum size, in bytes, of a single payload received from a peer helper. A payload may
+    /// carry several records, so this must not be lower than what peers send at once. Larger
+    /// payloads are rejected with [`Error::MessageTooLarge`] before they are buffered, protecting
+    /// against peers that try to exhaust memory. The channel that received such a payload fails
+    /// all receives after it. Unlimited if not set.
+    ///
+    /// [`Error::MessageTooLarge`]: crate::helpers::Error::MessageTooLarge
+    pub max_payload_size: Option<NonZeroUsize>,
+
+    /// Startup health check. If set, the gateway must send its first message to, or receive its
+    /// first message from, a peer within this time after it is created. Otherwise it logs an
+    /// error and shuts down: receives that are pending at the deadline and all sends and receives
+    /// after it fail with [`Error::ShutDown`], which surfaces a misconfigured network early
+    /// instead of leaving receives hanging. Choose it generously, as a helper may legitimately
+    /// wait for its peers to finish local work before it has anything to exchange with them. Not
+    /// checked if not set.
+    ///
+    /// [`Error::ShutDown`]: crate::helpers::Error::ShutDown
+    pub startup_deadline: Option<std::time::Duration>,
+
+    /// Enables [`Gateway::abort`]. An abortable gateway listens for aborts from its peers on a
+    /// dedicated control channel, and every MPC receive also watches for the query to be
+    /// aborted, which adds some overhead to it. Off by default.
+    pub abortable: bool,
 }
 
 impl Gateway {
@@ -88,18 +184,35 @@ impl Gateway {
         mpc_transport: MpcTransportImpl,
         shard_transport: ShardTransportImpl,
     ) -> Self {
+        let transports = Transports {
+            mpc: RoleResolvingTransport {
+                roles,
+                inner: mpc_transport,
+            },
+            shard: shard_transport,
+        };
+        let role = transports.mpc.identity();
+        let abort = config.abortable.then(|| {
+            let channels = [Direction::Left, Direction::Right].map(|direction| {
+                let channel_id = HelperChannelId::new(role.peer(direction), abort_gate());
+                let rx = open_mpc_receiver(&transports.mpc, query_id, &config, &channel_id);
+                (channel_id.peer, rx)
+            });
+            AbortSignal::new(peer_aborts(channels))
+        });
+
         #[allow(clippy::useless_conversion)] // not useless in stall-detection build
         Self {
             query_id,
             config,
-            transports: Transports {
-                mpc: RoleResolvingTransport {
-                    roles,
-                    inner: mpc_transport,
-                },
-                shard: shard_transport,
-            },
-            inner: State::default().into(),
+            transports,
+            inner: State {
+                abort,
+                pending_receives: PendingReceives::new(config.max_pending_receives),
+                startup: StartupCheck::new(config.startup_deadline),
+                ..State::default()
+            }
+            .into(),
         }
     }
 
@@ -108,14 +221,107 @@ impl Gateway {
         self.transports.mpc.identity()
     }
 
+    /// Returns the role of the helper to the left of this one, following [`Role::peer`].
+    #[must_use]
+    pub fn left(&self) -> Role {
+        self.role().peer(Direction::Left)
+    }
+
+    /// Returns the role of the helper to the right of this one, following [`Role::peer`].
+    #[must_use]
+    pub fn right(&self) -> Role {
+        self.role().peer(Direction::Right)
+    }
+
     #[must_use]
     pub fn config(&self) -> &GatewayConfig {
         &self.config
     }
 
+    /// Aborts the query on this helper and tells both peers to abort it too. Every MPC receive
+    /// that is pending or started afterwards returns [`Error::Aborted`], so protocol tasks
+    /// waiting for data that will never arrive unwind promptly. This helper reports the given
+    /// reason, the peers report which helper aborted and the first 64 bytes of its reason. Only
+    /// the first reason is kept, and peers are only notified once.
+    ///
+    /// Peers are notified through a dedicated control step ([`AbortStep`]) that every abortable
+    /// gateway listens on from the moment it is created. That channel is not blocked by data
+    /// buffered on other channels. A peer that already finished the query and dropped its
+    /// gateway is not notified.
+    ///
+    /// This has no effect unless the gateway is [abortable](GatewayConfig::abortable), and all
+    /// three helpers must agree on that setting.
+    ///
+    /// [`Error::Aborted`]: crate::helpers::Error::Aborted
+    pub async fn abort(&self, reason: &str) {
+        let Some(abort) = &self.inner.abort else {
+            tracing::warn!("cannot abort the query, the gateway is not abortable: {reason}");
+            return;
+        };
+        if abort.abort(reason) {
+            let message = AbortReason::new(reason);
+            for peer in [self.left(), self.right()] {
+                let channel_id = HelperChannelId::new(peer, abort_gate());
+                // This helper is aborting anyway, there is nothing to do if this gateway is shut
+                // down or the peer is gone.
+                if let Ok(sender) =
+                    self.try_get_mpc_sender::<AbortReason>(&channel_id, TotalRecords::ONE)
+                {
+                    let _ = sender.send(RecordId::FIRST, &message).await;
+                }
+            }
+        }
+    }
+
+    /// Returns the number of communication rounds this helper has made with its MPC peers so far.
+    /// A round starts with the first send that follows a completed receive, so sending on any
+    /// number of channels before waiting for a peer counts as a single round.
+    ///
+    /// The count is only meaningful when the protocol processes records one after another.
+    /// Sends and receives of unrelated records that run concurrently interleave, which makes
+    /// this helper appear to make more rounds than the protocol depth.
+    #[must_use]
+    pub fn rounds(&self) -> usize {
+        self.inner.rounds.get()
+    }
+
+    /// Lists MPC channels opened on this helper so far, for sending or receiving, sorted by peer
+    /// and gate. A channel is opened the first time a sender or a receiver is requested for it.
+    #[must_use]
+    pub fn active_channels(&self) -> Vec<HelperChannelId> {
+        let senders = self.inner.mpc_senders.inner.iter().map(|e| e.key().clone());
+        let receivers = self
+            .inner
+            .mpc_receivers
+            .inner
+            .iter()
+            .map(|e| e.key().clone());
+
+        senders
+            .chain(receivers)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Reports, for every MPC channel this helper receives on, which records arrived but were
+    /// not consumed yet and which records were requested but have not arrived. Useful to figure
+    /// out in what order a stuck protocol processes records; channels are sorted by peer and gate.
+    ///
+    /// Only data the receiver has already pulled off the transport is reported as received.
+    #[cfg(feature = "stall-detection")]
+    #[must_use]
+    pub fn debug_snapshot(&self) -> Vec<ChannelSnapshot> {
+        self.inner.mpc_receivers.snapshot()
+    }
+
     /// Returns a sender suitable for sending data between MPC helpers. The data must be approved
     /// for sending by implementing [`MpcMessage`] trait.
     ///
+    /// Every channel is backed by its own stream. Control messages that must not wait behind
+    /// the data buffered on other channels to the same peer can be sent with
+    /// [`send::SendingEnd::send_priority`].
+    ///
     /// Do not remove the test below, it verifies that we don't allow raw sharings to be sent
     /// between MPC helpers without using secure reveal.
     ///
@@ -128,14 +334,43 @@ impl Gateway {
     /// let mpc_channel = gateway.get_mpc_sender::<AdditiveShare<Fp32BitPrime>>(todo!(), todo!());
     /// ```
     ///
+    /// If the gateway is no longer alive, the returned channel is not connected to the peer and
+    /// every send on it fails with [`Error::ShutDown`]. Use [`Self::try_get_mpc_sender`] to find
+    /// out when requesting the channel.
+    ///
     /// ## Panics
-    /// If there is a failure connecting via HTTP
+    /// If there is a failure connecting via HTTP.
+    ///
+    /// [`Error::ShutDown`]: crate::helpers::Error::ShutDown
     #[must_use]
     pub fn get_mpc_sender<M: MpcMessage>(
         &self,
         channel_id: &HelperChannelId,
         total_records: TotalRecords,
     ) -> send::SendingEnd<Role, M> {
+        self.try_get_mpc_sender(channel_id, total_records)
+            .unwrap_or_else(|_| {
+                let channel = GatewaySenders::detached::<M>(
+                    channel_id,
+                    self.config.active_work(),
+                    total_records,
+                );
+                send::SendingEnd::new(channel, self.role()).shut_down()
+            })
+    }
+
+    /// Same as [`Self::get_mpc_sender`], but reports a gateway that is no longer alive as an error.
+    ///
+    /// ## Errors
+    /// [`Error::ShutDown`] if the gateway is no longer alive, see [`Self::is_alive`].
+    ///
+    /// [`Error::ShutDown`]: crate::helpers::Error::ShutDown
+    pub fn try_get_mpc_sender<M: MpcMessage>(
+        &self,
+        channel_id: &HelperChannelId,
+        total_records: TotalRecords,
+    ) -> Result<send::SendingEnd<Role, M>, crate::helpers::Error<Role>> {
+        self.check_alive(channel_id)?;
         let transport = &self.transports.mpc;
         let channel = self.inner.mpc_senders.get::<M, _>(
             channel_id,
@@ -145,7 +380,9 @@ impl Gateway {
             total_records,
         );
 
-        send::SendingEnd::new(channel, transport.identity())
+        Ok(send::SendingEnd::new(channel, transport.identity())
+            .with_rounds(self.inner.rounds.clone())
+            .with_startup(self.inner.startup.clone()))
     }
 
     /// Returns a sender for shard-to-shard traffic. This sender is more relaxed compared to one
@@ -170,27 +407,100 @@ impl Gateway {
         );
 
         send::SendingEnd::new(channel, transport.identity())
+            .with_startup(self.inner.startup.clone())
     }
 
+    /// If the gateway is no longer alive, the returned channel is not connected to the peer and
+    /// every receive on it fails with [`Error::ShutDown`]. Use [`Self::try_get_mpc_receiver`] to
+    /// find out when requesting the channel.
+    ///
+    /// [`Error::ShutDown`]: crate::helpers::Error::ShutDown
     #[must_use]
     pub fn get_mpc_receiver<M: MpcMessage>(
         &self,
         channel_id: &HelperChannelId,
+    ) -> receive::MpcReceivingEnd<M> {
+        let rx = self.is_alive().then(|| self.mpc_receiver(channel_id));
+        self.mpc_receiving_end(channel_id, rx)
+    }
+
+    /// Same as [`Self::get_mpc_receiver`], but reports a gateway that is no longer alive as an
+    /// error.
+    ///
+    /// ## Errors
+    /// [`Error::ShutDown`] if the gateway is no longer alive, see [`Self::is_alive`].
+    ///
+    /// [`Error::ShutDown`]: crate::helpers::Error::ShutDown
+    pub fn try_get_mpc_receiver<M: MpcMessage>(
+        &self,
+        channel_id: &HelperChannelId,
+    ) -> Result<receive::MpcReceivingEnd<M>, crate::helpers::Error<Role>> {
+        self.check_alive(channel_id)?;
+        Ok(self.mpc_receiving_end(channel_id, Some(self.mpc_receiver(channel_id))))
+    }
+
+    fn mpc_receiving_end<M: MpcMessage>(
+        &self,
+        channel_id: &HelperChannelId,
+        rx: Option<UR>,
     ) -> receive::MpcReceivingEnd<M> {
         receive::MpcReceivingEnd::new(
             channel_id.clone(),
-            self.inner.mpc_receivers.get_or_create(channel_id, || {
-                UnorderedReceiver::new(
-                    Box::pin(LogErrors::new(self.transports.mpc.receive(
-                        channel_id.peer,
-                        (self.query_id, channel_id.gate.clone()),
-                    ))),
-                    self.config.active_work(),
-                )
-            }),
+            rx,
+            self.inner.abort.clone(),
+            self.inner.pending_receives.clone(),
+            self.inner.rounds.clone(),
+            self.inner.startup.clone(),
         )
     }
 
+    /// Returns `false` once this gateway can no longer reach its peers, because its transports
+    /// are gone or because nothing was sent to or received from them before the
+    /// [startup deadline](GatewayConfig::startup_deadline). Channels cannot be opened on a
+    /// gateway that is not alive.
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        self.transports.mpc.is_connected()
+            && self.transports.shard.is_connected()
+            && self.inner.startup.passed()
+    }
+
+    fn check_alive(&self, channel_id: &HelperChannelId) -> Result<(), crate::helpers::Error<Role>> {
+        if self.is_alive() {
+            Ok(())
+        } else {
+            Err(crate::helpers::Error::ShutDown {
+                channel_id: channel_id.clone(),
+            })
+        }
+    }
+
+    /// Opens receiving channels from both peers for each of the given gates ahead of time, so
+    /// that the first receive on them does not pay for setting up the stream. Channels that
+    /// are already open are left as is. For the in-memory transport this only registers the
+    /// channels.
+    ///
+    /// Sending channels are not opened, because their buffers are sized by the message type and
+    /// the number of records, which are only known when a sender is requested.
+    pub fn prewarm(&self, gates: &[Gate]) {
+        for gate in gates {
+            for peer in [self.left(), self.right()] {
+                self.mpc_receiver(&HelperChannelId::new(peer, gate.clone()));
+            }
+        }
+    }
+
+    fn mpc_receiver(&self, channel_id: &HelperChannelId) -> UR {
+        self.inner.mpc_receivers.get_or_create(channel_id, || {
+            open_mpc_receiver(
+                &self.transports.mpc,
+                self.query_id,
+                &self.config,
+                channel_id,
+            )
+        })
+    }
+
     /// Requests a stream of records to be received from the given shard. In contrast with
     /// [`Self::get_mpc_receiver`] stream, items in this stream are available in FIFO order only.
     pub fn get_shard_receiver<M: Message>(
@@ -215,10 +525,29 @@ impl Gateway {
         receive::ShardReceivingEnd {
             channel_id: channel_id.clone(),
             rx: RecordsStream::new(rx),
+            startup: self.inner.startup.clone(),
         }
     }
 }
 
+/// Gate of the control channel used by [`Gateway::abort`].
+fn abort_gate() -> Gate {
+    Gate::default().narrow(&AbortStep)
+}
+
+fn open_mpc_receiver(
+    transport: &RoleResolvingTransport,
+    query_id: QueryId,
+    config: &GatewayConfig,
+    channel_id: &HelperChannelId,
+) -> UR {
+    UnorderedReceiver::new(
+        Box::pin(transport.receive(channel_id.peer, (query_id, channel_id.gate.clone()))),
+        config.active_work(),
+    )
+    .with_max_payload_size(config.max_payload_size)
+}
+
 impl Default for GatewayConfig {
     fn default() -> Self {
         Self::new(1024)
@@ -244,6 +573,10 @@ impl GatewayConfig {
             } else {
                 30
             }),
+            max_pending_receives: None,
+            max_payload_size: None,
+            startup_deadline: None,
+            abortable: false,
         }
     }
 
@@ -256,26 +589,57 @@ impl GatewayConfig {
 
 #[cfg(all(test, unit_test))]
 mod tests {
-    use std::iter::{repeat, zip};
+    use std::{
+        iter::{repeat, zip},
+        num::NonZeroUsize,
+        pin::pin,
+        time::Duration,
+    };
 
+    use bytes::Bytes;
     use futures::{
-        future::{join, try_join, try_join_all},
-        stream::StreamExt,
+        future::{join, join_all, select, try_join, try_join_all, Either},
+        poll,
+        stream::{self, StreamExt},
     };
 
     use crate::{
+        error::BoxError,
         ff::{boolean_array::BA3, Fp31, Fp32BitPrime, Gf2, U128Conversions},
-        helpers::{Direction, GatewayConfig, MpcMessage, Role, SendingEnd},
+        helpers::{
+            transport::in_memory::{InMemoryMpcNetwork, InMemoryShardNetwork},
+            Direction, Error, Gateway, GatewayConfig, HelperChannelId, HelperIdentity, MpcMessage,
+            Role, RoleAssignment, SendingEnd, Transport,
+        },
         protocol::{
             context::{Context, ShardedContext},
-            RecordId,
+            step::Gate,
+            QueryId, RecordId,
         },
         secret_sharing::replicated::semi_honest::AdditiveShare,
-        sharding::ShardConfiguration,
+        sharding::{ShardConfiguration, ShardIndex},
         test_executor::run,
         test_fixture::{Reconstruct, Runner, TestWorld, TestWorldConfig, WithShards},
     };
 
+    #[tokio::test]
+    async fn left_and_right_peers() {
+        let world = TestWorld::default();
+        for (role, left, right) in [
+            (Role::H1, Role::H3, Role::H2),
+            (Role::H2, Role::H1, Role::H3),
+            (Role::H3, Role::H2, Role::H1),
+        ] {
+            let gateway = world.gateway(role);
+            assert_eq!(role, gateway.role());
+            assert_eq!(left, gateway.left());
+            assert_eq!(right, gateway.right());
+            assert_ne!(gateway.left(), gateway.right());
+            assert_ne!(role, gateway.left());
+            assert_ne!(role, gateway.right());
+        }
+    }
+
     /// Verifies that [`Gateway`] send buffer capacity is adjusted to the message size.
     /// IPA protocol opens many channels to send values from different fields, while message size
     /// is set per channel, it does not have to be the same across multiple send channels.
@@ -455,6 +819,733 @@ mod tests {
         let _world = unsafe { Box::from_raw(world_ptr) };
     }
 
+    #[tokio::test]
+    async fn half_close() {
+        let world = TestWorld::default();
+        world
+            .semi_honest((), |ctx, ()| async move {
+                let ctx = ctx.narrow("half-close").set_total_records(2);
+                let send_channel = ctx.send_channel::<Fp31>(ctx.role().peer(Direction::Right));
+                let recv_channel = ctx.recv_channel::<Fp31>(ctx.role().peer(Direction::Left));
+
+                send_channel
+                    .send(RecordId::FIRST, Fp31::truncate_from(1_u128))
+                    .await
+                    .unwrap();
+                send_channel.half_close().await;
+
+                let r = send_channel
+                    .send(RecordId::from(1), Fp31::truncate_from(2_u128))
+                    .await;
+                assert!(matches!(r, Err(Error::SendAfterClose { .. })));
+
+                // receiving side is not affected by closing the sending half
+                assert_eq!(
+                    Fp31::truncate_from(1_u128),
+                    recv_channel.receive(RecordId::FIRST).await.unwrap()
+                );
+            })
+            .await;
+    }
+
+    /// Flushing the sender makes messages available to the peer, before the send buffer
+    /// is full or the channel is closed.
+    #[tokio::test]
+    async fn flush() {
+        let config = TestWorldConfig {
+            gateway_config: GatewayConfig::new(10),
+            ..Default::default()
+        };
+        let world = TestWorld::new_with(config);
+        world
+            .semi_honest((), |ctx, ()| async move {
+                let ctx = ctx.narrow("flush").set_total_records(10);
+                let send_channel = ctx.send_channel::<Fp31>(ctx.role().peer(Direction::Right));
+                let recv_channel = ctx.recv_channel::<Fp31>(ctx.role().peer(Direction::Left));
+
+                for i in 0..3_u32 {
+                    send_channel
+                        .send(RecordId::from(i), Fp31::truncate_from(i))
+                        .await
+                        .unwrap();
+                }
+                send_channel.flush().await;
+
+                for i in 0..3_u32 {
+                    assert_eq!(
+                        Fp31::truncate_from(i),
+                        recv_channel.receive(RecordId::from(i)).await.unwrap()
+                    );
+                }
+            })
+            .await;
+    }
+
+    /// H1 fills up the send buffer of the bulk channel before it sends a control message with
+    /// high priority. H2 asks for the bulk data first, but the control message is delivered
+    /// before the bulk backlog drains.
+    #[tokio::test]
+    async fn send_priority() {
+        const BULK: usize = 4;
+        let network = InMemoryMpcNetwork::default();
+        let shards = InMemoryShardNetwork::with_shards(1_u32);
+        let roles = RoleAssignment::new(HelperIdentity::make_three());
+        let [h1, h2, _] = zip(
+            network.transports(),
+            shards.shard_transports(ShardIndex::FIRST),
+        )
+        .map(|(mpc, shard)| {
+            Gateway::new(
+                QueryId::DEFAULT,
+                GatewayConfig::new(BULK),
+                roles.clone(),
+                mpc,
+                shard,
+            )
+        })
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap_or_else(|_| unreachable!());
+
+        let bulk_tx = h1.get_mpc_sender::<Fp31>(
+            &HelperChannelId::new(Role::H2, Gate::from("bulk")),
+            (2 * BULK).into(),
+        );
+        for i in 0..BULK {
+            bulk_tx
+                .send(RecordId::from(i), Fp31::truncate_from(1_u128))
+                .await
+                .unwrap();
+        }
+        let control_tx = h1.get_mpc_sender::<Fp31>(
+            &HelperChannelId::new(Role::H2, Gate::from("control")),
+            BULK.into(),
+        );
+        // H2 is not receiving yet, but that does not hold H1 back
+        control_tx
+            .send_priority(RecordId::FIRST, Fp31::truncate_from(2_u128))
+            .await
+            .unwrap();
+
+        let bulk_rx =
+            h2.get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, Gate::from("bulk")));
+        let control_rx =
+            h2.get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, Gate::from("control")));
+        let receive = async {
+            match select(
+                pin!(bulk_rx.receive(RecordId::FIRST)),
+                pin!(control_rx.receive(RecordId::FIRST)),
+            )
+            .await
+            {
+                Either::Left(_) => panic!("bulk data was received before the control message"),
+                Either::Right((control, bulk)) => (control.unwrap(), bulk.await.unwrap()),
+            }
+        };
+        let (control, bulk) = receive.await;
+        assert_eq!(Fp31::truncate_from(2_u128), control);
+        assert_eq!(Fp31::truncate_from(1_u128), bulk);
+    }
+
+    /// H1 sends two control messages with high priority concurrently, while H2 is blocked
+    /// receiving on another channel. Neither the priority sends nor that channel wait for each
+    /// other.
+    #[tokio::test]
+    async fn send_priority_concurrent() {
+        let network = InMemoryMpcNetwork::default();
+        let shards = InMemoryShardNetwork::with_shards(1_u32);
+        let roles = RoleAssignment::new(HelperIdentity::make_three());
+        let [h1, h2, _] = zip(
+            network.transports(),
+            shards.shard_transports(ShardIndex::FIRST),
+        )
+        .map(|(mpc, shard)| {
+            Gateway::new(
+                QueryId::DEFAULT,
+                GatewayConfig::default(),
+                roles.clone(),
+                mpc,
+                shard,
+            )
+        })
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap_or_else(|_| unreachable!());
+
+        let bulk_rx =
+            h2.get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, Gate::from("bulk")));
+        let mut bulk = pin!(bulk_rx.receive(RecordId::FIRST));
+        assert!(poll!(&mut bulk).is_pending());
+
+        let [control_a, control_b] = ["control_a", "control_b"].map(|gate| {
+            h1.get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, Gate::from(gate)), 1.into())
+        });
+        let (a, b) = join(
+            control_a.send_priority(RecordId::FIRST, Fp31::truncate_from(2_u128)),
+            control_b.send_priority(RecordId::FIRST, Fp31::truncate_from(3_u128)),
+        )
+        .await;
+        a.unwrap();
+        b.unwrap();
+
+        h1.get_mpc_sender::<Fp31>(
+            &HelperChannelId::new(Role::H2, Gate::from("bulk")),
+            1.into(),
+        )
+        .send(RecordId::FIRST, Fp31::truncate_from(1_u128))
+        .await
+        .unwrap();
+        assert_eq!(Fp31::truncate_from(1_u128), bulk.await.unwrap());
+    }
+
+    /// H1 never sends anything to H3, so H3 misses its startup deadline and shuts down, failing
+    /// the receive it has pending. H2 receives from H1 in time and H1 only sends, both of them
+    /// stay alive after the deadline.
+    #[tokio::test]
+    async fn startup_deadline() {
+        const DEADLINE: Duration = Duration::from_millis(50);
+        let network = InMemoryMpcNetwork::default();
+        let shards = InMemoryShardNetwork::with_shards(1_u32);
+        let roles = RoleAssignment::new(HelperIdentity::make_three());
+        let config = GatewayConfig {
+            startup_deadline: Some(DEADLINE),
+            ..GatewayConfig::default()
+        };
+        let [h1, h2, h3] = zip(
+            network.transports(),
+            shards.shard_transports(ShardIndex::FIRST),
+        )
+        .map(|(mpc, shard)| Gateway::new(QueryId::DEFAULT, config, roles.clone(), mpc, shard))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap_or_else(|_| unreachable!());
+
+        let channel_id = |peer| HelperChannelId::new(peer, Gate::from("startup"));
+        h1.get_mpc_sender::<Fp31>(&channel_id(Role::H2), 1.into())
+            .send(RecordId::FIRST, Fp31::truncate_from(1_u128))
+            .await
+            .unwrap();
+        h2.get_mpc_receiver::<Fp31>(&channel_id(Role::H1))
+            .receive(RecordId::FIRST)
+            .await
+            .unwrap();
+
+        let from_h1 = h3.get_mpc_receiver::<Fp31>(&channel_id(Role::H1));
+        let r = tokio::time::timeout(2 * DEADLINE, from_h1.receive(RecordId::FIRST)).await;
+        assert!(
+            matches!(r, Ok(Err(Error::ShutDown { .. }))),
+            "pending receive must fail at the deadline"
+        );
+
+        assert!(h1.is_alive());
+        assert!(h2.is_alive());
+        assert!(!h3.is_alive());
+        assert!(matches!(
+            h3.try_get_mpc_receiver::<Fp31>(&channel_id(Role::H2)),
+            Err(Error::ShutDown { .. })
+        ));
+    }
+
+    /// H1 detects a failure and aborts the query. Receives that H2 and H3 have outstanding on
+    /// data from H1 resolve with an abort error instead of hanging, without them calling abort.
+    #[tokio::test]
+    async fn abort_resolves_pending_receives() {
+        const REASON: &str = "malicious security check failed on H1";
+        let mut gateway_config = GatewayConfig::default();
+        gateway_config.abortable = true;
+        let world = TestWorld::new_with(TestWorldConfig {
+            gateway_config,
+            ..Default::default()
+        });
+        let channel_id = HelperChannelId::new(Role::H1, Gate::from("data"));
+        let receivers = [Role::H2, Role::H3]
+            .map(|role| world.gateway(role).get_mpc_receiver::<Fp31>(&channel_id));
+
+        let pending = join_all(receivers.iter().map(|r| r.receive(RecordId::FIRST)));
+        let abort = async {
+            crate::task::yield_now().await;
+            world.gateway(Role::H1).abort(REASON).await;
+        };
+        let (results, ()) = join(pending, abort).await;
+        for result in results {
+            assert!(matches!(
+                result,
+                Err(Error::Aborted { reason, .. }) if reason == format!("H1 aborted the query: {REASON}")
+            ));
+        }
+
+        // H1 reports its own reason
+        let from_h2 = world
+            .gateway(Role::H1)
+            .get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H2, Gate::from("data")));
+        let r = from_h2.receive(RecordId::FIRST).await;
+        assert!(matches!(r, Err(Error::Aborted { reason, .. }) if reason == REASON));
+
+        // receives started after the abort fail right away, and the first reason is kept
+        world.gateway(Role::H2).abort("another reason").await;
+        for receiver in &receivers {
+            let r = receiver.receive(RecordId::from(1_u32)).await;
+            assert!(matches!(r, Err(Error::Aborted { reason, .. }) if reason.contains("H1")));
+        }
+    }
+
+    /// H1 sends records 0..3 and H2 consumes only the first one while waiting for record 5.
+    #[cfg(feature = "stall-detection")]
+    #[tokio::test]
+    async fn debug_snapshot() {
+        use crate::helpers::ChannelSnapshot;
+
+        let world = TestWorld::default();
+        let gate = Gate::from("snapshot");
+        let sender = world
+            .gateway(Role::H1)
+            .get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, gate.clone()), 8.into());
+        let channel_id = HelperChannelId::new(Role::H1, gate);
+        let receiver = world
+            .gateway(Role::H2)
+            .get_mpc_receiver::<Fp31>(&channel_id);
+
+        for i in 0..3_u32 {
+            sender
+                .send(RecordId::from(i), Fp31::truncate_from(i))
+                .await
+                .unwrap();
+        }
+        sender.flush().await;
+        receiver.receive(RecordId::FIRST).await.unwrap();
+
+        let mut pending = pin!(receiver.receive(RecordId::from(5_u32)));
+        assert!(poll!(pending.as_mut()).is_pending());
+
+        assert_eq!(
+            vec![ChannelSnapshot {
+                channel_id,
+                received: vec![RecordId::from(1_u32), RecordId::from(2_u32)],
+                requested: vec![RecordId::from(5_u32)],
+            }],
+            world.gateway(Role::H2).debug_snapshot()
+        );
+        assert!(world.gateway(Role::H3).debug_snapshot().is_empty());
+    }
+
+    #[tokio::test]
+    async fn max_pending_receives() {
+        let mut gateway_config = GatewayConfig::default();
+        gateway_config.max_pending_receives = NonZeroUsize::new(2);
+        let world = TestWorld::new_with(TestWorldConfig {
+            gateway_config,
+            ..Default::default()
+        });
+        let channel_id = HelperChannelId::new(Role::H1, Gate::from("pending"));
+        let receiver = world
+            .gateway(Role::H2)
+            .get_mpc_receiver::<Fp31>(&channel_id);
+
+        let mut first = pin!(receiver.receive(RecordId::from(0_u32)));
+        let mut second = pin!(receiver.receive(RecordId::from(1_u32)));
+        assert!(poll!(first.as_mut()).is_pending());
+        assert!(poll!(second.as_mut()).is_pending());
+        assert!(matches!(
+            receiver.receive(RecordId::from(2_u32)).await,
+            Err(Error::TooManyPendingReceives { limit: 2, .. })
+        ));
+
+        // once a receive completes, its slot can be taken by another one
+        let sender = world
+            .gateway(Role::H1)
+            .get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, channel_id.gate), 3.into());
+        sender
+            .send(RecordId::FIRST, Fp31::truncate_from(1_u128))
+            .await
+            .unwrap();
+        sender.flush().await;
+        first.await.unwrap();
+
+        let mut third = pin!(receiver.receive(RecordId::from(2_u32)));
+        assert!(poll!(third.as_mut()).is_pending());
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    async fn self_send_rejected() {
+        let world = TestWorld::default();
+        let gateway = world.gateway(Role::H1);
+        let gate = Gate::from("self_send");
+        let value = Fp31::truncate_from(1_u128);
+
+        let to_self =
+            gateway.get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H1, gate.clone()), 1.into());
+        assert!(matches!(
+            to_self.send(RecordId::FIRST, value).await,
+            Err(Error::SelfSend { .. })
+        ));
+
+        let to_peer =
+            gateway.get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, gate.clone()), 1.into());
+        to_peer.send(RecordId::FIRST, value).await.unwrap();
+        let received = world
+            .gateway(Role::H2)
+            .get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, gate))
+            .receive(RecordId::FIRST)
+            .await
+            .unwrap();
+        assert_eq!(value, received);
+    }
+
+    /// The only receive slot is taken, so the first attempt fails. Once the slot is released,
+    /// the retry picks up the message.
+    #[tokio::test]
+    async fn receive_retry() {
+        let mut gateway_config = GatewayConfig::default();
+        gateway_config.max_pending_receives = NonZeroUsize::new(1);
+        let world = TestWorld::new_with(TestWorldConfig {
+            gateway_config,
+            ..Default::default()
+        });
+        let channel_id = HelperChannelId::new(Role::H1, Gate::from("retry"));
+        let receiver = world
+            .gateway(Role::H2)
+            .get_mpc_receiver::<Fp31>(&channel_id);
+
+        let mut first = pin!(receiver.receive(RecordId::FIRST));
+        assert!(poll!(first.as_mut()).is_pending());
+        let mut retry = pin!(receiver.receive_retry(RecordId::from(1_u32), 1));
+        assert!(poll!(retry.as_mut()).is_pending());
+
+        let sender = world
+            .gateway(Role::H1)
+            .get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, channel_id.gate), 2.into());
+        for i in 0..2_u32 {
+            sender
+                .send(RecordId::from(i), Fp31::truncate_from(i))
+                .await
+                .unwrap();
+        }
+        sender.flush().await;
+        first.await.unwrap();
+        assert_eq!(Fp31::truncate_from(1_u128), retry.await.unwrap());
+
+        // without retries, the limit is reported right away
+        let mut pending = pin!(receiver.receive(RecordId::from(2_u32)));
+        assert!(poll!(pending.as_mut()).is_pending());
+        assert!(matches!(
+            receiver.receive_retry(RecordId::from(3_u32), 0).await,
+            Err(Error::TooManyPendingReceives { limit: 1, .. })
+        ));
+    }
+
+    /// The connection to the peer drops once and comes back. The receive that is waiting when it
+    /// drops fails with a transient error, and retrying it picks up the record sent after.
+    #[tokio::test]
+    async fn receive_retry_after_interruption() {
+        let network = InMemoryMpcNetwork::default();
+        let shards = InMemoryShardNetwork::with_shards(1_u32);
+        let [_, mpc, _] = network.transports();
+        let [_, shard, _] = shards.shard_transports(ShardIndex::FIRST);
+        for gate in ["flaky", "flaky_no_retry"] {
+            mpc.inject(
+                QueryId::DEFAULT,
+                HelperIdentity::ONE,
+                Gate::from(gate),
+                stream::iter([
+                    Err(BoxError::from("connection reset")),
+                    Ok(Bytes::from_static(&[17])),
+                ]),
+            );
+        }
+        let gateway = Gateway::new(
+            QueryId::DEFAULT,
+            GatewayConfig::default(),
+            RoleAssignment::new(HelperIdentity::make_three()),
+            mpc,
+            shard,
+        );
+        let receiver =
+            |gate| gateway.get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, gate));
+
+        assert_eq!(
+            Fp31::truncate_from(17_u128),
+            receiver(Gate::from("flaky"))
+                .receive_retry(RecordId::FIRST, 1)
+                .await
+                .unwrap()
+        );
+
+        let err = receiver(Gate::from("flaky_no_retry"))
+            .receive_retry(RecordId::FIRST, 0)
+            .await
+            .unwrap_err();
+        assert!(err.is_transient());
+        assert!(matches!(err, Error::Interrupted { .. }), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn peer_closed() {
+        let world = TestWorld::default();
+        let gate = Gate::from("closed");
+        let receiver = world
+            .gateway(Role::H2)
+            .get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, gate.clone()));
+
+        let mut pending = pin!(receiver.receive(RecordId::from(2_u32)));
+        assert!(poll!(pending.as_mut()).is_pending());
+        let mut closed = pin!(receiver.closed());
+        assert!(poll!(closed.as_mut()).is_pending());
+
+        // the peer sends a single record and closes the channel
+        world
+            .gateway(Role::H1)
+            .get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, gate), 1.into())
+            .send(RecordId::FIRST, Fp31::truncate_from(7_u128))
+            .await
+            .unwrap();
+
+        // the end of the stream is behind the record that was sent
+        let ((), first) = join(closed, receiver.receive(RecordId::FIRST)).await;
+        assert_eq!(Fp31::truncate_from(7_u128), first.unwrap());
+        assert!(matches!(
+            pending.await,
+            Err(Error::PeerClosed { record_id, .. }) if record_id == RecordId::from(2_u32)
+        ));
+    }
+
+    #[test]
+    fn active_channels() {
+        run(|| async move {
+            let world = TestWorld::default();
+            let gateway = world.gateway(Role::H1);
+            assert!(gateway.active_channels().is_empty());
+
+            let send_channel = HelperChannelId::new(Role::H2, Gate::from("step_a"));
+            let recv_channel = HelperChannelId::new(Role::H3, Gate::from("step_b"));
+            let _sender = gateway.get_mpc_sender::<Fp31>(&send_channel, 1.into());
+            let _receiver = gateway.get_mpc_receiver::<Fp31>(&recv_channel);
+            // channels are listed once, no matter how many times they are requested
+            let _receiver = gateway.get_mpc_receiver::<Fp31>(&recv_channel);
+
+            assert_eq!(vec![send_channel, recv_channel], gateway.active_channels());
+        });
+    }
+
+    /// H2 relays a message from H1 to H3 without deserializing it.
+    #[tokio::test]
+    async fn relay_raw() {
+        let world = TestWorld::default();
+        let from_h1 = HelperChannelId::new(Role::H1, Gate::from("relay_in"));
+        let from_h2 = HelperChannelId::new(Role::H2, Gate::from("relay_out"));
+        let value = Fp32BitPrime::truncate_from(123_456_u128);
+
+        world
+            .gateway(Role::H1)
+            .get_mpc_sender::<Fp32BitPrime>(
+                &HelperChannelId::new(Role::H2, from_h1.gate.clone()),
+                1.into(),
+            )
+            .send(RecordId::FIRST, value)
+            .await
+            .unwrap();
+
+        let bytes = world
+            .gateway(Role::H2)
+            .get_mpc_receiver::<Fp32BitPrime>(&from_h1)
+            .receive_raw(RecordId::FIRST)
+            .await
+            .unwrap();
+        assert_eq!(4, bytes.len());
+        world
+            .gateway(Role::H2)
+            .get_mpc_sender::<Fp32BitPrime>(
+                &HelperChannelId::new(Role::H3, from_h2.gate.clone()),
+                1.into(),
+            )
+            .send_raw(RecordId::FIRST, &bytes)
+            .await
+            .unwrap();
+
+        let relayed = world
+            .gateway(Role::H3)
+            .get_mpc_receiver::<Fp32BitPrime>(&from_h2)
+            .receive(RecordId::FIRST)
+            .await
+            .unwrap();
+        assert_eq!(value, relayed);
+    }
+
+    #[tokio::test]
+    async fn prewarm() {
+        let world = TestWorld::default();
+        let gateway = world.gateway(Role::H1);
+        let gates = [Gate::from("prewarm_a"), Gate::from("prewarm_b")];
+
+        gateway.prewarm(&gates);
+        // prewarming twice does not open the channels again
+        gateway.prewarm(&gates[..1]);
+
+        let mut expected = gates
+            .iter()
+            .flat_map(|gate| {
+                [Role::H2, Role::H3].map(|peer| HelperChannelId::new(peer, gate.clone()))
+            })
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(expected, gateway.active_channels());
+    }
+
+    #[tokio::test]
+    async fn receive_windowed() {
+        const TOTAL: usize = 1000;
+        const WINDOW: usize = 64;
+
+        let world = TestWorld::default();
+        let gate = Gate::from("windowed");
+        let values = (0..TOTAL)
+            .map(|i| Fp31::truncate_from(u128::try_from(i).unwrap()))
+            .collect::<Vec<_>>();
+        let sender = world
+            .gateway(Role::H1)
+            .get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, gate.clone()), TOTAL.into());
+        let recv = world
+            .gateway(Role::H2)
+            .get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, gate));
+
+        let (sent, windows) = join(
+            try_join_all(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &v)| sender.send(RecordId::from(i), v)),
+            ),
+            recv.receive_windowed(RecordId::FIRST, TOTAL, NonZeroUsize::new(WINDOW).unwrap())
+                .unwrap()
+                .collect::<Vec<_>>(),
+        )
+        .await;
+        sent.unwrap();
+
+        let windows = windows.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(TOTAL.div_ceil(WINDOW), windows.len());
+        assert!(windows[..windows.len() - 1]
+            .iter()
+            .all(|w| w.len() == WINDOW));
+        assert_eq!(values, windows.concat());
+    }
+
+    #[tokio::test]
+    async fn receive_windowed_overflow() {
+        let world = TestWorld::default();
+        let gate = Gate::from("windowed_overflow");
+        let recv = world
+            .gateway(Role::H2)
+            .get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, gate));
+        let start = RecordId::from(u32::MAX - 1);
+
+        assert!(matches!(
+            recv.receive_windowed(start, 3, NonZeroUsize::new(2).unwrap()),
+            Err(crate::error::Error::RecordIdOverflow { count: 3, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn query_id_isolates_queries() {
+        fn gateway(gateways: &[Gateway], role: Role) -> &Gateway {
+            gateways.iter().find(|g| g.role() == role).unwrap()
+        }
+
+        let network = InMemoryMpcNetwork::default();
+        let shards = InMemoryShardNetwork::with_shards(1_u32);
+        let roles = RoleAssignment::new(HelperIdentity::make_three());
+        let query = |query_id: QueryId| {
+            network
+                .transports()
+                .iter()
+                .zip(shards.shard_transports(ShardIndex::FIRST))
+                .map(|(mpc, shard)| {
+                    Gateway::new(
+                        query_id,
+                        GatewayConfig::default(),
+                        roles.clone(),
+                        Transport::clone_ref(mpc),
+                        shard,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let queries = [
+            (query(QueryId::DEFAULT), 4_u128),
+            (query(QueryId::new(1)), 9),
+        ];
+
+        let channel_id = |peer| HelperChannelId::new(peer, Gate::from("same_step"));
+        for (gateways, v) in &queries {
+            let sender =
+                gateway(gateways, Role::H1).get_mpc_sender::<Fp31>(&channel_id(Role::H2), 1.into());
+            sender
+                .send(RecordId::FIRST, Fp31::truncate_from(*v))
+                .await
+                .unwrap();
+        }
+
+        // the second query receives first, so it would get the other query's record if
+        // both shared the channel
+        for (gateways, v) in queries.iter().rev() {
+            let receiver =
+                gateway(gateways, Role::H2).get_mpc_receiver::<Fp31>(&channel_id(Role::H1));
+            assert_eq!(
+                Fp31::truncate_from(*v),
+                receiver.receive(RecordId::FIRST).await.unwrap()
+            );
+        }
+    }
+
+    /// Once the network behind a gateway is torn down, opening a channel reports the gateway as
+    /// shut down instead of handing out a channel that never delivers anything. Channels requested
+    /// without the `try_` getters fail every send and receive instead.
+    #[tokio::test]
+    async fn shut_down() {
+        let network = InMemoryMpcNetwork::default();
+        let shards = InMemoryShardNetwork::with_shards(1_u32);
+        let [mpc, ..] = network.transports();
+        let [shard, ..] = shards.shard_transports(ShardIndex::FIRST);
+        let gateway = Gateway::new(
+            QueryId::DEFAULT,
+            GatewayConfig::default(),
+            RoleAssignment::new(HelperIdentity::make_three()),
+            mpc,
+            shard,
+        );
+        let channel_id = |peer| HelperChannelId::new(peer, Gate::from("shut_down"));
+        assert!(gateway.is_alive());
+        assert!(gateway
+            .try_get_mpc_receiver::<Fp31>(&channel_id(Role::H2))
+            .is_ok());
+
+        drop(network);
+        assert!(!gateway.is_alive());
+        assert!(matches!(
+            gateway.try_get_mpc_sender::<Fp31>(&channel_id(Role::H2), 1.into()),
+            Err(Error::ShutDown { .. })
+        ));
+        assert!(matches!(
+            gateway.try_get_mpc_receiver::<Fp31>(&channel_id(Role::H3)),
+            Err(Error::ShutDown { .. })
+        ));
+
+        let sender = gateway.get_mpc_sender::<Fp31>(&channel_id(Role::H2), 1.into());
+        assert!(matches!(
+            sender
+                .send(RecordId::FIRST, Fp31::truncate_from(1_u128))
+                .await,
+            Err(Error::ShutDown { .. })
+        ));
+        sender.close(RecordId::FIRST).await;
+        let receiver = gateway.get_mpc_receiver::<Fp31>(&channel_id(Role::H3));
+        assert!(matches!(
+            receiver.receive(RecordId::FIRST).await,
+            Err(Error::ShutDown { .. })
+        ));
+        receiver.closed().await;
+    }
+
     #[test]
     fn shards() {
         run(|| async move {
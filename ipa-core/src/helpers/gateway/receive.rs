@@ -1,25 +1,35 @@
 use std::{
     marker::PhantomData,
-    pin::Pin,
+    num::NonZeroUsize,
+    pin::{pin, Pin},
     task::{Context, Poll},
 };
 
 use bytes::Bytes;
 use dashmap::{mapref::entry::Entry, DashMap};
-use futures::Stream;
+use futures::{
+    future::{self, select, try_join_all, Either},
+    stream, Stream, StreamExt,
+};
 use pin_project::pin_project;
 
 use crate::{
     error::BoxError,
     helpers::{
         buffers::{UnorderedReceiver, UnorderedReceiverError},
-        gateway::transport::RoleResolvingTransport,
+        gateway::{
+            abort::AbortSignal, rounds::RoundCounter, startup::StartupCheck,
+            transport::RoleResolvingTransport, RawMessage,
+        },
         transport::SingleRecordStream,
-        ChannelId, Error, HelperChannelId, LogErrors, Message, MpcMessage, Role, ShardChannelId,
+        ChannelId, Error, HelperChannelId, Message, MpcMessage, Role, ShardChannelId,
         ShardTransportImpl, Transport, TransportIdentity,
     },
     protocol::RecordId,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
 /// Receiving end of the MPC gateway channel.
@@ -30,15 +40,69 @@ use crate::{
 /// [`gat`]: https://github.com/rust-lang/rust/issues/100013
 pub struct MpcReceivingEnd<M> {
     channel_id: HelperChannelId,
-    unordered_rx: UR,
+    /// Not set if this channel was requested from a gateway that is no longer alive.
+    unordered_rx: Option<UR>,
+    abort: Option<AbortSignal>,
+    pending: PendingReceives,
+    rounds: RoundCounter,
+    startup: StartupCheck,
     _phantom: PhantomData<fn() -> M>,
 }
 
+/// Number of MPC receives in flight across all channels of a gateway, with an optional limit.
+#[derive(Clone, Default)]
+pub(super) struct PendingReceives {
+    count: Arc<AtomicUsize>,
+    limit: Option<NonZeroUsize>,
+}
+
+/// Releases the pending receive slot when the receive completes or gets cancelled.
+struct PendingReceive<'a>(&'a AtomicUsize);
+
+impl PendingReceives {
+    pub fn new(limit: Option<NonZeroUsize>) -> Self {
+        Self {
+            count: Arc::default(),
+            limit,
+        }
+    }
+
+    /// Takes a slot for a new receive. Returns `Err` with the limit if all slots are taken.
+    fn acquire(&self) -> Result<PendingReceive<'_>, usize> {
+        let prev = self.count.fetch_add(1, Ordering::Relaxed);
+        let slot = PendingReceive(&self.count);
+        match self.limit {
+            Some(limit) if prev >= limit.get() => Err(limit.get()),
+            _ => Ok(slot),
+        }
+    }
+}
+
+impl Drop for PendingReceive<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 #[pin_project]
 pub struct ShardReceivingEnd<M: Message> {
     pub(super) channel_id: ShardChannelId,
     #[pin]
     pub(super) rx: SingleRecordStream<M, ShardReceiveStream>,
+    pub(super) startup: StartupCheck,
+}
+
+/// Point-in-time view of a single MPC receiving channel, see [`Gateway::debug_snapshot`].
+///
+/// [`Gateway::debug_snapshot`]: crate::helpers::Gateway::debug_snapshot
+#[cfg(feature = "stall-detection")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelSnapshot {
+    pub channel_id: HelperChannelId,
+    /// Records that arrived from the peer, but nobody asked for them yet.
+    pub received: Vec<RecordId>,
+    /// Records that somebody is waiting for, but the peer has not sent them yet.
+    pub requested: Vec<RecordId>,
 }
 
 /// Receiving channels, indexed by (role, step).
@@ -47,8 +111,8 @@ pub(super) struct GatewayReceivers<I, S> {
 }
 
 pub type UR = UnorderedReceiver<
-    LogErrors<<RoleResolvingTransport as Transport>::RecordsStream, Bytes, BoxError>,
-    Vec<u8>,
+    <RoleResolvingTransport as Transport>::RecordsStream,
+    Result<Bytes, BoxError>,
 >;
 
 /// Stream of records received from a peer shard.
@@ -62,10 +126,21 @@ pub struct ShardReceiveStream(
 );
 
 impl<M: MpcMessage> MpcReceivingEnd<M> {
-    pub(super) fn new(channel_id: HelperChannelId, rx: UR) -> Self {
+    pub(super) fn new(
+        channel_id: HelperChannelId,
+        rx: Option<UR>,
+        abort: Option<AbortSignal>,
+        pending: PendingReceives,
+        rounds: RoundCounter,
+        startup: StartupCheck,
+    ) -> Self {
         Self {
             channel_id,
             unordered_rx: rx,
+            abort,
+            pending,
+            rounds,
+            startup,
             _phantom: PhantomData,
         }
     }
@@ -74,26 +149,186 @@ impl<M: MpcMessage> MpcReceivingEnd<M> {
     /// message is actually received and deserialized.
     ///
     /// ## Errors
-    /// Returns an error if receiving fails
+    /// Returns an error if receiving fails or [`Error::Aborted`] if the gateway was aborted
+    /// before the message arrived. If the gateway limits the number of pending receives and
+    /// that limit is reached, [`Error::TooManyPendingReceives`] is returned right away. A payload
+    /// from the peer above the configured maximum size fails with [`Error::MessageTooLarge`], and
+    /// so does every receive on this channel after it. If the transport reports an error, e.g.
+    /// because the connection to the peer dropped, the receive waiting for data at that point
+    /// fails with [`Error::Interrupted`]. If the channel was requested from a
+    /// gateway that was no longer alive, every receive fails with [`Error::ShutDown`], and so
+    /// does a pending receive once the gateway misses its [startup deadline].
     ///
     /// ## Panics
     /// This will panic if message size does not fit into 8 bytes and it somehow got serialized
     /// and sent to this helper.
+    ///
+    /// [startup deadline]: crate::helpers::GatewayConfig::startup_deadline
     #[tracing::instrument(level = "trace", "receive", skip_all, fields(i = %record_id, from = ?self.channel_id.peer, gate = ?self.channel_id.gate.as_ref()))]
     pub async fn receive(&self, record_id: RecordId) -> Result<M, Error<Role>> {
-        self.unordered_rx
-            .recv::<M, _>(record_id)
-            .await
-            .map_err(|e| match e {
-                UnorderedReceiverError::DeserializeFailed(inner) => Error::DeserializeFailed {
-                    channel_id: self.channel_id.clone(),
-                    inner,
-                },
-                UnorderedReceiverError::EndOfStream(inner) => Error::EndOfStream {
+        self.receive_as::<M>(record_id).await
+    }
+
+    /// Same as [`Self::receive`], but retries up to `max_retries` times if the receive fails
+    /// with a [transient] error, e.g. because the limit of pending receives was reached or the
+    /// transport was interrupted. The task yields before every retry, to give receives in flight
+    /// a chance to complete. A failed receive is no longer registered with the channel by the
+    /// time it returns, so retrying never requests the same record twice.
+    ///
+    /// ## Errors
+    /// Returns the last transient error if all retries are exhausted. Any other error is
+    /// returned right away, as described in [`Self::receive`].
+    ///
+    /// [transient]: Error::is_transient
+    pub async fn receive_retry(
+        &self,
+        record_id: RecordId,
+        max_retries: usize,
+    ) -> Result<M, Error<Role>> {
+        let mut retries = 0;
+        loop {
+            match self.receive(record_id).await {
+                Err(e) if e.is_transient() && retries < max_retries => {
+                    retries += 1;
+                    crate::task::yield_now().await;
+                }
+                r => return r,
+            }
+        }
+    }
+
+    /// Receives the message associated with the given record id, but returns its serialized
+    /// form instead of deserializing it. Together with [`SendingEnd::send_raw`] this lets a
+    /// helper relay messages to another channel without decoding them.
+    ///
+    /// ## Errors
+    /// Same as [`Self::receive`].
+    ///
+    /// [`SendingEnd::send_raw`]: crate::helpers::gateway::SendingEnd::send_raw
+    #[tracing::instrument(level = "trace", "receive_raw", skip_all, fields(i = %record_id, from = ?self.channel_id.peer, gate = ?self.channel_id.gate.as_ref()))]
+    pub async fn receive_raw(&self, record_id: RecordId) -> Result<Box<[u8]>, Error<Role>> {
+        let raw = self.receive_as::<RawMessage<M>>(record_id).await?;
+        Ok(Box::from(raw.0.as_slice()))
+    }
+
+    /// Resolves once the peer has closed its side of this channel and is not going to send any
+    /// more records, e.g. because it finished its output. A task waiting for a record can
+    /// `select!` between the receive and this future, instead of hanging when the record
+    /// never comes. Records are not buffered while waiting, so if the peer sent records that
+    /// were not received yet, this only resolves once they are.
+    ///
+    /// Once the channel is closed, the receive for the first record the peer did not send
+    /// fails with [`Error::EndOfStream`], and receives for the records after it, pending or
+    /// not, fail with [`Error::PeerClosed`].
+    ///
+    /// A channel requested from a gateway that was no longer alive is always closed.
+    pub async fn closed(&self) {
+        if let Some(rx) = &self.unordered_rx {
+            rx.closed().await;
+        }
+    }
+
+    /// Receives `total` records starting at `start`, `window` records at a time. The stream
+    /// yields a vector per window, in record order, once all of its records have arrived. Only
+    /// one window is requested at a time, which bounds the memory needed to receive a large
+    /// number of records.
+    ///
+    /// ## Errors
+    /// If `start + total` overflows the record id, before anything is received. Each window
+    /// fails the same way [`Self::receive`] does.
+    pub fn receive_windowed(
+        &self,
+        start: RecordId,
+        total: usize,
+        window: NonZeroUsize,
+    ) -> Result<impl Stream<Item = Result<Vec<M>, Error<Role>>> + '_, crate::error::Error> {
+        let record_ids = RecordId::range(start, total)?;
+        Ok(stream::iter(record_ids)
+            .chunks(window.get())
+            .then(move |window| try_join_all(window.into_iter().map(|r| self.receive(r)))))
+    }
+
+    /// Resolves with the error that a pending receive fails with, once the query is aborted or
+    /// the gateway misses its startup deadline.
+    async fn interrupted(&self) -> Error<Role> {
+        let aborted = async {
+            match &self.abort {
+                Some(abort) => abort.aborted().await,
+                None => future::pending().await,
+            }
+        };
+        match select(pin!(aborted), pin!(self.startup.expired())).await {
+            Either::Left((reason, _)) => Error::Aborted {
+                channel_id: self.channel_id.clone(),
+                reason: reason.to_string(),
+            },
+            Either::Right(((), _)) => Error::ShutDown {
+                channel_id: self.channel_id.clone(),
+            },
+        }
+    }
+
+    async fn receive_as<T: Message>(&self, record_id: RecordId) -> Result<T, Error<Role>> {
+        let Some(unordered_rx) = &self.unordered_rx else {
+            return Err(Error::ShutDown {
+                channel_id: self.channel_id.clone(),
+            });
+        };
+        let _slot = self
+            .pending
+            .acquire()
+            .map_err(|limit| Error::TooManyPendingReceives {
+                record_id,
+                channel_id: self.channel_id.clone(),
+                limit,
+            })?;
+        let recv = pin!(unordered_rx.recv::<T, _>(record_id));
+        // Most of the time there is nothing that could interrupt the receive, so keep it cheap.
+        let result = if self.abort.is_none() && !self.startup.is_armed() {
+            recv.await
+        } else {
+            match select(pin!(self.interrupted()), recv).await {
+                Either::Left((e, _)) => return Err(e),
+                Either::Right((result, _)) => result,
+            }
+        };
+        if result.is_ok() {
+            self.rounds.on_receive();
+            self.startup.on_activity();
+        }
+
+        result.map_err(|e| match e {
+            UnorderedReceiverError::DeserializeFailed(inner) => Error::DeserializeFailed {
+                channel_id: self.channel_id.clone(),
+                inner,
+            },
+            UnorderedReceiverError::EndOfStream(inner) => Error::EndOfStream {
+                channel_id: self.channel_id.clone(),
+                inner,
+            },
+            UnorderedReceiverError::Closed(record_id) => Error::PeerClosed {
+                record_id,
+                channel_id: self.channel_id.clone(),
+            },
+            UnorderedReceiverError::Interrupted { record_id, inner } => {
+                tracing::warn!(
+                    "transport error while receiving {record_id:?} from {:?}: {inner}",
+                    self.channel_id
+                );
+                Error::Interrupted {
+                    record_id,
                     channel_id: self.channel_id.clone(),
                     inner,
-                },
-            })
+                }
+            }
+            UnorderedReceiverError::MessageTooLarge { record_id, size } => {
+                tracing::warn!(
+                    "rejected {size} byte payload from {:?} while receiving {record_id:?}",
+                    self.channel_id
+                );
+                Error::MessageTooLarge { record_id, size }
+            }
+        })
     }
 }
 
@@ -101,7 +336,13 @@ impl<M: Message> Stream for ShardReceivingEnd<M> {
     type Item = Result<M, crate::error::Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.project().rx.poll_next(cx)
+        let this = self.project();
+        let r = this.rx.poll_next(cx);
+        if let Poll::Ready(Some(Ok(_))) = r {
+            this.startup.on_activity();
+        }
+
+        r
     }
 }
 
@@ -128,6 +369,34 @@ impl<I: TransportIdentity, S: Clone> GatewayReceivers<I, S> {
     }
 }
 
+#[cfg(feature = "stall-detection")]
+impl GatewayReceivers<Role, UR> {
+    pub fn snapshot(&self) -> Vec<ChannelSnapshot> {
+        let mut r = self
+            .inner
+            .iter()
+            .map(|entry| ChannelSnapshot {
+                channel_id: entry.key().clone(),
+                received: entry
+                    .value()
+                    .buffered()
+                    .into_iter()
+                    .map(RecordId::from)
+                    .collect(),
+                requested: entry
+                    .value()
+                    .waiting()
+                    .into_iter()
+                    .map(RecordId::from)
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        r.sort_by(|a, b| a.channel_id.cmp(&b.channel_id));
+
+        r
+    }
+}
+
 impl Stream for ShardReceiveStream {
     type Item = <<ShardTransportImpl as Transport>::RecordsStream as Stream>::Item;
 
@@ -0,0 +1,50 @@
+use crate::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Counts communication rounds made by a helper, see [`Gateway::rounds`].
+///
+/// A new round starts with the first send that follows a completed receive. Sends that are not
+/// separated by a receive belong to the same round, because they do not depend on anything
+/// the peers sent in between.
+///
+/// This sits on the send and receive paths, so it only uses relaxed atomics and does not write
+/// to shared state unless the round changes. Sends and receives that race with each other
+/// may be attributed to either round.
+///
+/// [`Gateway::rounds`]: crate::helpers::Gateway::rounds
+#[derive(Clone, Default)]
+pub(super) struct RoundCounter(Arc<RoundState>);
+
+#[derive(Default)]
+struct RoundState {
+    rounds: AtomicUsize,
+    received_since_send: AtomicBool,
+}
+
+impl RoundCounter {
+    pub fn on_send(&self) {
+        let state = &self.0;
+        if state.received_since_send.load(Ordering::Relaxed)
+            && state.received_since_send.swap(false, Ordering::Relaxed)
+        {
+            state.rounds.fetch_add(1, Ordering::Relaxed);
+        } else if state.rounds.load(Ordering::Relaxed) == 0 {
+            // Concurrent first sends must not count more than one round.
+            let _ = state
+                .rounds
+                .compare_exchange(0, 1, Ordering::Relaxed, Ordering::Relaxed);
+        }
+    }
+
+    pub fn on_receive(&self) {
+        if !self.0.received_since_send.load(Ordering::Relaxed) {
+            self.0.received_since_send.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn get(&self) -> usize {
+        self.0.rounds.load(Ordering::Relaxed)
+    }
+}
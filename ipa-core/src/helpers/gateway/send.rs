@@ -8,17 +8,23 @@ use std::{
 
 use dashmap::{mapref::entry::Entry, DashMap};
 use futures::Stream;
+use generic_array::GenericArray;
 #[cfg(all(test, feature = "shuttle"))]
 use shuttle::future as tokio;
 use typenum::Unsigned;
 
 use crate::{
     helpers::{
-        buffers::OrderingSender, routing::RouteId, ChannelId, Error, Message, TotalRecords,
-        Transport, TransportIdentity,
+        buffers::OrderingSender,
+        gateway::{rounds::RoundCounter, startup::StartupCheck, RawMessage},
+        routing::RouteId,
+        ChannelId, Error, Message, TotalRecords, Transport, TransportIdentity,
     },
     protocol::{QueryId, RecordId},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     telemetry::{
         labels::{ROLE, STEP},
         metrics::{BYTES_SENT, RECORDS_SENT},
@@ -29,6 +35,13 @@ use crate::{
 pub struct SendingEnd<I: TransportIdentity, M> {
     sender_id: I,
     inner: Arc<GatewaySender<I>>,
+    /// Only set for MPC channels, shard traffic does not count towards protocol rounds.
+    rounds: Option<RoundCounter>,
+    /// Any send counts as activity for the startup health check of the gateway.
+    startup: Option<StartupCheck>,
+    /// Set if this channel was requested from a gateway that is no longer alive. Such a channel
+    /// is not connected to the transport and every send on it fails.
+    shut_down: bool,
     /// This makes this struct [`Send`] even if [`M`] is not [`Sync`].
     _phantom: PhantomData<fn() -> M>,
 }
@@ -36,12 +49,18 @@ pub struct SendingEnd<I: TransportIdentity, M> {
 /// Sending channels, indexed by identity and gate.
 pub(super) struct GatewaySenders<I> {
     pub(super) inner: DashMap<ChannelId<I>, Arc<GatewaySender<I>>>,
+    /// Number of [`SendingEnd::send_priority`] calls made so far, one counter per peer.
+    priority_sends: DashMap<I, Arc<AtomicUsize>>,
 }
 
 pub(super) struct GatewaySender<I> {
     channel_id: ChannelId<I>,
     ordering_tx: OrderingSender,
     total_records: TotalRecords,
+    /// Shared with all the other channels to the same peer.
+    priority_sends: Arc<AtomicUsize>,
+    /// The value of `priority_sends` this channel has already stepped aside for.
+    priority_seen: AtomicUsize,
 }
 
 struct GatewaySendStream<I> {
@@ -52,16 +71,25 @@ impl<I: TransportIdentity> Default for GatewaySenders<I> {
     fn default() -> Self {
         Self {
             inner: DashMap::default(),
+            priority_sends: DashMap::default(),
         }
     }
 }
 
 impl<I: TransportIdentity> GatewaySender<I> {
-    fn new(channel_id: ChannelId<I>, tx: OrderingSender, total_records: TotalRecords) -> Self {
+    fn new(
+        channel_id: ChannelId<I>,
+        tx: OrderingSender,
+        total_records: TotalRecords,
+        priority_sends: Arc<AtomicUsize>,
+    ) -> Self {
+        let priority_seen = AtomicUsize::new(priority_sends.load(Ordering::Relaxed));
         Self {
             channel_id,
             ordering_tx: tx,
             total_records,
+            priority_sends,
+            priority_seen,
         }
     }
 
@@ -84,8 +112,14 @@ impl<I: TransportIdentity> GatewaySender<I> {
             }
         }
 
+        if self.is_closed() {
+            return Err(Error::SendAfterClose {
+                record_id,
+                channel_id: self.channel_id.clone(),
+            });
+        }
+
         // TODO: make OrderingSender::send fallible
-        // TODO: test channel close
         let i = usize::from(record_id);
         self.ordering_tx.send(i, msg).await;
         if self.total_records.is_last(record_id) {
@@ -95,6 +129,29 @@ impl<I: TransportIdentity> GatewaySender<I> {
         Ok(())
     }
 
+    /// Sends the message and makes it available to the transport right away, ahead of the data
+    /// buffered on other channels to the same peer, see [`SendingEnd::send_priority`].
+    pub async fn send_priority<M: Message, B: Borrow<M>>(
+        &self,
+        record_id: RecordId,
+        msg: B,
+    ) -> Result<(), Error<I>> {
+        self.send(record_id, msg).await?;
+        self.ordering_tx.request_flush();
+        let seen = self.priority_sends.fetch_add(1, Ordering::Relaxed) + 1;
+        // this channel does not need to step aside for its own message
+        self.priority_seen.fetch_max(seen, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Returns `true` if another channel to the same peer made a [`Self::send_priority`] call
+    /// since the last time this was checked.
+    fn priority_elsewhere(&self) -> bool {
+        let sends = self.priority_sends.load(Ordering::Relaxed);
+        self.priority_seen.fetch_max(sends, Ordering::Relaxed) < sends
+    }
+
     #[cfg(feature = "stall-detection")]
     pub fn waiting(&self) -> std::collections::BTreeSet<usize> {
         self.ordering_tx.waiting()
@@ -112,6 +169,15 @@ impl<I: TransportIdentity> GatewaySender<I> {
     pub async fn close(&self, at: RecordId) {
         self.ordering_tx.close(at.into()).await;
     }
+
+    pub async fn flush(&self) {
+        self.ordering_tx.flush().await;
+    }
+
+    /// Returns the record id of the next message this sender is waiting for.
+    pub fn next_record(&self) -> RecordId {
+        RecordId::from(self.ordering_tx.next_index())
+    }
 }
 
 impl<I: TransportIdentity, M: Message> SendingEnd<I, M> {
@@ -119,17 +185,38 @@ impl<I: TransportIdentity, M: Message> SendingEnd<I, M> {
         Self {
             sender_id: id,
             inner: sender,
+            rounds: None,
+            startup: None,
+            shut_down: false,
             _phantom: PhantomData,
         }
     }
 
+    pub(super) fn with_rounds(mut self, rounds: RoundCounter) -> Self {
+        self.rounds = Some(rounds);
+        self
+    }
+
+    pub(super) fn with_startup(mut self, startup: StartupCheck) -> Self {
+        self.startup = Some(startup);
+        self
+    }
+
+    pub(super) fn shut_down(mut self) -> Self {
+        self.shut_down = true;
+        self
+    }
+
     /// Sends the given message to the recipient. This method will block if there is no enough
     /// capacity to hold the message and will return only after message has been confirmed
     /// for sending.
     ///
     /// ## Errors
-    /// If send operation fails or `record_id` exceeds the channel limit set by [`set_total_records`]
-    /// call.
+    /// If send operation fails, the channel has been closed or `record_id` exceeds the channel
+    /// limit set by [`set_total_records`] call. In debug builds, sending to the helper's own
+    /// identity fails with [`Error::SelfSend`], as it points to a mistake in computing the peer.
+    /// If the channel was requested from a gateway that was no longer alive, every send fails
+    /// with [`Error::ShutDown`].
     ///
     /// [`set_total_records`]: crate::protocol::context::Context::set_total_records
     #[tracing::instrument(level = "trace", "send", skip_all, fields(
@@ -139,7 +226,103 @@ impl<I: TransportIdentity, M: Message> SendingEnd<I, M> {
         gate = ?self.inner.channel_id.gate.as_ref()
     ))]
     pub async fn send<B: Borrow<M>>(&self, record_id: RecordId, msg: B) -> Result<(), Error<I>> {
+        self.check_peer(record_id)?;
+        self.on_send();
+        let r = self.inner.send(record_id, msg).await;
+        self.record_metrics();
+
+        r
+    }
+
+    /// Sends a message that is already serialized, e.g. the one obtained from
+    /// [`MpcReceivingEnd::receive_raw`], without deserializing it first.
+    ///
+    /// ## Errors
+    /// Same as [`Self::send`].
+    ///
+    /// ## Panics
+    /// If `bytes` is not exactly the size of a serialized `M`.
+    ///
+    /// [`MpcReceivingEnd::receive_raw`]: crate::helpers::gateway::MpcReceivingEnd::receive_raw
+    #[tracing::instrument(level = "trace", "send_raw", skip_all, fields(
+        i = %record_id,
+        total = %self.inner.total_records,
+        to = ?self.inner.channel_id.peer,
+        gate = ?self.inner.channel_id.gate.as_ref()
+    ))]
+    pub async fn send_raw(&self, record_id: RecordId, bytes: &[u8]) -> Result<(), Error<I>> {
+        assert_eq!(
+            M::Size::USIZE,
+            bytes.len(),
+            "raw message must be exactly {} bytes",
+            M::Size::USIZE
+        );
+        let msg = RawMessage::<M>(GenericArray::clone_from_slice(bytes), PhantomData);
+        self.check_peer(record_id)?;
+        self.on_send();
         let r = self.inner.send(record_id, msg).await;
+        self.record_metrics();
+
+        r
+    }
+
+    /// Sends the given message with high priority. The message and everything sent on this
+    /// channel before it are made available to the transport right away, without waiting for
+    /// the send buffer to fill up. The other channels to the same peer step aside once when they
+    /// are polled next, so the transport has a chance to take the message before the backlog
+    /// accumulated on them. This is meant for control messages, e.g. validation failures, that
+    /// the peer must see as soon as possible.
+    ///
+    /// Priority is only a hint: it never holds back the other channels and this method does not
+    /// wait for the peer to receive the message.
+    ///
+    /// ## Errors
+    /// Same as [`Self::send`].
+    #[tracing::instrument(level = "trace", "send_priority", skip_all, fields(
+        i = %record_id,
+        total = %self.inner.total_records,
+        to = ?self.inner.channel_id.peer,
+        gate = ?self.inner.channel_id.gate.as_ref()
+    ))]
+    pub async fn send_priority<B: Borrow<M>>(
+        &self,
+        record_id: RecordId,
+        msg: B,
+    ) -> Result<(), Error<I>> {
+        self.check_peer(record_id)?;
+        self.on_send();
+        let r = self.inner.send_priority(record_id, msg).await;
+        self.record_metrics();
+
+        r
+    }
+
+    fn check_peer(&self, record_id: RecordId) -> Result<(), Error<I>> {
+        if self.shut_down {
+            return Err(Error::ShutDown {
+                channel_id: self.inner.channel_id.clone(),
+            });
+        }
+        if cfg!(debug_assertions) && self.inner.channel_id.peer == self.sender_id {
+            return Err(Error::SelfSend {
+                record_id,
+                channel_id: self.inner.channel_id.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn on_send(&self) {
+        if let Some(rounds) = &self.rounds {
+            rounds.on_send();
+        }
+        if let Some(startup) = &self.startup {
+            startup.on_activity();
+        }
+    }
+
+    fn record_metrics(&self) {
         metrics::increment_counter!(RECORDS_SENT,
             STEP => self.inner.channel_id.gate.as_ref().to_string(),
             ROLE => self.sender_id.as_str(),
@@ -148,8 +331,6 @@ impl<I: TransportIdentity, M: Message> SendingEnd<I, M> {
             STEP => self.inner.channel_id.gate.as_ref().to_string(),
             ROLE => self.sender_id.as_str(),
         );
-
-        r
     }
 
     /// Closes the sending channel at the specified record. After calling it, it will no longer be
@@ -159,10 +340,34 @@ impl<I: TransportIdentity, M: Message> SendingEnd<I, M> {
     /// ## Panics
     /// This may panic if method is called twice and futures created by it are awaited concurrently.
     pub async fn close(&self, at: RecordId) {
-        if !self.inner.is_closed() {
+        if !self.shut_down && !self.inner.is_closed() {
             self.inner.close(at).await;
         }
     }
+
+    /// Hands all the messages sent through this channel so far over to the transport, without
+    /// waiting for the send buffer to fill up. This can be used as a barrier at protocol phase
+    /// boundaries. Note that it does not wait for the peer to receive them.
+    pub async fn flush(&self) {
+        self.inner.flush().await;
+    }
+
+    /// Signals that this helper has nothing more to send on this channel, without affecting
+    /// any receiving channels it may have open with the same peer. Everything that was sent
+    /// so far gets flushed to the transport and the stream is terminated right after the
+    /// last record written. Any subsequent call to [`send`] will fail with
+    /// [`Error::SendAfterClose`].
+    ///
+    /// Unlike [`close`], it does not require the caller to know the position to close at, but
+    /// all in-flight sends on this channel must complete before calling it.
+    ///
+    /// [`send`]: Self::send
+    /// [`close`]: Self::close
+    pub async fn half_close(&self) {
+        if !self.inner.is_closed() {
+            self.inner.close(self.inner.next_record()).await;
+        }
+    }
 }
 
 impl<I: TransportIdentity> GatewaySenders<I> {
@@ -185,7 +390,18 @@ impl<I: TransportIdentity> GatewaySenders<I> {
         match self.inner.entry(channel_id.clone()) {
             Entry::Occupied(entry) => Arc::clone(entry.get()),
             Entry::Vacant(entry) => {
-                let sender = Self::new_sender::<M>(capacity, channel_id.clone(), total_records);
+                let priority_sends = Arc::clone(
+                    self.priority_sends
+                        .entry(channel_id.peer)
+                        .or_default()
+                        .value(),
+                );
+                let sender = Self::new_sender::<M>(
+                    capacity,
+                    channel_id.clone(),
+                    total_records,
+                    priority_sends,
+                );
                 entry.insert(Arc::clone(&sender));
 
                 tokio::spawn({
@@ -208,10 +424,21 @@ impl<I: TransportIdentity> GatewaySenders<I> {
         }
     }
 
+    /// Returns a channel that is not connected to the transport, for gateways that are no
+    /// longer alive.
+    pub fn detached<M: Message>(
+        channel_id: &ChannelId<I>,
+        capacity: NonZeroUsize,
+        total_records: TotalRecords,
+    ) -> Arc<GatewaySender<I>> {
+        Self::new_sender::<M>(capacity, channel_id.clone(), total_records, Arc::default())
+    }
+
     fn new_sender<M: Message>(
         capacity: NonZeroUsize,
         channel_id: ChannelId<I>,
         total_records: TotalRecords,
+        priority_sends: Arc<AtomicUsize>,
     ) -> Arc<GatewaySender<I>> {
         // Spare buffer is not required when messages have uniform size and buffer is a
         // multiple of that size.
@@ -236,6 +463,7 @@ impl<I: TransportIdentity> GatewaySenders<I> {
             channel_id,
             OrderingSender::new(write_size, SPARE),
             total_records,
+            priority_sends,
         ))
     }
 }
@@ -244,6 +472,13 @@ impl<I> Stream for GatewaySendStream<I> {
     type Item = Vec<u8>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Pin::get_mut(self).inner.ordering_tx.take_next(cx)
+        let sender = &Pin::get_mut(self).inner;
+        if sender.priority_elsewhere() {
+            // Let the transport poll the channel with the high priority message first. This
+            // must not hold the data back for longer than that, so wake up right away.
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        sender.ordering_tx.take_next(cx)
     }
 }
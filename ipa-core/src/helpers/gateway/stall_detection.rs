@@ -73,11 +73,12 @@ mod gateway {
     use super::{receive, send, AtomicUsize, Debug, Formatter, ObserveState, Observed, Weak};
     use crate::{
         helpers::{
-            gateway::{Gateway, ShardTransportImpl, State},
-            GatewayConfig, HelperChannelId, Message, MpcMessage, MpcReceivingEnd, MpcTransportImpl,
-            Role, RoleAssignment, SendingEnd, ShardChannelId, ShardReceivingEnd, TotalRecords,
+            gateway::{ChannelSnapshot, Gateway, ShardTransportImpl, State},
+            Error, GatewayConfig, HelperChannelId, Message, MpcMessage, MpcReceivingEnd,
+            MpcTransportImpl, Role, RoleAssignment, SendingEnd, ShardChannelId, ShardReceivingEnd,
+            TotalRecords,
         },
-        protocol::QueryId,
+        protocol::{step::Gate, QueryId},
         sharding::ShardIndex,
         sync::Arc,
     };
@@ -96,8 +97,32 @@ mod gateway {
                 #[inline]
                 pub fn role(&self) -> Role;
 
+                #[inline]
+                pub fn left(&self) -> Role;
+
+                #[inline]
+                pub fn right(&self) -> Role;
+
                 #[inline]
                 pub fn config(&self) -> &GatewayConfig;
+
+                #[inline]
+                pub async fn abort(&self, reason: &str);
+
+                #[inline]
+                pub fn rounds(&self) -> usize;
+
+                #[inline]
+                pub fn is_alive(&self) -> bool;
+
+                #[inline]
+                pub fn active_channels(&self) -> Vec<HelperChannelId>;
+
+                #[inline]
+                pub fn debug_snapshot(&self) -> Vec<ChannelSnapshot>;
+
+                #[inline]
+                pub fn prewarm(&self, gates: &[Gate]);
             }
         }
 
@@ -162,6 +187,21 @@ mod gateway {
             )
         }
 
+        /// See [`Gateway::try_get_mpc_sender`].
+        ///
+        /// ## Errors
+        /// If the gateway is no longer alive.
+        pub fn try_get_mpc_sender<M: MpcMessage>(
+            &self,
+            channel_id: &HelperChannelId,
+            total_records: TotalRecords,
+        ) -> Result<SendingEnd<Role, M>, Error<Role>> {
+            self.inner()
+                .gateway
+                .try_get_mpc_sender(channel_id, total_records)
+                .map(|sender| Observed::wrap(Weak::clone(self.get_sn()), sender))
+        }
+
         pub fn get_shard_sender<M: Message>(
             &self,
             channel_id: &ShardChannelId,
@@ -186,6 +226,20 @@ mod gateway {
             )
         }
 
+        /// See [`Gateway::try_get_mpc_receiver`].
+        ///
+        /// ## Errors
+        /// If the gateway is no longer alive.
+        pub fn try_get_mpc_receiver<M: MpcMessage>(
+            &self,
+            channel_id: &HelperChannelId,
+        ) -> Result<MpcReceivingEnd<M>, Error<Role>> {
+            self.inner()
+                .gateway
+                .try_get_mpc_receiver(channel_id)
+                .map(|receiver| Observed::wrap(Weak::clone(self.get_sn()), receiver))
+        }
+
         pub fn get_shard_receiver<M: Message>(
             &self,
             channel_id: &ShardChannelId,
@@ -264,6 +318,7 @@ mod receive {
     use std::{
         collections::BTreeMap,
         fmt::{Debug, Formatter},
+        num::NonZeroUsize,
         pin::Pin,
         task::{Context, Poll},
     };
@@ -289,6 +344,14 @@ mod receive {
             to { self.advance(); self.inner() } {
                 #[inline]
                 pub async fn receive(&self, record_id: RecordId) -> Result<M, Error<Role>>;
+                #[inline]
+                pub async fn receive_retry(&self, record_id: RecordId, max_retries: usize) -> Result<M, Error<Role>>;
+                #[inline]
+                pub async fn receive_raw(&self, record_id: RecordId) -> Result<Box<[u8]>, Error<Role>>;
+                #[inline]
+                pub async fn closed(&self);
+                #[inline]
+                pub fn receive_windowed(&self, start: RecordId, total: usize, window: NonZeroUsize) -> Result<impl Stream<Item = Result<Vec<M>, Error<Role>>> + '_, crate::error::Error>;
             }
         }
     }
@@ -375,7 +438,15 @@ mod send {
                 #[inline]
                 pub async fn send<B: Borrow<M>>(&self, record_id: RecordId, msg: B) -> Result<(), Error<I>>;
                 #[inline]
+                pub async fn send_raw(&self, record_id: RecordId, bytes: &[u8]) -> Result<(), Error<I>>;
+                #[inline]
+                pub async fn send_priority<B: Borrow<M>>(&self, record_id: RecordId, msg: B) -> Result<(), Error<I>>;
+                #[inline]
                 pub async fn close(&self, at: RecordId);
+                #[inline]
+                pub async fn half_close(&self);
+                #[inline]
+                pub async fn flush(&self);
             }
         }
     }
@@ -0,0 +1,94 @@
+use std::{
+    future,
+    time::{Duration, Instant},
+};
+
+use crate::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Startup health check of a gateway, see [`GatewayConfig::startup_deadline`].
+///
+/// Any send or receive on the gateway channels counts as activity. The deadline is checked
+/// whenever the gateway is asked whether it is alive, and every MPC receive started before there
+/// was any activity arms a timer for it (see [`Self::expired`]), so receives that are pending when
+/// the deadline passes fail instead of hanging. Once failed, the check stays failed even if
+/// activity happens later.
+///
+/// [`GatewayConfig::startup_deadline`]: crate::helpers::GatewayConfig::startup_deadline
+#[derive(Clone, Default)]
+pub(super) struct StartupCheck(Arc<StartupState>);
+
+#[derive(Default)]
+struct StartupState {
+    deadline: Option<Instant>,
+    active: AtomicBool,
+    failed: AtomicBool,
+}
+
+impl StartupCheck {
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self(Arc::new(StartupState {
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            ..StartupState::default()
+        }))
+    }
+
+    /// Records that something was sent to or received from a peer. This sits on the send and
+    /// receive paths, so it only writes to the shared state once.
+    pub fn on_activity(&self) {
+        if !self.0.active.load(Ordering::Relaxed) {
+            self.0.active.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns `false` if there was no activity before the deadline passed.
+    pub fn passed(&self) -> bool {
+        let state = &self.0;
+        if state.failed.load(Ordering::Relaxed) {
+            return false;
+        }
+        if state.active.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        match state.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                if !state.failed.swap(true, Ordering::Relaxed) {
+                    tracing::error!(
+                        "nothing was sent to or received from peer helpers before the startup \
+                         deadline, the network may be misconfigured. Shutting down the gateway"
+                    );
+                }
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns `true` if a receive has to watch for the check to fail, see [`Self::expired`].
+    pub fn is_armed(&self) -> bool {
+        let state = &self.0;
+        state.deadline.is_some()
+            && (!state.active.load(Ordering::Relaxed) || state.failed.load(Ordering::Relaxed))
+    }
+
+    /// Resolves once the check fails. Stays pending if there is no deadline or if there was
+    /// activity before it, so there is no timer to arm once the gateway is up and running.
+    pub async fn expired(&self) {
+        let state = &self.0;
+        match state.deadline {
+            Some(deadline) if !state.active.load(Ordering::Relaxed) => {
+                ::tokio::time::sleep_until(deadline.into()).await;
+                if !self.passed() {
+                    return;
+                }
+            }
+            _ if state.failed.load(Ordering::Relaxed) => return,
+            _ => {}
+        }
+
+        future::pending().await
+    }
+}
@@ -82,4 +82,8 @@ impl Transport for RoleResolvingTransport {
 
         self.inner.receive(origin_helper, route)
     }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
 }
@@ -54,6 +54,9 @@ mod gateway_exports {
     pub type ShardReceivingEnd<M> = gateway::ShardReceivingEnd<M>;
 }
 
+pub(crate) use gateway::AbortStep;
+#[cfg(feature = "stall-detection")]
+pub use gateway::ChannelSnapshot;
 pub use gateway::GatewayConfig;
 // TODO: this type should only be available within infra. Right now several infra modules
 // are exposed at the root level. That makes it impossible to have a proper hierarchy here.
@@ -66,12 +69,15 @@ pub use prss_protocol::negotiate as negotiate_prss;
 pub use transport::WrappedAxumBodyStream;
 pub use transport::{
     make_owned_handler, query, routing, ApiError, BodyStream, BytesStream, HandlerBox, HandlerRef,
-    HelperResponse, Identity as TransportIdentity, LengthDelimitedStream, LogErrors, NoQueryId,
+    HelperResponse, Identity as TransportIdentity, LengthDelimitedStream, NoQueryId,
     NoResourceIdentifier, NoStep, QueryIdBinding, ReceiveRecords, RecordsStream, RequestHandler,
     RouteParams, StepBinding, StreamCollection, StreamKey, Transport, WrappedBoxBodyStream,
 };
 #[cfg(feature = "in-memory-infra")]
-pub use transport::{InMemoryMpcNetwork, InMemoryShardNetwork, InMemoryTransport};
+pub use transport::{
+    InMemoryMpcNetwork, InMemoryShardNetwork, InMemoryTransport, MessageTrace, RecordedChannel,
+    RecordedChunk, RecordingNetwork, ReplayNetwork,
+};
 use typenum::{Unsigned, U8};
 use x25519_dalek::PublicKey;
 
@@ -290,6 +296,36 @@ impl Role {
         }
     }
 
+    /// Returns the other two roles in ring order: the peer to the right first, then the one to
+    /// the left. Protocols that broadcast to all peers, like a barrier or an abort, can iterate
+    /// over them.
+    #[must_use]
+    pub const fn peers(&self) -> [Role; 2] {
+        [self.peer(Direction::Right), self.peer(Direction::Left)]
+    }
+
+    /// Stable index of this role, suitable for compact serialization: 0 for `H1`, 1 for `H2` and
+    /// 2 for `H3`. This is the same order roles are indexed by in arrays of per-helper values.
+    #[must_use]
+    pub const fn as_index(&self) -> u8 {
+        match self {
+            H1 => 0,
+            H2 => 1,
+            H3 => 2,
+        }
+    }
+
+    /// The inverse of [`Self::as_index`]. Returns `None` if `index` does not belong to any role.
+    #[must_use]
+    pub const fn from_index(index: u8) -> Option<Role> {
+        match index {
+            0 => Some(H1),
+            1 => Some(H2),
+            2 => Some(H3),
+            _ => None,
+        }
+    }
+
     /// Need `derive_const` feature to get out of nigntly to get rid of this function.
     #[must_use]
     pub const fn eq(self, other: Self) -> bool {
@@ -391,6 +427,13 @@ impl RoleAssignment {
     pub fn identity(&self, role: Role) -> HelperIdentity {
         self.helper_roles[role]
     }
+
+    /// Returns identities of the helpers assigned to the peers of `role`, in the order given by
+    /// [`Role::peers`].
+    #[must_use]
+    pub fn peer_identities(&self, role: Role) -> [HelperIdentity; 2] {
+        role.peers().map(|peer| self.identity(peer))
+    }
 }
 
 impl TryFrom<[(HelperIdentity, Role); 3]> for RoleAssignment {
@@ -616,6 +659,28 @@ mod tests {
             assert_eq!(4, data[Role::H2]);
             assert_eq!(5, data[Role::H3]);
         }
+
+        #[test]
+        pub fn wire_index() {
+            for &role in Role::all() {
+                assert_eq!(Some(role), Role::from_index(role.as_index()));
+                assert_eq!([3, 4, 5][role], [3, 4, 5][usize::from(role.as_index())]);
+            }
+            assert_eq!(None, Role::from_index(3));
+            assert_eq!(None, Role::from_index(u8::MAX));
+        }
+
+        #[test]
+        pub fn peers() {
+            assert_eq!([Role::H2, Role::H3], Role::H1.peers());
+            assert_eq!([Role::H3, Role::H1], Role::H2.peers());
+            assert_eq!([Role::H1, Role::H2], Role::H3.peers());
+            for &role in Role::all() {
+                let [a, b] = role.peers();
+                assert_ne!(a, b);
+                assert!(a != role && b != role);
+            }
+        }
     }
 
     mod role_assignment_tests {
@@ -658,6 +723,11 @@ mod tests {
             assert_eq!(HelperIdentity::from(3), assignment.identity(Role::H1));
             assert_eq!(HelperIdentity::from(2), assignment.identity(Role::H2));
             assert_eq!(HelperIdentity::from(1), assignment.identity(Role::H3));
+
+            assert_eq!(
+                [HelperIdentity::from(2), HelperIdentity::from(1)],
+                assignment.peer_identities(Role::H1)
+            );
         }
 
         #[test]
@@ -1,8 +1,10 @@
+mod replay;
 mod sharding;
 mod transport;
 
 use std::array;
 
+pub use replay::{MessageTrace, RecordedChannel, RecordedChunk, RecordingNetwork, ReplayNetwork};
 pub use sharding::InMemoryShardNetwork;
 pub use transport::Setup;
 
@@ -28,8 +30,11 @@ impl Default for InMemoryMpcNetwork {
 impl InMemoryMpcNetwork {
     #[must_use]
     pub fn new(handlers: [Option<HandlerRef>; 3]) -> Self {
-        let [mut first, mut second, mut third]: [_; 3] =
-            HelperIdentity::make_three().map(Setup::new);
+        Self::from_setups(HelperIdentity::make_three().map(Setup::new), handlers)
+    }
+
+    fn from_setups(setups: [Setup<HelperIdentity>; 3], handlers: [Option<HandlerRef>; 3]) -> Self {
+        let [mut first, mut second, mut third] = setups;
 
         first.connect(&mut second);
         second.connect(&mut third);
@@ -67,3 +72,134 @@ impl InMemoryMpcNetwork {
         }
     }
 }
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    #[cfg(feature = "shuttle")]
+    use futures::future::join_all;
+
+    use crate::{
+        ff::{Fp31, U128Conversions},
+        helpers::{
+            transport::in_memory::{InMemoryMpcNetwork, InMemoryShardNetwork},
+            Gateway, GatewayConfig, HelperChannelId, HelperIdentity, Role, RoleAssignment,
+            Transport,
+        },
+        protocol::{step::Gate, QueryId, RecordId},
+        sharding::ShardIndex,
+        test_executor::run,
+    };
+
+    /// Wires three gateways together over the in-memory network, without going through
+    /// `TestWorld`, and makes sure messages sent from one helper arrive at its peer.
+    #[test]
+    fn gateways_talk_to_each_other() {
+        run(|| async move {
+            let network = InMemoryMpcNetwork::default();
+            let shard_network = InMemoryShardNetwork::with_shards(1_u32);
+            let roles = RoleAssignment::new(HelperIdentity::make_three());
+            let gateways = network
+                .transports()
+                .iter()
+                .zip(shard_network.shard_transports(ShardIndex::FIRST))
+                .map(|(mpc, shard)| {
+                    Gateway::new(
+                        QueryId::DEFAULT,
+                        GatewayConfig::default(),
+                        roles.clone(),
+                        Transport::clone_ref(mpc),
+                        shard,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let gate = Gate::from("in-memory-network");
+            let h1 = gateways.iter().find(|g| g.role() == Role::H1).unwrap();
+            let h2 = gateways.iter().find(|g| g.role() == Role::H2).unwrap();
+
+            let input = Fp31::truncate_from(7_u128);
+            h1.get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, gate.clone()), 1.into())
+                .send(RecordId::FIRST, input)
+                .await
+                .unwrap();
+            let output = h2
+                .get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, gate))
+                .receive(RecordId::FIRST)
+                .await
+                .unwrap();
+
+            assert_eq!(input, output);
+        });
+    }
+
+    /// The in-memory network does not pick a delivery order itself, it is whatever order the
+    /// executor schedules the transport tasks in. Under shuttle that schedule is derived from a
+    /// seed, so the same seed replays the same interleaving.
+    #[cfg(feature = "shuttle")]
+    #[test]
+    fn seeded_delivery_is_reproducible() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::test_executor::run_seeded;
+
+        fn delivery_order(seed: u64) -> Vec<Role> {
+            let order = Arc::new(Mutex::new(Vec::new()));
+            run_seeded(seed, {
+                let order = Arc::clone(&order);
+                move || {
+                    let order = Arc::clone(&order);
+                    async move {
+                        let network = InMemoryMpcNetwork::default();
+                        let shard_network = InMemoryShardNetwork::with_shards(1_u32);
+                        let roles = RoleAssignment::new(HelperIdentity::make_three());
+                        let gateways = network
+                            .transports()
+                            .iter()
+                            .zip(shard_network.shard_transports(ShardIndex::FIRST))
+                            .map(|(mpc, shard)| {
+                                Gateway::new(
+                                    QueryId::DEFAULT,
+                                    GatewayConfig::default(),
+                                    roles.clone(),
+                                    Transport::clone_ref(mpc),
+                                    shard,
+                                )
+                            })
+                            .collect::<Vec<_>>();
+
+                        let gate = Gate::from("seeded");
+                        join_all(gateways.iter().map(|gateway| {
+                            let gate = gate.clone();
+                            let order = Arc::clone(&order);
+                            async move {
+                                let to = HelperChannelId::new(gateway.right(), gate.clone());
+                                let from = HelperChannelId::new(gateway.left(), gate);
+                                gateway
+                                    .get_mpc_sender::<Fp31>(&to, 1.into())
+                                    .send(RecordId::FIRST, Fp31::truncate_from(1_u128))
+                                    .await
+                                    .unwrap();
+                                gateway
+                                    .get_mpc_receiver::<Fp31>(&from)
+                                    .receive(RecordId::FIRST)
+                                    .await
+                                    .unwrap();
+                                order.lock().unwrap().push(gateway.role());
+                            }
+                        }))
+                        .await;
+                    }
+                }
+            });
+
+            let mut guard = order.lock().unwrap();
+            std::mem::take(&mut *guard)
+        }
+
+        for seed in [0, 42, 0xdead_beef] {
+            let order = delivery_order(seed);
+            assert_eq!(3, order.len());
+            assert_eq!(order, delivery_order(seed));
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io,
+    path::Path,
+    task::{Context, Poll, Waker},
+};
+
+use bytes::Bytes;
+use futures::stream;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::{
+        transport::in_memory::{
+            transport::{Recording, Setup, StreamItem},
+            InMemoryMpcNetwork,
+        },
+        HelperIdentity,
+    },
+    protocol::{step::Gate, QueryId},
+    sync::{Arc, Mutex},
+};
+
+/// Record streams that one helper received from its peers, and the chunks it read from them in
+/// the order it read them. Use [`RecordingNetwork`] to capture a trace and [`ReplayNetwork`] to
+/// feed it back to a helper.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageTrace {
+    pub channels: Vec<RecordedChannel>,
+    pub chunks: Vec<RecordedChunk>,
+}
+
+/// A single record stream, in the order the streams arrived.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedChannel {
+    pub query_id: QueryId,
+    pub from: HelperIdentity,
+    pub gate: String,
+}
+
+/// Bytes that a helper read from one of the [`MessageTrace::channels`] at once.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedChunk {
+    /// Index into [`MessageTrace::channels`].
+    pub channel: usize,
+    pub data: Vec<u8>,
+}
+
+impl MessageTrace {
+    /// Saves this trace to `path` as JSON.
+    ///
+    /// ## Errors
+    /// If the file can't be created or written to.
+    pub fn write_to(&self, path: &Path) -> io::Result<()> {
+        serde_json::to_writer(File::create(path)?, self)?;
+        Ok(())
+    }
+
+    /// Loads a trace previously saved with [`Self::write_to`].
+    ///
+    /// ## Errors
+    /// If the file can't be read or does not hold a trace.
+    pub fn read_from(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_reader(File::open(path)?)?)
+    }
+}
+
+/// In-memory network that records the bytes each helper receives from its peers, so that
+/// a run that fails intermittently can be replayed with [`ReplayNetwork`].
+pub struct RecordingNetwork {
+    network: InMemoryMpcNetwork,
+    recordings: [Recording<HelperIdentity>; 3],
+}
+
+impl Default for RecordingNetwork {
+    fn default() -> Self {
+        let mut setups = HelperIdentity::make_three().map(Setup::new);
+        let recordings = setups.each_mut().map(Setup::record);
+
+        Self {
+            network: InMemoryMpcNetwork::from_setups(setups, [None, None, None]),
+            recordings,
+        }
+    }
+}
+
+impl RecordingNetwork {
+    #[must_use]
+    pub fn network(&self) -> &InMemoryMpcNetwork {
+        &self.network
+    }
+
+    /// Returns what helper `id` has received so far. Only the bytes that the helper actually
+    /// read from each stream are included.
+    ///
+    /// ## Panics
+    /// If the recording lock is poisoned.
+    #[must_use]
+    pub fn trace(&self, id: HelperIdentity) -> MessageTrace {
+        let recording = self.recordings[id].lock().unwrap();
+        MessageTrace {
+            channels: recording
+                .streams
+                .iter()
+                .map(|(query_id, from, gate)| RecordedChannel {
+                    query_id: *query_id,
+                    from: *from,
+                    gate: gate.as_ref().to_owned(),
+                })
+                .collect(),
+            chunks: recording
+                .chunks
+                .iter()
+                .map(|(channel, data)| RecordedChunk {
+                    channel: *channel,
+                    data: data.to_vec(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// In-memory network where one helper receives everything from a recorded [`MessageTrace`].
+/// The other helpers do not need to run: messages sent to them are accepted, but nobody reads
+/// them.
+///
+/// Chunks are handed out in the order they were recorded: a stream only yields its next chunk
+/// once the chunks recorded before it have been read from their streams.
+pub struct ReplayNetwork {
+    network: InMemoryMpcNetwork,
+}
+
+impl ReplayNetwork {
+    /// ## Panics
+    /// If the trace has more than one stream for the same query, sender and gate, or if a chunk
+    /// refers to a stream that is not in the trace.
+    #[must_use]
+    pub fn new(id: HelperIdentity, trace: &MessageTrace) -> Self {
+        let network = InMemoryMpcNetwork::default();
+        let transport = &network.transports[id];
+        let sequencer = Arc::new(Mutex::new(Sequencer::new(trace)));
+        for (index, channel) in trace.channels.iter().enumerate() {
+            let sequencer = Arc::clone(&sequencer);
+            transport.inject(
+                channel.query_id,
+                channel.from,
+                Gate::from(channel.gate.as_str()),
+                stream::poll_fn(move |cx| sequencer.lock().unwrap().poll_next(index, cx)),
+            );
+        }
+
+        Self { network }
+    }
+
+    #[must_use]
+    pub fn network(&self) -> &InMemoryMpcNetwork {
+        &self.network
+    }
+}
+
+/// Hands out the chunks of a [`MessageTrace`] to the streams of [`ReplayNetwork`] in order.
+struct Sequencer {
+    chunks: VecDeque<(usize, Bytes)>,
+    /// Number of chunks that each stream has yet to yield.
+    remaining: Vec<usize>,
+    /// Streams waiting for their next chunk to come up.
+    wakers: Vec<Option<Waker>>,
+}
+
+impl Sequencer {
+    fn new(trace: &MessageTrace) -> Self {
+        let mut remaining = vec![0; trace.channels.len()];
+        for chunk in &trace.chunks {
+            remaining[chunk.channel] += 1;
+        }
+
+        Self {
+            chunks: trace
+                .chunks
+                .iter()
+                .map(|chunk| (chunk.channel, Bytes::from(chunk.data.clone())))
+                .collect(),
+            remaining,
+            wakers: vec![None; trace.channels.len()],
+        }
+    }
+
+    fn poll_next(&mut self, channel: usize, cx: &mut Context<'_>) -> Poll<Option<StreamItem>> {
+        if self.remaining[channel] == 0 {
+            return Poll::Ready(None);
+        }
+        if self.chunks.front().map(|(next, _)| *next) != Some(channel) {
+            self.wakers[channel] = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let (_, data) = self.chunks.pop_front().unwrap();
+        self.remaining[channel] -= 1;
+        if let Some(waker) = self
+            .chunks
+            .front()
+            .and_then(|(next, _)| self.wakers[*next].take())
+        {
+            waker.wake();
+        }
+
+        Poll::Ready(Some(Ok(data)))
+    }
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use std::iter::zip;
+
+    use futures::future::join;
+
+    use super::{MessageTrace, RecordingNetwork, ReplayNetwork};
+    use crate::{
+        ff::{Fp31, U128Conversions},
+        helpers::{
+            transport::in_memory::{InMemoryMpcNetwork, InMemoryShardNetwork},
+            Gateway, GatewayConfig, HelperChannelId, HelperIdentity, Role, RoleAssignment,
+            Transport,
+        },
+        protocol::{step::Gate, QueryId, RecordId},
+        sharding::ShardIndex,
+        test_executor::run,
+    };
+
+    fn gateways(
+        query_id: QueryId,
+        network: &InMemoryMpcNetwork,
+        shards: &InMemoryShardNetwork,
+    ) -> Vec<Gateway> {
+        let roles = RoleAssignment::new(HelperIdentity::make_three());
+        network
+            .transports()
+            .iter()
+            .zip(shards.shard_transports(ShardIndex::FIRST))
+            .map(|(mpc, shard)| {
+                Gateway::new(
+                    query_id,
+                    GatewayConfig::default(),
+                    roles.clone(),
+                    Transport::clone_ref(mpc),
+                    shard,
+                )
+            })
+            .collect()
+    }
+
+    async fn receive_all(gateway: &Gateway) -> Vec<Fp31> {
+        let mut values = Vec::new();
+        for (peer, gate, count) in [(Role::H1, "replay_a", 2_u32), (Role::H3, "replay_b", 1)] {
+            let channel =
+                gateway.get_mpc_receiver::<Fp31>(&HelperChannelId::new(peer, gate.into()));
+            for i in 0..count {
+                values.push(channel.receive(RecordId::from(i)).await.unwrap());
+            }
+        }
+        values
+    }
+
+    #[test]
+    fn replay_reproduces_received_messages() {
+        run(|| async move {
+            let recording = RecordingNetwork::default();
+            let shards = InMemoryShardNetwork::with_shards(1_u32);
+            let recorded = gateways(QueryId::DEFAULT, recording.network(), &shards);
+            let gateway = |role| recorded.iter().find(|g| g.role() == role).unwrap();
+
+            let to_h2 = |gate: &str| HelperChannelId::new(Role::H2, Gate::from(gate));
+            let h1 = gateway(Role::H1).get_mpc_sender::<Fp31>(&to_h2("replay_a"), 2.into());
+            let h3 = gateway(Role::H3).get_mpc_sender::<Fp31>(&to_h2("replay_b"), 1.into());
+            for (i, v) in [4_u128, 9].into_iter().enumerate() {
+                h1.send(RecordId::from(i), Fp31::truncate_from(v))
+                    .await
+                    .unwrap();
+            }
+            h3.send(RecordId::FIRST, Fp31::truncate_from(21_u128))
+                .await
+                .unwrap();
+
+            let received = receive_all(gateway(Role::H2)).await;
+            assert_eq!([4_u128, 9, 21].map(Fp31::truncate_from).to_vec(), received);
+
+            let file = tempfile::NamedTempFile::new().unwrap();
+            let trace = recording.trace(HelperIdentity::TWO);
+            assert_eq!(2, trace.channels.len());
+            trace.write_to(file.path()).unwrap();
+            let trace = MessageTrace::read_from(file.path()).unwrap();
+
+            let replay = ReplayNetwork::new(HelperIdentity::TWO, &trace);
+            let shards = InMemoryShardNetwork::with_shards(1_u32);
+            let replayed = gateways(QueryId::DEFAULT, replay.network(), &shards);
+            let h2 = replayed.iter().find(|g| g.role() == Role::H2).unwrap();
+            assert_eq!(received, receive_all(h2).await);
+        });
+    }
+
+    #[test]
+    fn replay_keeps_query_ids() {
+        const GATE: &str = "replay_query";
+
+        async fn receive(gateways: &[Gateway]) -> Fp31 {
+            let h2 = gateways.iter().find(|g| g.role() == Role::H2).unwrap();
+            h2.get_mpc_receiver::<Fp31>(&HelperChannelId::new(Role::H1, GATE.into()))
+                .receive(RecordId::FIRST)
+                .await
+                .unwrap()
+        }
+
+        run(|| async move {
+            let queries = [QueryId::DEFAULT, QueryId::new(7)];
+            let recording = RecordingNetwork::default();
+            let shards = InMemoryShardNetwork::with_shards(1_u32);
+            let recorded = queries.map(|query_id| gateways(query_id, recording.network(), &shards));
+            for (query, v) in zip(&recorded, [4_u128, 9]) {
+                let h1 = query.iter().find(|g| g.role() == Role::H1).unwrap();
+                h1.get_mpc_sender::<Fp31>(&HelperChannelId::new(Role::H2, GATE.into()), 1.into())
+                    .send(RecordId::FIRST, Fp31::truncate_from(v))
+                    .await
+                    .unwrap();
+            }
+            // read the second query first
+            let second = receive(&recorded[1]).await;
+            let first = receive(&recorded[0]).await;
+            assert_eq!([4_u128, 9].map(Fp31::truncate_from), [first, second]);
+
+            let trace = recording.trace(HelperIdentity::TWO);
+            assert_eq!(
+                [queries[1], queries[0]].to_vec(),
+                trace
+                    .chunks
+                    .iter()
+                    .map(|chunk| trace.channels[chunk.channel].query_id)
+                    .collect::<Vec<_>>()
+            );
+
+            let replay = ReplayNetwork::new(HelperIdentity::TWO, &trace);
+            let shards = InMemoryShardNetwork::with_shards(1_u32);
+            let replayed = queries.map(|query_id| gateways(query_id, replay.network(), &shards));
+            let (first, second) = join(receive(&replayed[0]), receive(&replayed[1])).await;
+            assert_eq!([4_u128, 9].map(Fp31::truncate_from), [first, second]);
+        });
+    }
+}
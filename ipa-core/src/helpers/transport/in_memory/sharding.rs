@@ -118,7 +118,7 @@ mod tests {
                         .transport(identity, a)
                         .send(
                             b,
-                            (RouteId::Records, QueryId, Gate::default()),
+                            (RouteId::Records, QueryId::DEFAULT, Gate::default()),
                             ReceiverStream::new(rx),
                         )
                         .await
@@ -129,7 +129,7 @@ mod tests {
                 for (a, b) in shard_pairs(shard_count) {
                     sum += shard_network
                         .transport(identity, a)
-                        .receive(b, (QueryId, Gate::default()))
+                        .receive(b, (QueryId::DEFAULT, Gate::default()))
                         .into_bytes_stream()
                         .collect::<Vec<_>>()
                         .await
@@ -168,7 +168,7 @@ mod tests {
                 .transport(HelperIdentity::ONE, src_shard)
                 .send(
                     dst_shard,
-                    (RouteId::Records, QueryId, Gate::default()),
+                    (RouteId::Records, QueryId::DEFAULT, Gate::default()),
                     ReceiverStream::new(rx),
                 )
                 .await
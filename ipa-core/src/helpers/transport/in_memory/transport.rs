@@ -23,11 +23,11 @@ use crate::{
     helpers::{
         transport::routing::{Addr, RouteId},
         ApiError, BodyStream, HandlerRef, HelperResponse, NoResourceIdentifier, QueryIdBinding,
-        ReceiveRecords, RequestHandler, RouteParams, StepBinding, StreamCollection, Transport,
-        TransportIdentity,
+        ReceiveRecords, RequestHandler, RouteParams, StepBinding, StreamCollection, StreamKey,
+        Transport, TransportIdentity,
     },
     protocol::{step::Gate, QueryId},
-    sync::{Arc, Weak},
+    sync::{Arc, Mutex, Weak},
 };
 
 type Packet<I> = (
@@ -37,7 +37,25 @@ type Packet<I> = (
 );
 type ConnectionTx<I> = Sender<Packet<I>>;
 type ConnectionRx<I> = Receiver<Packet<I>>;
-type StreamItem = Result<Bytes, BoxError>;
+pub(super) type StreamItem = Result<Bytes, BoxError>;
+/// Record streams a transport received and the chunks read from them. See [`Setup::record`].
+pub(super) struct RecordingState<I> {
+    /// Query id, sender and gate of every stream, in the order the streams arrived.
+    pub streams: Vec<StreamKey<I>>,
+    /// Index into `streams` and bytes of every chunk, in the order the receiver read them.
+    pub chunks: Vec<(usize, Bytes)>,
+}
+
+impl<I> Default for RecordingState<I> {
+    fn default() -> Self {
+        Self {
+            streams: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+}
+
+pub(super) type Recording<I> = Arc<Mutex<RecordingState<I>>>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error<I> {
@@ -66,15 +84,21 @@ pub struct InMemoryTransport<I> {
     identity: I,
     connections: HashMap<I, ConnectionTx<I>>,
     record_streams: StreamCollection<I, InMemoryStream>,
+    recording: Option<Recording<I>>,
 }
 
 impl<I: TransportIdentity> InMemoryTransport<I> {
     #[must_use]
-    fn new(identity: I, connections: HashMap<I, ConnectionTx<I>>) -> Self {
+    fn new(
+        identity: I,
+        connections: HashMap<I, ConnectionTx<I>>,
+        recording: Option<Recording<I>>,
+    ) -> Self {
         Self {
             identity,
             connections,
             record_streams: StreamCollection::default(),
+            recording,
         }
     }
 
@@ -91,6 +115,7 @@ impl<I: TransportIdentity> InMemoryTransport<I> {
         tokio::spawn(
             {
                 let streams = self.record_streams.clone();
+                let recording = self.recording.clone();
                 async move {
                     while let Some((addr, stream, ack)) = rx.recv().await {
                         tracing::trace!("received new message: {addr:?}");
@@ -100,6 +125,14 @@ impl<I: TransportIdentity> InMemoryTransport<I> {
                                 let query_id = addr.query_id.unwrap();
                                 let gate = addr.gate.unwrap();
                                 let from = addr.origin.unwrap();
+                                let stream = match &recording {
+                                    Some(recording) => record_stream(
+                                        recording,
+                                        (query_id, from, gate.clone()),
+                                        stream,
+                                    ),
+                                    None => stream,
+                                };
                                 streams.add_stream((query_id, from, gate), stream);
                                 Ok(HelperResponse::ok())
                             }
@@ -140,6 +173,41 @@ impl<I: TransportIdentity> InMemoryTransport<I> {
     pub fn reset(&self) {
         self.record_streams.clear();
     }
+
+    /// Makes this transport behave as if `from` opened `stream` as the record stream for `gate`
+    /// of query `query_id`.
+    pub(crate) fn inject<S>(&self, query_id: QueryId, from: I, gate: Gate, stream: S)
+    where
+        S: Stream<Item = StreamItem> + Send + 'static,
+    {
+        self.record_streams
+            .add_stream((query_id, from, gate), InMemoryStream::wrap(stream));
+    }
+}
+
+/// Adds `key` to the streams of `recording`, and every chunk that the receiver reads from
+/// `stream` to its chunks.
+fn record_stream<I: TransportIdentity>(
+    recording: &Recording<I>,
+    key: StreamKey<I>,
+    stream: InMemoryStream,
+) -> InMemoryStream {
+    let recording = Arc::clone(recording);
+    let index = {
+        let mut recording = recording.lock().unwrap();
+        recording.streams.push(key);
+        recording.streams.len() - 1
+    };
+
+    InMemoryStream::wrap(stream.inspect(move |item| {
+        if let Ok(bytes) = item {
+            recording
+                .lock()
+                .unwrap()
+                .chunks
+                .push((index, bytes.clone()));
+        }
+    }))
 }
 
 #[async_trait]
@@ -207,6 +275,10 @@ impl<I: TransportIdentity> Transport for Weak<InMemoryTransport<I>> {
             self.upgrade().unwrap().record_streams.clone(),
         )
     }
+
+    fn is_connected(&self) -> bool {
+        self.strong_count() > 0
+    }
 }
 
 /// Convenience struct to support heterogeneous in-memory streams
@@ -251,6 +323,7 @@ pub struct Setup<I> {
     tx: ConnectionTx<I>,
     rx: ConnectionRx<I>,
     connections: HashMap<I, ConnectionTx<I>>,
+    recording: Option<Recording<I>>,
 }
 
 impl<I: TransportIdentity> Setup<I> {
@@ -262,9 +335,16 @@ impl<I: TransportIdentity> Setup<I> {
             tx,
             rx,
             connections: HashMap::default(),
+            recording: None,
         }
     }
 
+    /// Makes the transport record every record stream it receives. The returned recording is
+    /// filled in as the streams are read.
+    pub(super) fn record(&mut self) -> Recording<I> {
+        Arc::clone(self.recording.get_or_insert_with(Arc::default))
+    }
+
     /// Establishes a link between this helper and another one
     ///
     /// ## Panics
@@ -288,7 +368,11 @@ impl<I: TransportIdentity> Setup<I> {
         self,
         handler: Option<HandlerRef<I>>,
     ) -> (ConnectionTx<I>, Arc<InMemoryTransport<I>>) {
-        let transport = Arc::new(InMemoryTransport::new(self.identity, self.connections));
+        let transport = Arc::new(InMemoryTransport::new(
+            self.identity,
+            self.connections,
+            self.recording,
+        ));
         transport.listen(handler, self.rx);
 
         (self.tx, transport)
@@ -372,7 +456,7 @@ mod tests {
                     .send(query_config)
                     .unwrap();
                 Ok(HelperResponse::from(PrepareQuery {
-                    query_id: QueryId,
+                    query_id: QueryId::DEFAULT,
                     config: query_config,
                     roles: RoleAssignment::try_from([Role::H1, Role::H2, Role::H3]).unwrap(),
                 }))
@@ -399,7 +483,7 @@ mod tests {
         let expected = vec![vec![1], vec![2]];
 
         let mut stream = transport
-            .receive(HelperIdentity::TWO, (QueryId, Gate::from(STEP)))
+            .receive(HelperIdentity::TWO, (QueryId::DEFAULT, Gate::from(STEP)))
             .into_bytes_stream();
 
         // make sure it is not ready as it hasn't received the records stream yet.
@@ -409,7 +493,7 @@ mod tests {
         ));
         send_and_ack(
             &tx,
-            Addr::records(HelperIdentity::TWO, QueryId, Gate::from(STEP)),
+            Addr::records(HelperIdentity::TWO, QueryId::DEFAULT, Gate::from(STEP)),
             stream::iter(expected.clone()),
         )
         .await;
@@ -424,13 +508,13 @@ mod tests {
 
         send_and_ack(
             &tx,
-            Addr::records(HelperIdentity::TWO, QueryId, Gate::from(STEP)),
+            Addr::records(HelperIdentity::TWO, QueryId::DEFAULT, Gate::from(STEP)),
             stream::iter(expected.clone()),
         )
         .await;
 
         let stream = Arc::downgrade(&transport)
-            .receive(HelperIdentity::TWO, (QueryId, Gate::from(STEP)))
+            .receive(HelperIdentity::TWO, (QueryId::DEFAULT, Gate::from(STEP)))
             .into_bytes_stream();
 
         assert_eq!(expected, stream.collect::<Vec<_>>().await);
@@ -451,7 +535,7 @@ mod tests {
             let gate = Gate::from(STEP);
 
             let mut recv = to_transport
-                .receive(from, (QueryId, gate.clone()))
+                .receive(from, (QueryId::DEFAULT, gate.clone()))
                 .into_bytes_stream();
             assert!(matches!(
                 poll_immediate(&mut recv).next().await,
@@ -459,7 +543,11 @@ mod tests {
             ));
 
             from_transport
-                .send(to, (RouteId::Records, QueryId, gate.clone()), stream)
+                .send(
+                    to,
+                    (RouteId::Records, QueryId::DEFAULT, gate.clone()),
+                    stream,
+                )
                 .await
                 .unwrap();
             stream_tx.send(vec![1, 2, 3]).await.unwrap();
@@ -505,11 +593,11 @@ mod tests {
         let transport = Arc::downgrade(&owned_transport);
 
         let mut recv_stream = transport
-            .receive(HelperIdentity::TWO, (QueryId, gate.clone()))
+            .receive(HelperIdentity::TWO, (QueryId::DEFAULT, gate.clone()))
             .into_bytes_stream();
         send_and_ack(
             &tx,
-            Addr::records(HelperIdentity::TWO, QueryId, gate.clone()),
+            Addr::records(HelperIdentity::TWO, QueryId::DEFAULT, gate.clone()),
             stream,
         )
         .await;
@@ -518,7 +606,7 @@ mod tests {
         assert_eq!(vec![4, 5, 6], recv_stream.next().await.unwrap());
 
         // the same stream cannot be received again
-        let mut err_recv = transport.receive(HelperIdentity::TWO, (QueryId, gate.clone()));
+        let mut err_recv = transport.receive(HelperIdentity::TWO, (QueryId::DEFAULT, gate.clone()));
         let err = AssertUnwindSafe(err_recv.next()).catch_unwind().await;
         assert_eq!(
             Some(true),
@@ -529,7 +617,7 @@ mod tests {
 
         // even after the input stream is closed
         drop(stream_tx);
-        let mut err_recv = transport.receive(HelperIdentity::TWO, (QueryId, gate.clone()));
+        let mut err_recv = transport.receive(HelperIdentity::TWO, (QueryId::DEFAULT, gate.clone()));
         let err = AssertUnwindSafe(err_recv.next()).catch_unwind().await;
         assert_eq!(
             Some(true),
@@ -551,13 +639,13 @@ mod tests {
         transport1
             .send(
                 HelperIdentity::TWO,
-                (RouteId::Records, QueryId, gate.clone()),
+                (RouteId::Records, QueryId::DEFAULT, gate.clone()),
                 rx,
             )
             .await
             .unwrap();
         let mut recv = transport2
-            .receive(HelperIdentity::ONE, (QueryId, gate))
+            .receive(HelperIdentity::ONE, (QueryId::DEFAULT, gate))
             .into_bytes_stream();
 
         tx.send(0, Fp31::try_from(0_u128).unwrap()).await;
@@ -24,8 +24,11 @@ pub use handler::{
     make_owned_handler, Error as ApiError, HandlerBox, HandlerRef, HelperResponse, RequestHandler,
 };
 #[cfg(feature = "in-memory-infra")]
-pub use in_memory::{InMemoryMpcNetwork, InMemoryShardNetwork, InMemoryTransport};
-pub use receive::{LogErrors, ReceiveRecords};
+pub use in_memory::{
+    InMemoryMpcNetwork, InMemoryShardNetwork, InMemoryTransport, MessageTrace, RecordedChannel,
+    RecordedChunk, RecordingNetwork, ReplayNetwork,
+};
+pub use receive::ReceiveRecords;
 #[cfg(feature = "web-app")]
 pub use stream::WrappedAxumBodyStream;
 pub use stream::{
@@ -213,6 +216,13 @@ pub trait Transport: Clone + Send + Sync + 'static {
         route: R,
     ) -> Self::RecordsStream;
 
+    /// Returns `false` once this transport can no longer send or receive anything, e.g. because
+    /// the network it belongs to has been torn down. Transports that cannot tell always report
+    /// `true`.
+    fn is_connected(&self) -> bool {
+        true
+    }
+
     /// Alias for `Clone::clone`.
     ///
     /// `Transport` is implemented for `Weak<InMemoryTranport>` and `Arc<HttpTransport>`. Clippy won't
@@ -59,7 +59,7 @@ impl<I: TransportIdentity, S: Stream> StreamCollection<I, S> {
                     };
                     waker.wake();
                 }
-                rs @ (StreamState::Ready(_) | StreamState::Completed) => {
+                rs => {
                     let state = format!("{rs:?}");
                     let key = entry.key().clone();
                     drop(streams);
@@ -67,7 +67,7 @@ impl<I: TransportIdentity, S: Stream> StreamCollection<I, S> {
                 }
             },
             Entry::Vacant(entry) => {
-                entry.insert(StreamState::Ready(stream));
+                entry.insert(StreamState::Unclaimed(stream));
             }
         }
     }
@@ -86,8 +86,9 @@ impl<I: TransportIdentity, S: Stream> StreamCollection<I, S> {
                     old_waker.clone_from(waker);
                     None
                 }
-                rs @ StreamState::Ready(_) => {
-                    let StreamState::Ready(stream) = std::mem::replace(rs, StreamState::Completed)
+                rs @ (StreamState::Ready(_) | StreamState::Unclaimed(_)) => {
+                    let (StreamState::Ready(stream) | StreamState::Unclaimed(stream)) =
+                        std::mem::replace(rs, StreamState::Completed)
                     else {
                         unreachable!();
                     };
@@ -106,11 +107,33 @@ impl<I: TransportIdentity, S: Stream> StreamCollection<I, S> {
         }
     }
 
-    /// Clears up this collection, leaving no streams inside it.
+    /// Returns the keys of streams that were received from peers, but never requested by this
+    /// helper. Streams stay in this state when helpers disagree on the protocol steps, so this
+    /// is a useful diagnostic for step mismatch bugs.
+    ///
+    /// ## Panics
+    /// if mutex is poisoned.
+    pub fn unclaimed(&self) -> Vec<StreamKey<I>> {
+        let streams = self.inner.lock().unwrap();
+        streams
+            .iter()
+            .filter(|(_, state)| matches!(state, StreamState::Unclaimed(_)))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// Clears up this collection, leaving no streams inside it. Streams that were received but
+    /// never requested are logged as a warning before being dropped.
     ///
     /// ## Panics
     /// if mutex is poisoned.
     pub fn clear(&self) {
+        for key in self.unclaimed() {
+            tracing::warn!(
+                stream = ?key,
+                "Stream was received but never requested, helpers may disagree on protocol steps"
+            );
+        }
         let mut streams = self.inner.lock().unwrap();
         streams.clear();
     }
@@ -122,6 +145,8 @@ enum StreamState<S> {
     Waiting(Waker),
     /// Stream is ready to be consumed
     Ready(S),
+    /// Stream arrived before anyone requested it and is ready to be consumed.
+    Unclaimed(S),
     /// Stream was successfully received and taken away from [`StreamCollection`].
     /// It may not be requested or received again.
     Completed,
@@ -136,9 +161,53 @@ impl<S> Debug for StreamState<S> {
             StreamState::Ready(_) => {
                 write!(f, "Ready")
             }
+            StreamState::Unclaimed(_) => {
+                write!(f, "Unclaimed")
+            }
             StreamState::Completed => {
                 write!(f, "Completed")
             }
         }
     }
 }
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use futures::{
+        stream::{empty, Empty},
+        task::noop_waker_ref,
+    };
+
+    use super::{StreamCollection, StreamKey};
+    use crate::{
+        helpers::HelperIdentity,
+        protocol::{step::Gate, QueryId},
+    };
+
+    fn key(gate: &str) -> StreamKey<HelperIdentity> {
+        (QueryId::DEFAULT, HelperIdentity::ONE, Gate::from(gate))
+    }
+
+    #[test]
+    fn unclaimed_stream() {
+        let coll = StreamCollection::<HelperIdentity, Empty<()>>::default();
+        coll.add_stream(key("opened"), empty());
+        coll.add_stream(key("never_opened"), empty());
+
+        assert!(coll.add_waker(&key("opened"), noop_waker_ref()).is_some());
+        assert_eq!(vec![key("never_opened")], coll.unclaimed());
+
+        coll.clear();
+        assert!(coll.unclaimed().is_empty());
+    }
+
+    #[test]
+    fn waiting_stream_is_not_unclaimed() {
+        let coll = StreamCollection::<HelperIdentity, Empty<()>>::default();
+        assert!(coll.add_waker(&key("pending"), noop_waker_ref()).is_none());
+        assert!(coll.unclaimed().is_empty());
+
+        coll.add_stream(key("pending"), empty());
+        assert!(coll.unclaimed().is_empty());
+    }
+}
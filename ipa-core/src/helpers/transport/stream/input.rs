@@ -656,11 +656,40 @@ mod test {
         use futures_util::{FutureExt, StreamExt, TryStreamExt};
 
         use crate::{
-            ff::{Fp31, Fp32BitPrime},
+            ff::{Fp31, Fp32BitPrime, Serializable, SerializableExt},
             helpers::{transport::stream::input::Single, RecordsStream},
-            secret_sharing::SharedValue,
+            rand::{thread_rng, Rng},
+            secret_sharing::{
+                replicated::{semi_honest::AdditiveShare, ReplicatedSecretSharing},
+                SharedValue,
+            },
         };
 
+        #[tokio::test]
+        async fn write_rows_round_trip() {
+            let mut rng = thread_rng();
+            let rows = (0..100)
+                .map(|_| AdditiveShare::<Fp32BitPrime>::new(rng.gen(), rng.gen()))
+                .collect::<Vec<_>>();
+
+            let mut buf = Vec::new();
+            let written = AdditiveShare::<Fp32BitPrime>::write_rows(&rows, &mut buf).unwrap();
+            assert_eq!(buf.len(), written);
+            assert_eq!(
+                AdditiveShare::<Fp32BitPrime>::total_serialized_size(rows.len()),
+                Some(written)
+            );
+
+            // read it back in chunks that do not align with record boundaries
+            let chunks = buf
+                .chunks(7)
+                .map(Bytes::copy_from_slice)
+                .collect::<Vec<_>>();
+            let stream = RecordsStream::<AdditiveShare<Fp32BitPrime>, _, Single>::from(chunks);
+            let collected = stream.try_collect::<Vec<_>>().await.unwrap();
+            assert_eq!(rows, collected);
+        }
+
         #[tokio::test]
         async fn fp31() {
             let vec = vec![3; 10];
@@ -43,7 +43,7 @@ extern crate shuttle_crate as shuttle;
 pub(crate) mod sync {
     pub use shuttle::sync::{Arc, Mutex, MutexGuard, Weak};
     pub mod atomic {
-        pub use shuttle::sync::atomic::{AtomicUsize, Ordering};
+        pub use shuttle::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     }
 }
 
@@ -51,7 +51,7 @@ pub(crate) mod sync {
 pub(crate) mod sync {
     pub use std::sync::{Arc, Mutex, MutexGuard, Weak};
     pub mod atomic {
-        pub use std::sync::atomic::{AtomicUsize, Ordering};
+        pub use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     }
 }
 
@@ -70,7 +70,7 @@ pub(crate) mod rand {
 
 #[cfg(all(feature = "shuttle", test))]
 pub(crate) mod task {
-    pub use shuttle::future::{JoinError, JoinHandle};
+    pub use shuttle::future::{yield_now, JoinError, JoinHandle};
 }
 
 #[cfg(all(feature = "multi-threading", feature = "shuttle"))]
@@ -94,7 +94,7 @@ pub(crate) mod shim {
 
 #[cfg(not(all(feature = "shuttle", test)))]
 pub(crate) mod task {
-    pub use tokio::task::{JoinError, JoinHandle};
+    pub use tokio::task::{yield_now, JoinError, JoinHandle};
 }
 
 #[cfg(all(feature = "shuttle", test))]
@@ -116,6 +116,19 @@ pub(crate) mod test_executor {
     {
         shuttle::check_random(move || shuttle::future::block_on(f()), ITER);
     }
+
+    /// Runs `f` once, under a schedule that is fully determined by `seed`. Running it again with
+    /// the same seed reproduces the same interleaving of tasks, including the order in which the
+    /// in-memory transport delivers messages, so a failing schedule can be replayed.
+    pub fn run_seeded<F, Fut>(seed: u64, f: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()>,
+    {
+        let scheduler = shuttle::scheduler::RandomScheduler::new_from_seed(1, seed);
+        shuttle::Runner::new(scheduler, shuttle::Config::default())
+            .run(move || shuttle::future::block_on(f()));
+    }
 }
 
 #[cfg(all(test, unit_test, not(feature = "shuttle")))]
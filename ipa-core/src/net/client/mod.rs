@@ -534,7 +534,7 @@ pub(crate) mod tests {
 
     #[tokio::test]
     async fn create() {
-        let expected_query_id = QueryId;
+        let expected_query_id = QueryId::DEFAULT;
         let expected_query_config = QueryConfig::new(TestMultiply, FieldType::Fp31, 1).unwrap();
 
         let handler = || {
@@ -563,7 +563,7 @@ pub(crate) mod tests {
         let handler = move || {
             make_owned_handler(move |addr, _| async move {
                 let input = PrepareQuery {
-                    query_id: QueryId,
+                    query_id: QueryId::DEFAULT,
                     config,
                     roles: RoleAssignment::new(HelperIdentity::make_three()),
                 };
@@ -577,7 +577,7 @@ pub(crate) mod tests {
         test_query_command(
             |client| {
                 let req = PrepareQuery {
-                    query_id: QueryId,
+                    query_id: QueryId::DEFAULT,
                     config,
                     roles: RoleAssignment::new(HelperIdentity::make_three()),
                 };
@@ -590,7 +590,7 @@ pub(crate) mod tests {
 
     #[tokio::test]
     async fn input() {
-        let expected_query_id = QueryId;
+        let expected_query_id = QueryId::DEFAULT;
         let expected_input = &[8u8; 25];
         let handler = move || {
             make_owned_handler(move |addr, data| async move {
@@ -618,7 +618,7 @@ pub(crate) mod tests {
         let TestServer {
             client, transport, ..
         } = TestServer::builder().build().await;
-        let expected_query_id = QueryId;
+        let expected_query_id = QueryId::DEFAULT;
         let expected_step = Gate::default().narrow("test-step");
         let expected_payload = vec![7u8; MESSAGE_PAYLOAD_SIZE_BYTES];
 
@@ -635,7 +635,10 @@ pub(crate) mod tests {
         MpcHelperClient::resp_ok(resp).await.unwrap();
 
         let mut stream = Arc::clone(&transport)
-            .receive(HelperIdentity::ONE, (QueryId, expected_step.clone()))
+            .receive(
+                HelperIdentity::ONE,
+                (QueryId::DEFAULT, expected_step.clone()),
+            )
             .into_bytes_stream();
 
         assert_eq!(
@@ -650,7 +653,7 @@ pub(crate) mod tests {
             Fp31::try_from(1u128).unwrap(),
             Fp31::try_from(2u128).unwrap(),
         ];
-        let expected_query_id = QueryId;
+        let expected_query_id = QueryId::DEFAULT;
         let handler = move || {
             make_owned_handler(move |addr, _| async move {
                 let results: Box<dyn ProtocolResult> = Box::new(
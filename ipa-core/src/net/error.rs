@@ -23,7 +23,7 @@ pub enum Error {
     BadPathString(#[source] BoxError),
     #[error(transparent)]
     MissingExtension(#[from] axum::extract::rejection::ExtensionRejection),
-    #[error("query id not found: {}", .0.as_ref())]
+    #[error("query id not found: {0}")]
     QueryIdNotFound(QueryId),
     #[error(transparent)]
     HyperPassthrough(#[from] hyper::Error),
@@ -258,7 +258,7 @@ pub mod query {
                     .path_and_query(format!(
                         "{}/{}?{}",
                         BASE_AXUM_PATH,
-                        self.data.query_id.as_ref(),
+                        self.data.query_id,
                         QueryConfigQueryParams(self.data.config),
                     ))
                     .build()?;
@@ -310,8 +310,7 @@ pub mod query {
                     .authority(authority)
                     .path_and_query(format!(
                         "{}/{}/input",
-                        BASE_AXUM_PATH,
-                        self.query_input.query_id.as_ref(),
+                        BASE_AXUM_PATH, self.query_input.query_id,
                     ))
                     .build()?;
                 let body = Body::wrap_stream(self.query_input.input_stream);
@@ -364,7 +363,7 @@ pub mod query {
                     .path_and_query(format!(
                         "{}/{}/step/{}",
                         BASE_AXUM_PATH,
-                        self.query_id.as_ref(),
+                        self.query_id,
                         self.gate.as_ref()
                     ))
                     .build()?;
@@ -427,7 +426,7 @@ pub mod query {
                     .path_and_query(format!(
                         "{}/{}",
                         crate::net::http_serde::query::BASE_AXUM_PATH,
-                        self.query_id.as_ref()
+                        self.query_id
                     ))
                     .build()?;
                 Ok(hyper::Request::get(uri).body(hyper::Body::empty())?)
@@ -497,7 +496,7 @@ pub mod query {
                     .path_and_query(format!(
                         "{}/{}/complete",
                         crate::net::http_serde::query::BASE_AXUM_PATH,
-                        self.query_id.as_ref()
+                        self.query_id
                     ))
                     .build()?;
                 Ok(hyper::Request::get(uri).body(hyper::Body::empty())?)
@@ -69,7 +69,7 @@ mod tests {
             let query_config = addr.into().unwrap();
             assert_eq!(query_config, expected_query_config);
             Ok(HelperResponse::from(PrepareQuery {
-                query_id: QueryId,
+                query_id: QueryId::DEFAULT,
                 config: query_config,
                 roles: RoleAssignment::try_from([Role::H1, Role::H2, Role::H3]).unwrap(),
             }))
@@ -77,7 +77,7 @@ mod tests {
         let resp = assert_success_with(req, handler).await;
         let http_serde::query::create::ResponseBody { query_id } =
             serde_json::from_slice(&resp).unwrap();
-        assert_eq!(QueryId, query_id);
+        assert_eq!(QueryId::DEFAULT, query_id);
     }
 
     #[tokio::test]
@@ -59,7 +59,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn input_test() {
-        let expected_query_id = QueryId;
+        let expected_query_id = QueryId::DEFAULT;
         let expected_input = &[4u8; 4];
         let req = http_serde::query::input::Request::new(QueryInput {
             query_id: expected_query_id,
@@ -107,7 +107,7 @@ mod tests {
     impl Default for OverrideReq {
         fn default() -> Self {
             Self {
-                query_id: QueryId.as_ref().to_string(),
+                query_id: QueryId.to_string(),
                 input_stream: vec![4; 4],
             }
         }
@@ -85,7 +85,7 @@ mod tests {
                 panic!("unexpected call");
             };
             let expected_prepare_query = PrepareQuery {
-                query_id: QueryId,
+                query_id: QueryId::DEFAULT,
                 config: QueryConfig::new(TestMultiply, FieldType::Fp31, 1).unwrap(),
                 roles: RoleAssignment::new(HelperIdentity::make_three()),
             };
@@ -121,7 +121,7 @@ mod tests {
         fn default() -> Self {
             Self {
                 client_id: Some(ClientIdentity(HelperIdentity::TWO)),
-                query_id: QueryId.as_ref().to_string(),
+                query_id: QueryId.to_string(),
                 field_type: format!("{:?}", FieldType::Fp31),
                 size: Some(1),
                 roles: OverrideReqRoles {
@@ -60,7 +60,7 @@ mod tests {
             Fp31::try_from(1u128).unwrap(),
             Fp31::try_from(2u128).unwrap(),
         ))]);
-        let expected_query_id = QueryId;
+        let expected_query_id = QueryId::DEFAULT;
         let raw_results = expected_results.to_vec();
         let req_handler = make_owned_handler(move |addr: Addr<HelperIdentity>, _: BodyStream| {
             let raw_results = raw_results.clone();
@@ -73,7 +73,7 @@ mod tests {
                 Ok(HelperResponse::from(results))
             }
         });
-        let req = http_serde::query::results::Request::new(QueryId);
+        let req = http_serde::query::results::Request::new(QueryId::DEFAULT);
         let req = req
             .try_into_http_request(Scheme::HTTP, Authority::from_static("localhost"))
             .unwrap();
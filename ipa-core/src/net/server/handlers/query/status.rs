@@ -52,7 +52,7 @@ mod tests {
     #[tokio::test]
     async fn status_test() {
         let expected_status = QueryStatus::Running;
-        let expected_query_id = QueryId;
+        let expected_query_id = QueryId::DEFAULT;
 
         let handler = make_owned_handler(
             move |addr: Addr<HelperIdentity>, _data: BodyStream| async move {
@@ -64,7 +64,7 @@ mod tests {
             },
         );
 
-        let req = http_serde::query::status::Request::new(QueryId);
+        let req = http_serde::query::status::Request::new(QueryId::DEFAULT);
         let req = req
             .try_into_http_request(Scheme::HTTP, Authority::from_static("localhost"))
             .unwrap();
@@ -66,7 +66,7 @@ mod tests {
         test_server.server.handle_req(req.into()).await;
 
         let mut stream = Arc::clone(&test_server.transport)
-            .receive(HelperIdentity::TWO, (QueryId, step))
+            .receive(HelperIdentity::TWO, (QueryId::DEFAULT, step))
             .into_bytes_stream();
 
         assert_eq!(
@@ -101,7 +101,7 @@ mod tests {
         fn default() -> Self {
             Self {
                 client_id: Some(ClientIdentity(HelperIdentity::ONE)),
-                query_id: QueryId.as_ref().to_string(),
+                query_id: QueryId.to_string(),
                 gate: Gate::default().narrow("test"),
                 payload: vec![1; DATA_LEN * MESSAGE_PAYLOAD_SIZE_BYTES],
             }
@@ -308,11 +308,16 @@ mod tests {
         );
 
         // Register the stream with the transport (normally called by step data HTTP API handler)
-        Arc::clone(&transport).receive_stream(QueryId, STEP.clone(), HelperIdentity::TWO, body);
+        Arc::clone(&transport).receive_stream(
+            QueryId::DEFAULT,
+            STEP.clone(),
+            HelperIdentity::TWO,
+            body,
+        );
 
         // Request step data reception (normally called by protocol)
         let mut stream = Arc::clone(&transport)
-            .receive(HelperIdentity::TWO, (QueryId, STEP.clone()))
+            .receive(HelperIdentity::TWO, (QueryId::DEFAULT, STEP.clone()))
             .into_bytes_stream();
 
         // make sure it is not ready as it hasn't received any data yet.
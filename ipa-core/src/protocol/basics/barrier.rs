@@ -0,0 +1,95 @@
+use futures::future::try_join4;
+
+use crate::{
+    error::Error,
+    ff::{boolean::Boolean, Field},
+    helpers::Direction,
+    protocol::{context::Context, RecordId},
+};
+
+/// Waits until all three helpers reach this point in the protocol.
+///
+/// Every helper sends a sentinel to both of its peers and waits for one from each of them, so
+/// the returned future resolves only after the other two helpers have called `barrier` with the
+/// same gate and record id. The context must be narrowed to a step that is used for nothing else
+/// and have its total records set.
+///
+/// If a peer never arrives, the barrier waits for it just like any other receive would. To bound
+/// the wait, abort the gateway, which resolves the barrier with [`Error::MpcInfraError`]
+/// wrapping [`Aborted`], or wrap it in a timeout.
+///
+/// ## Errors
+/// If sending or receiving the sentinel fails, including when the query is aborted.
+///
+/// [`Aborted`]: crate::helpers::Error::Aborted
+pub async fn barrier<C: Context>(ctx: C, record_id: RecordId) -> Result<(), Error> {
+    let left = ctx.role().peer(Direction::Left);
+    let right = ctx.role().peer(Direction::Right);
+
+    try_join4(
+        ctx.send_channel::<Boolean>(left)
+            .send(record_id, Boolean::ONE),
+        ctx.send_channel::<Boolean>(right)
+            .send(record_id, Boolean::ONE),
+        ctx.recv_channel::<Boolean>(left).receive(record_id),
+        ctx.recv_channel::<Boolean>(right).receive(record_id),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use std::pin::pin;
+
+    use futures::{
+        future::{join, join3},
+        poll,
+    };
+
+    use super::barrier;
+    use crate::{
+        protocol::{context::Context, RecordId},
+        task::yield_now,
+        test_fixture::TestWorld,
+    };
+
+    #[tokio::test]
+    async fn all_helpers_unblock_together() {
+        let world = TestWorld::default();
+        let [c1, c2, c3] = world
+            .contexts()
+            .map(|ctx| ctx.narrow("barrier").set_total_records(1));
+
+        let mut h1 = pin!(barrier(c1, RecordId::FIRST));
+        let mut h2 = pin!(barrier(c2, RecordId::FIRST));
+        for _ in 0..10 {
+            assert!(poll!(&mut h1).is_pending());
+            assert!(poll!(&mut h2).is_pending());
+            yield_now().await;
+        }
+
+        let (r1, r2, r3) = join3(h1, h2, barrier(c3, RecordId::FIRST)).await;
+        r1.unwrap();
+        r2.unwrap();
+        r3.unwrap();
+    }
+
+    #[tokio::test]
+    async fn missing_participant() {
+        let world = TestWorld::default();
+        let [c1, c2, _] = world
+            .contexts()
+            .map(|ctx| ctx.narrow("barrier").set_total_records(1));
+
+        let mut both = pin!(join(
+            barrier(c1, RecordId::FIRST),
+            barrier(c2, RecordId::FIRST)
+        ));
+        for _ in 0..10 {
+            assert!(poll!(&mut both).is_pending());
+            yield_now().await;
+        }
+    }
+}
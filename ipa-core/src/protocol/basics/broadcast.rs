@@ -0,0 +1,69 @@
+use futures::future::try_join;
+use generic_array::GenericArray;
+
+use crate::{
+    error::Error,
+    ff::Serializable,
+    helpers::MpcMessage,
+    protocol::{context::Context, RecordId},
+};
+
+/// Sends `msg` to both peers of this helper under the same `record_id`. The message is
+/// serialized once and the same bytes are sent to both of them, so peers receive it from their
+/// channel as a regular `M`.
+///
+/// ## Errors
+/// If sending to either of the peers fails.
+pub async fn broadcast<C: Context, M: MpcMessage>(
+    ctx: C,
+    record_id: RecordId,
+    msg: &M,
+) -> Result<(), Error> {
+    let mut bytes = GenericArray::default();
+    msg.serialize(&mut bytes);
+    let [right, left] = ctx.role().peers();
+
+    try_join(
+        ctx.send_channel::<M>(right).send_raw(record_id, &bytes),
+        ctx.send_channel::<M>(left).send_raw(record_id, &bytes),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use super::broadcast;
+    use crate::{
+        ff::{Fp31, U128Conversions},
+        helpers::Role,
+        protocol::{context::Context, RecordId},
+        test_fixture::{Runner, TestWorld},
+    };
+
+    #[tokio::test]
+    async fn received_by_both_peers() {
+        let world = TestWorld::default();
+        let value = Fp31::truncate_from(17_u128);
+
+        let results = world
+            .semi_honest((), |ctx, ()| async move {
+                let ctx = ctx.narrow("broadcast").set_total_records(1);
+                if ctx.role() == Role::H1 {
+                    broadcast(ctx, RecordId::FIRST, &value).await.unwrap();
+                    None
+                } else {
+                    let received = ctx
+                        .recv_channel::<Fp31>(Role::H1)
+                        .receive(RecordId::FIRST)
+                        .await
+                        .unwrap();
+                    Some(received)
+                }
+            })
+            .await;
+
+        assert_eq!([None, Some(value), Some(value)], results);
+    }
+}
@@ -0,0 +1,172 @@
+use std::iter::zip;
+
+use crate::{
+    error::{Error, LengthError},
+    ff::Field,
+    protocol::{
+        basics::SecureMul,
+        context::Context,
+        step::{BitStep, TwoHundredFiftySixBitOpStep},
+        RecordId,
+    },
+    secret_sharing::replicated::semi_honest::AdditiveShare,
+    seq_join::SeqJoin,
+};
+
+/// Computes the inner product of two secret-shared vectors.
+///
+/// All element-wise products are computed in a single round, each under its own step, so
+/// they can share `record_id`. The products are then summed locally.
+///
+/// ## Errors
+/// If `a` and `b` have different lengths or if any of the multiplications fails.
+///
+/// ## Panics
+/// If the vectors have more than 256 elements.
+pub async fn secure_dot_product<C, F>(
+    ctx: C,
+    record_id: RecordId,
+    a: &[AdditiveShare<F>],
+    b: &[AdditiveShare<F>],
+) -> Result<AdditiveShare<F>, Error>
+where
+    C: Context,
+    F: Field,
+    AdditiveShare<F>: SecureMul<C>,
+{
+    if a.len() != b.len() {
+        return Err(LengthError {
+            expected: a.len(),
+            actual: b.len(),
+        }
+        .into());
+    }
+    assert!(
+        a.len() <= TwoHundredFiftySixBitOpStep::max_bit_depth() as usize,
+        "Dot product supports up to 256 elements, got {}",
+        a.len()
+    );
+
+    let products = ctx
+        .parallel_join(zip(a, b).enumerate().map(|(i, (a, b))| {
+            a.multiply(
+                b,
+                ctx.narrow(&TwoHundredFiftySixBitOpStep::from(i)),
+                record_id,
+            )
+        }))
+        .await?;
+
+    Ok(products
+        .into_iter()
+        .fold(AdditiveShare::ZERO, |acc, product| acc + product))
+}
+
+/// Same as [`secure_dot_product`], but takes the vectors as pairs of elements.
+///
+/// All products are computed in a single round. Adding them up is local, so unlike a tree of
+/// multiplications, the sum does not need extra rounds or steps for its levels.
+///
+/// ## Errors
+/// If any of the multiplications fails.
+///
+/// ## Panics
+/// If there are more than 256 pairs.
+pub async fn tree_sum_products<C, F>(
+    ctx: C,
+    record_id: RecordId,
+    pairs: &[(AdditiveShare<F>, AdditiveShare<F>)],
+) -> Result<AdditiveShare<F>, Error>
+where
+    C: Context,
+    F: Field,
+    AdditiveShare<F>: SecureMul<C>,
+{
+    let (a, b): (Vec<_>, Vec<_>) = pairs.iter().cloned().unzip();
+    secure_dot_product(ctx, record_id, &a, &b).await
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use std::iter::zip;
+
+    use super::{secure_dot_product, tree_sum_products};
+    use crate::{
+        error::Error,
+        ff::{Field, Fp31, Fp32BitPrime},
+        protocol::{context::Context, RecordId},
+        rand::{thread_rng, Rng},
+        secret_sharing::replicated::semi_honest::AdditiveShare,
+        test_fixture::{Reconstruct, Runner, TestWorld},
+    };
+
+    #[tokio::test]
+    async fn dot_product() {
+        const LEN: usize = 10;
+        let world = TestWorld::default();
+        let mut rng = thread_rng();
+        let a = (0..LEN)
+            .map(|_| rng.gen::<Fp32BitPrime>())
+            .collect::<Vec<_>>();
+        let b = (0..LEN)
+            .map(|_| rng.gen::<Fp32BitPrime>())
+            .collect::<Vec<_>>();
+        let expected = zip(&a, &b).fold(Fp32BitPrime::ZERO, |acc, (a, b)| acc + *a * *b);
+
+        let result = world
+            .semi_honest((a.into_iter(), b.into_iter()), |ctx, (a, b)| async move {
+                let ctx = ctx.set_total_records(1);
+                secure_dot_product(ctx, RecordId::FIRST, &a, &b)
+                    .await
+                    .unwrap()
+            })
+            .await
+            .reconstruct();
+
+        assert_eq!(expected, result);
+    }
+
+    #[tokio::test]
+    async fn length_mismatch() {
+        let world = TestWorld::default();
+
+        let results = world
+            .semi_honest(
+                (
+                    vec![Fp31::ONE; 2].into_iter(),
+                    vec![Fp31::ONE; 3].into_iter(),
+                ),
+                |ctx, (a, b): (Vec<AdditiveShare<Fp31>>, Vec<AdditiveShare<Fp31>>)| async move {
+                    secure_dot_product(ctx, RecordId::FIRST, &a, &b).await
+                },
+            )
+            .await;
+
+        for r in results {
+            assert!(matches!(r, Err(Error::LengthError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn sum_of_products() {
+        const LEN: usize = 7;
+        let world = TestWorld::default();
+        let mut rng = thread_rng();
+        let pairs = (0..LEN)
+            .map(|_| (rng.gen::<Fp31>(), rng.gen::<Fp31>()))
+            .collect::<Vec<_>>();
+        let expected = pairs.iter().fold(Fp31::ZERO, |acc, &(a, b)| acc + a * b);
+
+        let result = world
+            .semi_honest(pairs.into_iter(), |ctx, pairs| async move {
+                let ctx = ctx.set_total_records(1);
+                tree_sum_products(ctx, RecordId::FIRST, &pairs)
+                    .await
+                    .unwrap()
+            })
+            .await
+            .reconstruct();
+
+        assert_eq!(expected, result);
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(feature = "descriptive-gate")]
+use ipa_macros::Step;
+
 use crate::{
     error::Error,
     ff::boolean::Boolean,
@@ -8,6 +11,15 @@ use crate::{
     },
     secret_sharing::replicated::semi_honest::AdditiveShare,
 };
+#[cfg(feature = "descriptive-gate")]
+use crate::{ff::Field, protocol::basics::SecureMul, secret_sharing::Linear};
+
+#[cfg(feature = "descriptive-gate")]
+#[derive(Step)]
+pub(crate) enum Select3Step {
+    LowBit,
+    HighBit,
+}
 
 /// Wide multiplexer.
 ///
@@ -52,3 +64,97 @@ where
 
     Ok((false_value + &product).into())
 }
+
+/// Three-way multiplexer.
+///
+/// Returns `options[i]`, where `i = sel_bits[0] + 2 * sel_bits[1]`. Both selector bits must be
+/// shares of either 0 or 1. The unused fourth index (both bits set) selects `options[2]`: the
+/// high bit takes precedence over the low one.
+///
+/// Works with any sharing that supports multiplication, including malicious shares, in which
+/// case the multiplications are checked the same way as in any other malicious protocol.
+/// Takes two rounds and two multiplications.
+///
+/// Its steps are not part of the IPA protocol yet, so it is only available with descriptive
+/// gates.
+///
+/// # Errors
+/// If the protocol fails to execute.
+#[cfg(feature = "descriptive-gate")]
+pub async fn select3<C, F, S>(
+    ctx: C,
+    record_id: RecordId,
+    sel_bits: [&S; 2],
+    options: [&S; 3],
+) -> Result<S, Error>
+where
+    C: Context,
+    F: Field,
+    S: Linear<F> + SecureMul<C>,
+{
+    let [low_bit, high_bit] = sel_bits;
+    let [option0, option1, option2] = options;
+    // options[0] + low_bit * (options[1] - options[0]) picks between the first two options,
+    // then the same construction picks between that and options[2] based on the high bit.
+    let low = low_bit
+        .multiply(
+            &(option1.clone() - option0),
+            ctx.narrow(&Select3Step::LowBit),
+            record_id,
+        )
+        .await?
+        + option0;
+    let high = high_bit
+        .multiply(
+            &(option2.clone() - &low),
+            ctx.narrow(&Select3Step::HighBit),
+            record_id,
+        )
+        .await?;
+
+    Ok(low + high)
+}
+
+#[cfg(all(test, unit_test, feature = "descriptive-gate"))]
+mod tests {
+    use super::select3;
+    use crate::{
+        ff::{Field, Fp31},
+        protocol::{context::Context, RecordId},
+        rand::{thread_rng, Rng},
+        test_fixture::{Reconstruct, Runner, TestWorld},
+    };
+
+    #[tokio::test]
+    async fn select3_all_selectors() {
+        let world = TestWorld::default();
+        let mut rng = thread_rng();
+        let options = [rng.gen::<Fp31>(), rng.gen(), rng.gen()];
+
+        for (index, expected) in [0, 1, 2, 2].into_iter().enumerate() {
+            let bit = |b: usize| {
+                if (index >> b) & 1 == 1 {
+                    Fp31::ONE
+                } else {
+                    Fp31::ZERO
+                }
+            };
+            let input = vec![bit(0), bit(1), options[0], options[1], options[2]];
+            let result = world
+                .upgraded_malicious(input.into_iter(), |ctx, s| async move {
+                    select3(
+                        ctx.set_total_records(1),
+                        RecordId::FIRST,
+                        [&s[0], &s[1]],
+                        [&s[2], &s[3], &s[4]],
+                    )
+                    .await
+                    .unwrap()
+                })
+                .await
+                .reconstruct();
+
+            assert_eq!(options[expected], result, "selector {index}");
+        }
+    }
+}
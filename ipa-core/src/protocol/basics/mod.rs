@@ -1,5 +1,8 @@
+mod barrier;
+mod broadcast;
 #[cfg(feature = "descriptive-gate")]
 pub mod check_zero;
+mod dot_product;
 mod if_else;
 pub(crate) mod mul;
 mod reshare;
@@ -9,11 +12,16 @@ pub mod share_validation;
 
 use std::ops::Not;
 
+pub use barrier::barrier;
+pub use broadcast::broadcast;
 #[cfg(feature = "descriptive-gate")]
 pub use check_zero::check_zero;
+pub use dot_product::{secure_dot_product, tree_sum_products};
 pub use if_else::select;
-pub use mul::{BooleanArrayMul, SecureMul};
-pub use reshare::Reshare;
+#[cfg(feature = "descriptive-gate")]
+pub use if_else::select3;
+pub use mul::{multiply_vectors, BooleanArrayMul, SecureMul};
+pub use reshare::{reshare_rows, Reshare};
 pub use reveal::{partial_reveal, reveal, Reveal};
 pub use share_known_value::ShareKnownValue;
 
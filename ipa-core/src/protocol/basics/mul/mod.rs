@@ -1,5 +1,6 @@
 use std::{
     future::Future,
+    iter::zip,
     ops::{Add, Sub},
 };
 
@@ -10,7 +11,7 @@ use crate::{
     ff::{
         boolean::Boolean,
         boolean_array::{BA16, BA20, BA256, BA3, BA32, BA5, BA64, BA8},
-        Expand,
+        Expand, Field,
     },
     protocol::{
         basics::PrimeField,
@@ -22,6 +23,7 @@ use crate::{
         RecordId,
     },
     secret_sharing::replicated::semi_honest::AdditiveShare as Replicated,
+    seq_join::SeqJoin,
     sharding,
 };
 
@@ -41,6 +43,41 @@ pub trait SecureMul<C: Context>: Send + Sync + Sized {
         C: 'fut;
 }
 
+/// Multiplies `a` and `b` element-wise.
+///
+/// The `i`-th pair is multiplied under record `record_id_base + i`, so `ctx` must be set up to
+/// accept that many records. All multiplications are driven concurrently.
+///
+/// ## Errors
+/// If any of the multiplications fails or if the record ids would overflow.
+///
+/// ## Panics
+/// If `a` and `b` have different lengths.
+pub async fn multiply_vectors<C, F>(
+    ctx: C,
+    record_id_base: RecordId,
+    a: &[Replicated<F>],
+    b: &[Replicated<F>],
+) -> Result<Vec<Replicated<F>>, Error>
+where
+    C: Context,
+    F: Field,
+    Replicated<F>: SecureMul<C>,
+{
+    assert_eq!(
+        a.len(),
+        b.len(),
+        "Cannot multiply vectors of different lengths"
+    );
+    let record_ids = RecordId::range(record_id_base, a.len())?;
+    ctx.parallel_join(
+        zip(a, b)
+            .zip(record_ids)
+            .map(|((a, b), record_id)| a.multiply(b, ctx.clone(), record_id)),
+    )
+    .await
+}
+
 // The BooleanArrayMul trait is implemented for types like `Replicated<BA32>`. It hides the `N`
 // const parameter so that implementations parameterized with a Boolean array type parameter (e.g.
 // breakdown key type BK is BA8) can invoke vectorized multiply. Without this trait, those
@@ -170,3 +207,55 @@ boolean_array_mul!(20, BA20);
 boolean_array_mul!(32, BA32);
 boolean_array_mul!(64, BA64);
 boolean_array_mul!(256, BA256);
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use std::iter::zip;
+
+    use super::multiply_vectors;
+    use crate::{
+        error::Error,
+        ff::{Field, Fp31},
+        protocol::{context::Context, RecordId},
+        rand::{thread_rng, Rng},
+        test_fixture::{Reconstruct, Runner, TestWorld},
+    };
+
+    #[tokio::test]
+    async fn element_wise() {
+        const LEN: usize = 20;
+        let world = TestWorld::default();
+        let mut rng = thread_rng();
+        let a = (0..LEN).map(|_| rng.gen::<Fp31>()).collect::<Vec<_>>();
+        let b = (0..LEN).map(|_| rng.gen::<Fp31>()).collect::<Vec<_>>();
+        let expected = zip(&a, &b).map(|(a, b)| *a * *b).collect::<Vec<_>>();
+
+        let result = world
+            .semi_honest((a.into_iter(), b.into_iter()), |ctx, (a, b)| async move {
+                multiply_vectors(ctx.set_total_records(LEN), RecordId::FIRST, &a, &b)
+                    .await
+                    .unwrap()
+            })
+            .await
+            .reconstruct();
+
+        assert_eq!(expected, result);
+    }
+
+    #[tokio::test]
+    async fn element_wise_record_id_overflow() {
+        let world = TestWorld::default();
+        let a = vec![Fp31::ONE; 3];
+
+        world
+            .semi_honest(
+                (a.clone().into_iter(), a.into_iter()),
+                |ctx, (a, b)| async move {
+                    let base = RecordId::from(u32::MAX - 1);
+                    let result = multiply_vectors(ctx.set_total_records(3), base, &a, &b).await;
+                    assert!(matches!(result, Err(Error::RecordIdOverflow { .. })));
+                },
+            )
+            .await;
+    }
+}
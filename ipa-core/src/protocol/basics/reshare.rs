@@ -9,10 +9,12 @@ use crate::{
     secret_sharing::replicated::{
         semi_honest::AdditiveShare as Replicated, ReplicatedSecretSharing,
     },
+    seq_join::SeqJoin,
 };
 #[cfg(feature = "descriptive-gate")]
 use crate::{
     protocol::basics::mul::malicious::Step::{RandomnessForValidation, ReshareRx},
+    protocol::basics::share_validation::validate_replicated_shares,
     protocol::context::SpecialAccessToUpgradedContext,
     protocol::context::UpgradedMaliciousContext,
     secret_sharing::replicated::malicious::ThisCodeIsAuthorizedToDowngradeFromMalicious,
@@ -101,6 +103,53 @@ impl<C: Context, F: Field> Reshare<C> for Replicated<F> {
     }
 }
 
+/// Reshares a batch of rows towards `to_helper`. Row `i` uses record id `record_id_base + i`,
+/// so the batch consumes the contiguous range `record_id_base..record_id_base + rows.len()`.
+/// `ctx` must be set up to accept records up to the end of that range. Several batches can run
+/// concurrently under the same `ctx`, as long as their ranges do not overlap. Overlapping ranges
+/// reuse PRSS indices and record ids, which panics in debug builds.
+///
+/// When `flush` is set, the helpers that send reshared values flush their channel before
+/// returning. This guarantees that every message of this batch has been handed over to the
+/// transport by the time this function returns, even if the channel stays open because `ctx`
+/// expects more records than `rows` has. It does not guarantee that the peer has read them, but
+/// the next phase can rely on them being delivered without sending anything else on this channel.
+///
+/// ## Errors
+/// If the record id range overflows, in which case nothing is reshared, or if any of the
+/// reshares fails.
+pub async fn reshare_rows<C, F>(
+    ctx: C,
+    record_id_base: RecordId,
+    rows: &[Replicated<F>],
+    to_helper: Role,
+    flush: bool,
+) -> Result<Vec<Replicated<F>>, Error>
+where
+    C: Context,
+    F: Field,
+{
+    let record_ids = RecordId::range(record_id_base, rows.len())?;
+    let output = ctx
+        .try_join(
+            rows.iter()
+                .zip(record_ids)
+                .map(|(row, record_id)| row.reshare(ctx.clone(), record_id, to_helper)),
+        )
+        .await?;
+
+    if flush && ctx.role() != to_helper {
+        let peer = if ctx.role() == to_helper.peer(Direction::Left) {
+            to_helper.peer(Direction::Right)
+        } else {
+            to_helper.peer(Direction::Left)
+        };
+        ctx.send_channel::<F>(peer).flush().await;
+    }
+
+    Ok(output)
+}
+
 #[cfg(feature = "descriptive-gate")]
 #[async_trait]
 /// For malicious reshare, we run semi honest reshare protocol twice, once for x and another for rx and return the results
@@ -133,13 +182,95 @@ impl<'a, F: ExtendableField> Reshare<UpgradedMaliciousContext<'a, F>> for Malici
     }
 }
 
+#[cfg(feature = "descriptive-gate")]
+#[derive(ipa_macros::Step)]
+pub(crate) enum ReshareValidationStep {
+    Reshare,
+    ValidateX,
+    ValidateRx,
+}
+
+/// Reshares a batch of malicious values towards `to_helper` and checks that the resulting shares
+/// are consistent across all three helpers before handing them over to the caller. MACs are still
+/// accumulated as part of the reshare, so this check does not replace the final validation, but
+/// it allows to detect a helper tampering with reshared values early.
+///
+/// ## Errors
+/// If reshare fails or [`Error::ReshareValidationFailed`] if helpers don't hold consistent shares
+/// of the reshared values.
+#[cfg(feature = "descriptive-gate")]
+pub async fn reshare_validated<F: ExtendableField>(
+    ctx: UpgradedMaliciousContext<'_, F>,
+    input: &[MaliciousReplicated<F>],
+    to_helper: Role,
+) -> Result<Vec<MaliciousReplicated<F>>, Error> {
+    let reshare_ctx = ctx
+        .narrow(&ReshareValidationStep::Reshare)
+        .set_total_records(input.len());
+    let output = reshare_ctx
+        .try_join(
+            input
+                .iter()
+                .enumerate()
+                .map(|(i, share)| share.reshare(reshare_ctx.clone(), RecordId::from(i), to_helper)),
+        )
+        .await?;
+
+    validate_reshared(ctx, &output).await?;
+
+    Ok(output)
+}
+
+/// Checks that both `x` and `rx` parts of the given malicious shares are consistent across helpers.
+#[cfg(feature = "descriptive-gate")]
+async fn validate_reshared<F: ExtendableField>(
+    ctx: UpgradedMaliciousContext<'_, F>,
+    shares: &[MaliciousReplicated<F>],
+) -> Result<(), Error> {
+    use futures::future::try_join;
+
+    let (x_left, x_right): (Vec<F>, Vec<F>) = shares
+        .iter()
+        .map(|s| s.x().access_without_downgrade().as_tuple())
+        .unzip();
+    let (rx_left, rx_right): (Vec<F::ExtendedField>, Vec<F::ExtendedField>) =
+        shares.iter().map(|s| s.rx().as_tuple()).unzip();
+
+    match try_join(
+        validate_replicated_shares(
+            ctx.narrow(&ReshareValidationStep::ValidateX),
+            &x_left,
+            &x_right,
+        ),
+        validate_replicated_shares(
+            ctx.narrow(&ReshareValidationStep::ValidateRx),
+            &rx_left,
+            &rx_right,
+        ),
+    )
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(Error::InconsistentShares) => Err(Error::ReshareValidationFailed),
+        Err(e) => Err(e),
+    }
+}
+
 #[cfg(all(test, unit_test))]
 mod tests {
     mod semi_honest {
+        use futures::future::try_join;
+
         use crate::{
-            ff::Fp32BitPrime,
-            helpers::Role,
-            protocol::{basics::Reshare, context::Context, prss::SharedRandomness, RecordId},
+            error::Error,
+            ff::{Field, Fp32BitPrime},
+            helpers::{Role, TotalRecords},
+            protocol::{
+                basics::{reshare::reshare_rows, Reshare},
+                context::Context,
+                prss::SharedRandomness,
+                RecordId,
+            },
             rand::{thread_rng, Rng},
             test_fixture::{Reconstruct, Runner, TestWorld},
         };
@@ -197,6 +328,96 @@ mod tests {
                 assert_eq!(secret, new_shares.reconstruct());
             }
         }
+
+        /// The number of records is indeterminate, so the channel is never closed and the flush
+        /// happens on a channel that is still open.
+        #[tokio::test]
+        async fn rows_flushed() {
+            let world = TestWorld::default();
+
+            for &role in Role::all() {
+                let mut rng = thread_rng();
+                let rows = (0..10)
+                    .map(|_| rng.gen::<Fp32BitPrime>())
+                    .collect::<Vec<_>>();
+                let new_shares = world
+                    .semi_honest(rows.clone().into_iter(), |ctx, rows| async move {
+                        let ctx = ctx.set_total_records(TotalRecords::Indeterminate);
+                        reshare_rows(ctx, RecordId::FIRST, &rows, role, true)
+                            .await
+                            .unwrap()
+                    })
+                    .await;
+
+                assert_eq!(rows, new_shares.reconstruct());
+            }
+        }
+
+        /// Two batches share the context, each one with its own range of record ids.
+        #[tokio::test]
+        async fn concurrent_batches() {
+            let world = TestWorld::default();
+            let mut rng = thread_rng();
+            let rows = (0..10)
+                .map(|_| rng.gen::<Fp32BitPrime>())
+                .collect::<Vec<_>>();
+
+            let new_shares = world
+                .semi_honest(rows.clone().into_iter(), |ctx, rows| async move {
+                    let ctx = ctx.set_total_records(rows.len());
+                    let (first, second) = rows.split_at(5);
+                    let (mut first, second) = try_join(
+                        reshare_rows(ctx.clone(), RecordId::FIRST, first, Role::H1, false),
+                        reshare_rows(ctx, RecordId::from(5_u32), second, Role::H1, false),
+                    )
+                    .await
+                    .unwrap();
+                    first.extend(second);
+                    first
+                })
+                .await;
+
+            assert_eq!(rows, new_shares.reconstruct());
+        }
+
+        #[tokio::test]
+        #[cfg(debug_assertions)]
+        #[should_panic(expected = "twice")]
+        async fn overlapping_batches() {
+            let world = TestWorld::default();
+            let rows = vec![Fp32BitPrime::ONE; 6];
+
+            world
+                .semi_honest(rows.into_iter(), |ctx, rows| async move {
+                    let ctx = ctx.set_total_records(rows.len());
+                    let (first, second) = rows.split_at(3);
+                    try_join(
+                        reshare_rows(ctx.clone(), RecordId::FIRST, first, Role::H1, false),
+                        reshare_rows(ctx, RecordId::from(2_u32), second, Role::H1, false),
+                    )
+                    .await
+                    .unwrap();
+                })
+                .await;
+        }
+
+        /// The range is checked before anything is sent, so there is nothing to flush either.
+        #[tokio::test]
+        async fn batch_record_id_overflow() {
+            let world = TestWorld::default();
+            let rows = vec![Fp32BitPrime::ONE; 3];
+
+            for flush in [false, true] {
+                world
+                    .semi_honest(rows.clone().into_iter(), |ctx, rows| async move {
+                        let ctx = ctx.set_total_records(TotalRecords::Indeterminate);
+                        let base = RecordId::from(u32::MAX - 1);
+                        let result = reshare_rows(ctx, base, &rows, Role::H1, flush).await;
+                        assert!(matches!(result, Err(Error::RecordIdOverflow { .. })));
+                    })
+                    .await;
+            }
+        }
     }
 
     mod malicious {
@@ -210,6 +431,7 @@ mod tests {
             protocol::{
                 basics::{
                     mul::malicious::Step::{RandomnessForValidation, ReshareRx},
+                    reshare::ReshareValidationStep,
                     Reshare,
                 },
                 context::{
@@ -228,6 +450,7 @@ mod tests {
                 },
                 SharedValue,
             },
+            seq_join::SeqJoin,
             test_fixture::{Reconstruct, Runner, TestWorld},
         };
 
@@ -353,6 +576,174 @@ mod tests {
             malicious_validation_fail_helper::<Gf2>(&PERTURBATIONS).await;
         }
 
+        #[tokio::test]
+        async fn reshare_validated() {
+            let world = TestWorld::default();
+            let mut rng = thread_rng();
+            let input = (0..10)
+                .map(|_| rng.gen::<Fp32BitPrime>())
+                .collect::<Vec<_>>();
+
+            for &role in Role::all() {
+                let output = world
+                    .malicious(input.clone().into_iter(), |ctx, a| async move {
+                        let v = ctx.validator();
+                        let m_a = v.context().upgrade(a).await.unwrap();
+                        let m_reshared = super::super::reshare_validated(v.context(), &m_a, role)
+                            .await
+                            .unwrap();
+                        v.validate(m_reshared).await.unwrap()
+                    })
+                    .await;
+
+                assert_eq!(input, output.reconstruct());
+            }
+        }
+
+        #[tokio::test]
+        async fn reshare_validated_detects_inconsistent_shares() {
+            use crate::secret_sharing::replicated::malicious::ThisCodeIsAuthorizedToDowngradeFromMalicious;
+
+            let world = TestWorld::default();
+            let mut rng = thread_rng();
+            let input = (0..10)
+                .map(|_| rng.gen::<Fp32BitPrime>())
+                .collect::<Vec<_>>();
+
+            let results = world
+                .malicious(input.into_iter(), |ctx, a| async move {
+                    let v = ctx.validator();
+                    let m_ctx = v.context();
+                    let m_a = m_ctx.upgrade(a).await.unwrap();
+                    let mut m_reshared =
+                        super::super::reshare_validated(m_ctx.narrow("reshare"), &m_a, Role::H1)
+                            .await
+                            .unwrap();
+
+                    // H2 tampers with the left share of the first reshared value, which only
+                    // H2 itself can detect by comparing it with the right share held by H1.
+                    if m_ctx.role() == Role::H2 {
+                        let x = m_reshared[0].x().access_without_downgrade().clone();
+                        let rx = m_reshared[0].rx().clone();
+                        m_reshared[0] = MaliciousReplicated::new(
+                            Replicated::new(x.left() + Fp32BitPrime::ONE, x.right()),
+                            rx,
+                        );
+                    }
+
+                    super::super::validate_reshared(m_ctx.narrow("validate"), &m_reshared).await
+                })
+                .await;
+
+            assert!(results[0].is_ok());
+            assert!(matches!(results[1], Err(Error::ReshareValidationFailed)));
+            assert!(results[2].is_ok());
+        }
+
+        /// Reshare as seen by the helper on the right of `to_helper`, except that it adds `error`
+        /// to the value it sends to the helper on the left. Unlike [`reshare_with_additive_attack`],
+        /// it keeps the correct value for itself, so the new shares are not consistent.
+        async fn reshare_sending_corrupted_value<C: Context, F: Field>(
+            ctx: C,
+            input: &Replicated<F>,
+            record_id: RecordId,
+            to_helper: Role,
+            error: F,
+        ) -> Result<Replicated<F>, Error> {
+            assert_eq!(to_helper.peer(Direction::Right), ctx.role());
+            let left = to_helper.peer(Direction::Left);
+            let (r0, _): (F, F) = ctx.prss().generate_fields(record_id);
+
+            let part2 = input.left() - r0;
+            ctx.send_channel::<F>(left)
+                .send(record_id, part2 + error)
+                .await?;
+            let part1 = ctx.recv_channel::<F>(left).receive(record_id).await?;
+
+            Ok(Replicated::new(r0, part1 + part2))
+        }
+
+        /// Same steps as [`reshare_validated`], but the `x` part of every share is corrupted on
+        /// its way to the helper on the left of `to_helper`.
+        ///
+        /// [`reshare_validated`]: super::super::reshare_validated
+        async fn reshare_validated_sending_corrupted_shares<F: ExtendableField>(
+            ctx: UpgradedMaliciousContext<'_, F>,
+            input: &[MaliciousReplicated<F>],
+            to_helper: Role,
+        ) -> Result<Vec<MaliciousReplicated<F>>, Error> {
+            use crate::{
+                protocol::context::SpecialAccessToUpgradedContext,
+                secret_sharing::replicated::malicious::ThisCodeIsAuthorizedToDowngradeFromMalicious,
+            };
+
+            let reshare_ctx = ctx
+                .narrow(&ReshareValidationStep::Reshare)
+                .set_total_records(input.len());
+            let output = reshare_ctx
+                .try_join(input.iter().enumerate().map(|(i, share)| {
+                    let ctx = reshare_ctx.clone();
+                    async move {
+                        let record_id = RecordId::from(i);
+                        let (rx, x) = try_join(
+                            share.rx().reshare(
+                                ctx.narrow(&ReshareRx).base_context(),
+                                record_id,
+                                to_helper,
+                            ),
+                            reshare_sending_corrupted_value(
+                                ctx.base_context(),
+                                share.x().access_without_downgrade(),
+                                record_id,
+                                to_helper,
+                                F::ONE,
+                            ),
+                        )
+                        .await?;
+                        let share = MaliciousReplicated::new(x, rx);
+                        ctx.narrow(&RandomnessForValidation)
+                            .accumulate_macs(record_id, &share);
+                        Ok::<_, Error>(share)
+                    }
+                }))
+                .await?;
+
+            super::super::validate_reshared(ctx, &output).await?;
+
+            Ok(output)
+        }
+
+        #[tokio::test]
+        async fn reshare_validated_detects_corrupted_share() {
+            let world = TestWorld::default();
+            let mut rng = thread_rng();
+            let input = (0..10)
+                .map(|_| rng.gen::<Fp32BitPrime>())
+                .collect::<Vec<_>>();
+            let to_helper = Role::H1;
+            let malicious_actor = to_helper.peer(Direction::Right);
+
+            let results = world
+                .malicious(input.into_iter(), |ctx, a| async move {
+                    let v = ctx.validator();
+                    let m_ctx = v.context();
+                    let m_a = m_ctx.upgrade(a).await.unwrap();
+                    let reshared = if m_ctx.role() == malicious_actor {
+                        reshare_validated_sending_corrupted_shares(m_ctx, &m_a, to_helper).await
+                    } else {
+                        super::super::reshare_validated(m_ctx, &m_a, to_helper).await
+                    };
+
+                    reshared.map(|_| ())
+                })
+                .await;
+
+            // H2 sends corrupted values to H3, which finds that its left shares do not match the
+            // right shares H2 kept.
+            assert!(results[0].is_ok());
+            assert!(matches!(results[2], Err(Error::ReshareValidationFailed)));
+        }
+
         async fn malicious_validation_fail_helper<F>(perturbations: &[(F, F::ExtendedField)])
         where
             F: ExtendableField,
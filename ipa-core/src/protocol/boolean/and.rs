@@ -61,3 +61,117 @@ where
         .await?,
     )
 }
+
+/// Parent context narrowed once for every bit that [`bool_and_8_bit`] would narrow it to.
+///
+/// Protocols that AND many records under the same parent context can build this once and call
+/// [`BoolAndContexts::bool_and_8_bit`] for each record, instead of constructing and narrowing
+/// the bit steps on every call. Results are identical to the free function.
+pub struct BoolAndContexts<C> {
+    bits: Vec<C>,
+}
+
+impl<C: Context> BoolAndContexts<C> {
+    /// Narrows `ctx` for inputs of up to `bits` bits. Only the steps that are actually needed are
+    /// narrowed, so this can be used with compact gates.
+    ///
+    /// ## Panics
+    /// If `bits` is zero or greater than 8.
+    #[must_use]
+    pub fn new(ctx: &C, bits: usize) -> Self {
+        assert!(
+            (1..=MAX_BITS).contains(&bits),
+            "Between 1 and {MAX_BITS} bits are supported, but {bits} was requested"
+        );
+        Self {
+            bits: (0..bits)
+                .map(|i| ctx.narrow(&BoolAndStep::Bit(i)))
+                .collect(),
+        }
+    }
+
+    /// Same as [`bool_and_8_bit`], but uses the cached contexts.
+    ///
+    /// ## Errors
+    /// Propagates errors from the multiplication protocol.
+    /// ## Panics
+    /// Panics if the bit-decomposed arguments do not have the same length, or if they are longer
+    /// than the number of bits these contexts were created for.
+    pub async fn bool_and_8_bit<'a, BI, const N: usize>(
+        &self,
+        record_id: RecordId,
+        a: &BitDecomposed<AdditiveShare<Boolean, N>>,
+        b: BI,
+    ) -> Result<BitDecomposed<AdditiveShare<Boolean, N>>, Error>
+    where
+        BI: IntoIterator,
+        <BI as IntoIterator>::IntoIter:
+            ExactSizeIterator<Item = &'a AdditiveShare<Boolean, N>> + Send,
+        Boolean: FieldSimd<N>,
+        AdditiveShare<Boolean, N>: SecureMul<C>,
+    {
+        let b = b.into_iter();
+        assert_eq!(a.len(), b.len());
+        assert!(
+            a.len() <= self.bits.len(),
+            "Up to {max} values are supported, but was given a value of {len} bits",
+            max = self.bits.len(),
+            len = a.len()
+        );
+
+        BitDecomposed::try_from(
+            self.bits[0]
+                .parallel_join(
+                    zip(zip(a.iter(), b), &self.bits)
+                        .map(|((a, b), ctx)| a.multiply(b, ctx.clone(), record_id)),
+                )
+                .await?,
+        )
+    }
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use std::iter::repeat;
+
+    use super::{bool_and_8_bit, BoolAndContexts, BoolAndStep};
+    use crate::{
+        ff::boolean::Boolean,
+        protocol::{context::Context, RecordId},
+        rand::{thread_rng, Rng},
+        secret_sharing::BitDecomposed,
+        test_fixture::{Reconstruct, Runner, TestWorld},
+    };
+
+    #[tokio::test]
+    async fn cached_matches_fresh() {
+        let world = TestWorld::default();
+        let mut rng = thread_rng();
+        let a = BitDecomposed::new((0..8).map(|_| rng.gen::<Boolean>()));
+        let b = rng.gen::<Boolean>();
+
+        let (fresh, cached) = world
+            .semi_honest((a.clone(), b), |ctx, (a, b)| async move {
+                let ctx = ctx.set_total_records(2);
+                let contexts = BoolAndContexts::new(&ctx, 8);
+                for (i, cached) in contexts.bits.iter().enumerate() {
+                    assert_eq!(ctx.narrow(&BoolAndStep::Bit(i)).gate(), cached.gate());
+                }
+
+                let fresh = bool_and_8_bit(ctx, RecordId::FIRST, &a, repeat(&b).take(8))
+                    .await
+                    .unwrap();
+                let cached = contexts
+                    .bool_and_8_bit(RecordId::from(1_u32), &a, repeat(&b).take(8))
+                    .await
+                    .unwrap();
+                (fresh, cached)
+            })
+            .await
+            .reconstruct();
+
+        let expected = BitDecomposed::new(a.iter().map(|&a| a * b));
+        assert_eq!(expected, fresh);
+        assert_eq!(fresh, cached);
+    }
+}
@@ -68,6 +68,40 @@ pub trait Context: Clone + Send + Sync + SeqJoin {
     where
         Gate: StepNarrow<S>;
 
+    /// Make a sub-context for the `branch`-th of several sub-protocols that run concurrently.
+    ///
+    /// This is [`narrow`] with [`ForkStep::Branch`], so each branch gets its own gate: forks of
+    /// the same context never share channels or PRSS, and every branch may use the full range
+    /// of record ids. Forking twice with the same `branch` yields the same gate, just like
+    /// narrowing twice with the same step. Prefer `narrow` with a named step when the set of
+    /// sub-protocols is fixed; `fork` is for when branches are only counted at runtime.
+    ///
+    /// Forked steps are not part of the IPA protocol, so they are not in `steps.txt` and this
+    /// is only available with descriptive gates.
+    ///
+    /// ## Panics
+    /// If `branch` is not less than [`ForkStep::MAX_BRANCHES`].
+    ///
+    /// [`narrow`]: Self::narrow
+    /// [`ForkStep::Branch`]: crate::protocol::step::ForkStep::Branch
+    /// [`ForkStep::MAX_BRANCHES`]: crate::protocol::step::ForkStep::MAX_BRANCHES
+    #[cfg(feature = "descriptive-gate")]
+    #[must_use]
+    fn fork(&self, branch: usize) -> Self
+    where
+        Gate: StepNarrow<crate::protocol::step::ForkStep>,
+    {
+        use crate::protocol::step::ForkStep;
+
+        assert!(
+            branch < ForkStep::MAX_BRANCHES,
+            "Cannot fork {:?} into branch {branch}, there are only {} branches",
+            self.gate(),
+            ForkStep::MAX_BRANCHES,
+        );
+        self.narrow(&ForkStep::from(branch))
+    }
+
     /// Sets the context's total number of records field. Communication channels are
     /// closed based on sending the expected total number of records.
     #[must_use]
@@ -577,7 +611,7 @@ mod tests {
         },
         helpers::{Direction, Role},
         protocol::{
-            basics::ShareKnownValue,
+            basics::{SecureMul, ShareKnownValue},
             context::{
                 reshard, validator::Step::MaliciousProtocol, Context, ShardedContext,
                 UpgradableContext, UpgradedContext, Validator,
@@ -820,6 +854,44 @@ mod tests {
             .await;
     }
 
+    /// Two multiplications run concurrently on forked contexts with the same record id. If the
+    /// forks aliased, the helpers would mix up messages and produce wrong products.
+    #[cfg(feature = "descriptive-gate")]
+    #[tokio::test]
+    async fn fork() {
+        let world = TestWorld::default();
+        let mut rng = rand::thread_rng();
+        let input = (0..4).map(|_| rng.gen::<Fp31>()).collect::<Vec<_>>();
+
+        let result = world
+            .semi_honest(
+                input.clone().into_iter(),
+                |ctx, s: Vec<Replicated<Fp31>>| async move {
+                    let ctx = ctx.set_total_records(1);
+                    assert_ne!(ctx.fork(0).gate(), ctx.fork(1).gate());
+                    let (ab, cd) = try_join!(
+                        s[0].multiply(&s[1], ctx.fork(0), RecordId::FIRST),
+                        s[2].multiply(&s[3], ctx.fork(1), RecordId::FIRST),
+                    )
+                    .unwrap();
+                    vec![ab, cd]
+                },
+            )
+            .await
+            .reconstruct();
+
+        assert_eq!(vec![input[0] * input[1], input[2] * input[3]], result);
+    }
+
+    #[cfg(feature = "descriptive-gate")]
+    #[tokio::test]
+    #[should_panic(expected = "there are only 64 branches")]
+    async fn fork_too_many_branches() {
+        let world = TestWorld::default();
+        let [ctx, ..] = world.contexts();
+        let _ = ctx.fork(crate::protocol::step::ForkStep::MAX_BRANCHES);
+    }
+
     #[test]
     fn receive_from_all_shards() {
         type Field = BA3;
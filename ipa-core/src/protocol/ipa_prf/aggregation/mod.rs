@@ -9,7 +9,7 @@ use ipa_macros::Step;
 
 use crate::{
     error::{Error, LengthError, UnwrapInfallible},
-    ff::{boolean::Boolean, CustomArray, U128Conversions},
+    ff::{boolean::Boolean, CustomArray, PrimeField, U128Conversions},
     helpers::{
         stream::{process_stream_by_chunks, Chunk, ChunkBuffer, FixedLength, TryFlattenItersExt},
         TotalRecords,
@@ -331,6 +331,25 @@ where
     Ok(Vec::transposed_from(&result)?)
 }
 
+/// Merges two histograms produced independently (for example, by different shards), summing the
+/// values for each breakdown. Histograms are indexed by breakdown key. If one histogram has more
+/// breakdowns than the other, the extra entries are carried through unchanged.
+///
+/// This is a local operation, addition of additive shares does not require communication.
+#[must_use]
+pub fn merge_histograms<F: PrimeField>(
+    a: &[Replicated<F>],
+    b: &[Replicated<F>],
+) -> Vec<Replicated<F>> {
+    let (longer, shorter) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut merged = longer.to_vec();
+    for (m, v) in merged.iter_mut().zip(shorter) {
+        *m += v;
+    }
+
+    merged
+}
+
 #[cfg(all(test, unit_test))]
 pub mod tests {
     use std::{array, cmp::min, iter::repeat_with};
@@ -339,13 +358,16 @@ pub mod tests {
     use proptest::prelude::*;
     use rand::{rngs::StdRng, SeedableRng};
 
-    use super::aggregate_values;
+    use super::{aggregate_values, merge_histograms};
     use crate::{
         const_assert,
         error::Error,
-        ff::{boolean::Boolean, boolean_array::BA8, U128Conversions},
+        ff::{boolean::Boolean, boolean_array::BA8, Fp31, U128Conversions},
         helpers::Role,
-        secret_sharing::{BitDecomposed, SharedValue},
+        secret_sharing::{
+            replicated::semi_honest::AdditiveShare as Replicated, BitDecomposed, IntoShares,
+            SharedValue,
+        },
         test_executor::run,
         test_fixture::{Reconstruct, Runner, TestWorld},
     };
@@ -358,6 +380,27 @@ pub mod tests {
         })
     }
 
+    #[test]
+    fn merge_two_histograms() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let a = [3, 1, 4].map(Fp31::truncate_from).to_vec();
+        let b = [2, 7, 1, 8, 2].map(Fp31::truncate_from).to_vec();
+
+        let [a0, a1, a2]: [Vec<Replicated<Fp31>>; 3] = a.share_with(&mut rng);
+        let [b0, b1, b2]: [Vec<Replicated<Fp31>>; 3] = b.share_with(&mut rng);
+        let merged = [
+            merge_histograms(&a0, &b0),
+            merge_histograms(&a1, &b1),
+            merge_histograms(&a2, &b2),
+        ];
+
+        assert_eq!(
+            [5, 8, 5, 8, 2].map(Fp31::truncate_from).to_vec(),
+            merged.reconstruct()
+        );
+        assert_eq!(merged[0], merge_histograms(&b0, &a0));
+    }
+
     #[test]
     fn aggregate_even() {
         // Test aggregation with clean log2 structure
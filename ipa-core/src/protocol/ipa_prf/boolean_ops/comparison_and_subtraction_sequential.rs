@@ -6,29 +6,33 @@
 
 use std::iter::repeat;
 
-#[cfg(all(test, unit_test))]
+#[cfg(any(feature = "descriptive-gate", all(test, unit_test)))]
 use ipa_macros::Step;
 
+#[cfg(any(feature = "descriptive-gate", all(test, unit_test)))]
+use crate::protocol::basics::{select, BooleanArrayMul};
 use crate::{
     error::Error,
-    ff::{boolean::Boolean, ArrayAccessRef, Field},
+    ff::{boolean::Boolean, ArrayAccess, ArrayAccessRef, Field},
     protocol::{
         basics::{BooleanProtocols, SecureMul, ShareKnownValue},
         context::Context,
         step::BitStep,
         RecordId,
     },
-    secret_sharing::{replicated::semi_honest::AdditiveShare, BitDecomposed, FieldSimd},
-};
-#[cfg(all(test, unit_test))]
-use crate::{
-    ff::CustomArray,
-    protocol::{
-        basics::{select, BooleanArrayMul},
-        context::SemiHonestContext,
+    secret_sharing::{
+        replicated::semi_honest::AdditiveShare, BitDecomposed, FieldSimd, SharedValue,
     },
-    secret_sharing::SharedValue,
 };
+#[cfg(all(test, unit_test))]
+use crate::{ff::CustomArray, protocol::context::SemiHonestContext};
+
+#[cfg(feature = "descriptive-gate")]
+#[derive(Step)]
+pub(crate) enum ClampStep {
+    Compare,
+    Select,
+}
 
 /// Comparison operation
 ///
@@ -77,6 +81,86 @@ where
     Ok(carry)
 }
 
+/// Comparison to a public constant
+///
+/// Outputs x>k, where k is known to all helpers. Both values are interpreted as unsigned
+/// integers of `V::BITS` bits.
+///
+/// `x` must be shared as a boolean array, so that its bits are available without a conversion:
+/// there is no protocol to bit-decompose prime field shares. Protocols compare values such as
+/// the per-user cap against attributed trigger values, which are boolean-shared already.
+///
+/// ## Errors
+/// Propagates errors from multiply
+///
+/// ## Panics
+/// If `V::BITS` exceeds the number of steps provided by `S`.
+pub async fn secure_gt_const<C, S, V>(
+    ctx: C,
+    record_id: RecordId,
+    x: &AdditiveShare<V>,
+    k: V,
+) -> Result<AdditiveShare<Boolean>, Error>
+where
+    C: Context,
+    S: BitStep,
+    V: SharedValue + ArrayAccess<Output = Boolean>,
+    AdditiveShare<V>: ArrayAccess<Output = AdditiveShare<Boolean>>,
+    AdditiveShare<Boolean>: BooleanProtocols<C>,
+{
+    assert!(
+        V::BITS <= S::max_bit_depth(),
+        "{} bits do not fit into the comparison steps",
+        V::BITS
+    );
+    let k = BitDecomposed::new(
+        k.iter()
+            .map(|bit| AdditiveShare::<Boolean>::share_known_value(&ctx, bit)),
+    );
+    compare_gt::<_, S, 1>(ctx, record_id, &x.to_bits(), &k).await
+}
+
+/// Clamps `credit` to a public `cap`
+///
+/// Outputs `min(credit, cap)`, obliviously choosing between `credit` and `cap` based on
+/// [`secure_gt_const`]. Both values are interpreted as unsigned integers of `V::BITS` bits, so
+/// the cap must be representable in the same boolean array type as the credit.
+///
+/// Its steps are not part of the IPA protocol yet, so it is only available with descriptive
+/// gates.
+///
+/// ## Errors
+/// Propagates errors from multiply
+///
+/// ## Panics
+/// If `V::BITS` exceeds the number of steps provided by `S`.
+#[cfg(feature = "descriptive-gate")]
+pub async fn clamp_to_cap<C, S, V>(
+    ctx: C,
+    record_id: RecordId,
+    credit: &AdditiveShare<V>,
+    cap: V,
+) -> Result<AdditiveShare<V>, Error>
+where
+    C: Context,
+    S: BitStep,
+    V: SharedValue + ArrayAccess<Output = Boolean>,
+    AdditiveShare<V>: ArrayAccess<Output = AdditiveShare<Boolean>> + BooleanArrayMul<C>,
+    AdditiveShare<Boolean>: BooleanProtocols<C>,
+{
+    let over_cap =
+        secure_gt_const::<_, S, V>(ctx.narrow(&ClampStep::Compare), record_id, credit, cap).await?;
+    let cap = AdditiveShare::<V>::share_known_value(&ctx, cap);
+    select(
+        ctx.narrow(&ClampStep::Select),
+        record_id,
+        &over_cap,
+        &cap,
+        credit,
+    )
+    .await
+}
+
 /// non-saturated unsigned integer subtraction
 /// subtracts y from x, Output has same length as x (carries and indices of y too large for x are ignored).
 /// When y>x, it computes `(x+2^|x|)-y`, considering only the least-significant
@@ -232,14 +316,14 @@ mod test {
     use crate::{
         ff::{
             boolean::Boolean,
-            boolean_array::{BA3, BA32, BA5, BA64},
+            boolean_array::{BA3, BA32, BA5, BA64, BA8},
             ArrayAccess, Expand, Field, U128Conversions,
         },
         protocol::{
             self,
             context::Context,
             ipa_prf::boolean_ops::comparison_and_subtraction_sequential::{
-                compare_geq, compare_gt, integer_sat_sub, integer_sub,
+                compare_geq, compare_gt, integer_sat_sub, integer_sub, secure_gt_const,
             },
             step::DefaultBitStep,
             RecordId,
@@ -381,6 +465,76 @@ mod test {
         });
     }
 
+    #[test]
+    fn semi_honest_gt_const() {
+        run(|| async move {
+            let world = TestWorld::default();
+            for (x, k) in [
+                (0_u8, 0_u8),
+                (5, 3),
+                (3, 5),
+                (7, 7),
+                (255, 254),
+                (254, 255),
+                (200, 0),
+            ] {
+                let result = world
+                    .semi_honest(BA8::truncate_from(x), |ctx, x| async move {
+                        secure_gt_const::<_, DefaultBitStep, _>(
+                            ctx.set_total_records(1),
+                            RecordId::FIRST,
+                            &x,
+                            BA8::truncate_from(k),
+                        )
+                        .await
+                        .unwrap()
+                    })
+                    .await
+                    .reconstruct();
+
+                assert_eq!(Boolean::from(x > k), result, "{x} > {k}");
+            }
+        });
+    }
+
+    #[cfg(feature = "descriptive-gate")]
+    #[test]
+    fn semi_honest_clamp_to_cap() {
+        run(|| async move {
+            let world = TestWorld::default();
+            for (credit, cap) in [
+                (0_u8, 0_u8),
+                (0, 10),
+                (10, 0),
+                (9, 10),
+                (10, 10),
+                (11, 10),
+                (255, 254),
+                (254, 255),
+            ] {
+                let result = world
+                    .semi_honest(BA8::truncate_from(credit), |ctx, credit| async move {
+                        super::clamp_to_cap::<_, DefaultBitStep, _>(
+                            ctx.set_total_records(1),
+                            RecordId::FIRST,
+                            &credit,
+                            BA8::truncate_from(cap),
+                        )
+                        .await
+                        .unwrap()
+                    })
+                    .await
+                    .reconstruct();
+
+                assert_eq!(
+                    BA8::truncate_from(credit.min(cap)),
+                    result,
+                    "min({credit}, {cap})"
+                );
+            }
+        });
+    }
+
     #[cfg(not(coverage))]
     const BENCH_COUNT: usize = 131_072;
 
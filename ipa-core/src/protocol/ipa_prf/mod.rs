@@ -10,7 +10,7 @@ use crate::{
     error::{Error, LengthError, UnwrapInfallible},
     ff::{
         boolean::Boolean, boolean_array::BA64, ec_prime_field::Fp25519, CustomArray, Serializable,
-        U128Conversions,
+        U128Conversions, VersionedSerializable,
     },
     helpers::stream::{process_slice_by_chunks, ChunkData, TryFlattenItersExt},
     protocol::{
@@ -47,6 +47,8 @@ mod malicious_security;
 mod quicksort;
 mod shuffle;
 
+pub use aggregation::merge_histograms;
+
 /// Match key type
 pub type MatchKey = BA64;
 /// Match key size
@@ -70,6 +72,12 @@ pub(crate) enum Step {
     SortByTimestamp,
 }
 
+/// A single input row of the OPRF IPA protocol.
+///
+/// The breakdown key can be any boolean array, including wide ones such as `BA16` or `BA20`. It
+/// is serialized in `ceil(BK::BITS / 8)` bytes per share, so the serialization format puts no
+/// bound on its width. In practice, it is bounded by the number of breakdowns the query
+/// aggregates into.
 #[derive(Clone, Debug)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct OPRFIPAInputRow<BK: SharedValue, TV: SharedValue, TS: SharedValue> {
@@ -80,6 +88,36 @@ pub struct OPRFIPAInputRow<BK: SharedValue, TV: SharedValue, TS: SharedValue> {
     pub timestamp: Replicated<TS>,
 }
 
+/// The default row has all of its fields set to zero. Such a row is a source event with a
+/// zero breakdown key, so it does not contribute anything to the final result and can be used
+/// for padding.
+impl<BK: SharedValue, TV: SharedValue, TS: SharedValue> Default for OPRFIPAInputRow<BK, TV, TS> {
+    fn default() -> Self {
+        Self {
+            match_key: Replicated::<MatchKey>::ZERO,
+            is_trigger: Replicated::<Boolean>::ZERO,
+            breakdown_key: Replicated::<BK>::ZERO,
+            trigger_value: Replicated::<TV>::ZERO,
+            timestamp: Replicated::<TS>::ZERO,
+        }
+    }
+}
+
+/// Pads `rows` with default rows until its length becomes a power of two. Empty input is left
+/// as is.
+///
+/// Returns the number of rows added, so callers can trim the results afterwards.
+pub fn pad_to_pow2<R: Default>(rows: &mut Vec<R>) -> usize {
+    if rows.is_empty() {
+        return 0;
+    }
+
+    let padding = rows.len().next_power_of_two() - rows.len();
+    rows.extend(iter::repeat_with(R::default).take(padding));
+
+    padding
+}
+
 impl<BK: SharedValue, TV: SharedValue, TS: SharedValue> Serializable for OPRFIPAInputRow<BK, TV, TS>
 where
     Replicated<BK>: Serializable,
@@ -168,6 +206,14 @@ where
     }
 }
 
+impl<BK: SharedValue, TV: SharedValue, TS: SharedValue> VersionedSerializable
+    for OPRFIPAInputRow<BK, TV, TS>
+where
+    Self: Serializable,
+{
+    const SERIALIZATION_VERSION: u8 = 1;
+}
+
 /// IPA OPRF Protocol
 ///
 /// The output of this function is a vector of secret-shared totals, one per breakdown key
@@ -306,13 +352,7 @@ where
                     }))
                 }
             },
-            || OPRFIPAInputRow {
-                match_key: Replicated::<MatchKey>::ZERO,
-                is_trigger: Replicated::<Boolean>::ZERO,
-                breakdown_key: Replicated::<BK>::ZERO,
-                trigger_value: Replicated::<TV>::ZERO,
-                timestamp: Replicated::<TS>::ZERO,
-            },
+            OPRFIPAInputRow::default,
         ),
     )
     .try_flatten_iters()
@@ -322,12 +362,20 @@ where
 
 #[cfg(all(test, any(unit_test, feature = "shuttle")))]
 pub mod tests {
+    use generic_array::GenericArray;
+    use typenum::Unsigned;
+
     use crate::{
+        error::{Error, LengthError},
         ff::{
             boolean_array::{BA16, BA20, BA3, BA8},
-            U128Conversions,
+            Serializable, SerializableExt, U128Conversions, VersionedSerializable,
+        },
+        protocol::ipa_prf::{oprf_ipa, pad_to_pow2, OPRFIPAInputRow},
+        secret_sharing::{
+            replicated::{semi_honest::AdditiveShare as Replicated, ReplicatedSecretSharing},
+            SharedValue,
         },
-        protocol::ipa_prf::oprf_ipa,
         test_executor::run,
         test_fixture::{ipa::TestRawDataRecord, Reconstruct, Runner, TestWorld},
     };
@@ -423,4 +471,153 @@ pub mod tests {
             );
         });
     }
+
+    #[test]
+    fn pad_rows_to_pow2() {
+        let mut rows = (0..5_u32)
+            .map(|i| OPRFIPAInputRow::<BA8, BA3, BA20> {
+                breakdown_key: Replicated::new(BA8::truncate_from(i), BA8::truncate_from(i)),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(3, pad_to_pow2(&mut rows));
+        assert_eq!(8, rows.len());
+        for (i, row) in (0..5_u32).zip(&rows) {
+            assert_eq!(
+                Replicated::new(BA8::truncate_from(i), BA8::truncate_from(i)),
+                row.breakdown_key
+            );
+        }
+        for row in &rows[5..] {
+            assert_eq!(OPRFIPAInputRow::default(), *row);
+            assert_eq!(Replicated::<BA8>::ZERO, row.breakdown_key);
+        }
+
+        // already a power of two
+        assert_eq!(0, pad_to_pow2(&mut rows));
+        assert_eq!(
+            0,
+            pad_to_pow2(&mut Vec::<OPRFIPAInputRow<BA8, BA3, BA20>>::new())
+        );
+    }
+
+    #[test]
+    fn serialized_size() {
+        type Row = OPRFIPAInputRow<BA8, BA3, BA20>;
+        let rows = (0..7_u32)
+            .map(|i| Row {
+                trigger_value: Replicated::new(BA3::truncate_from(i), BA3::truncate_from(i)),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let size = Row::total_serialized_size(rows.len()).unwrap();
+        let buf = Row::serialize_all(&rows);
+        assert_eq!(size, buf.len());
+
+        let row_size = size / rows.len();
+        let deserialized = buf
+            .chunks_exact(row_size)
+            .map(|chunk| Row::deserialize(GenericArray::from_slice(chunk)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, deserialized);
+
+        assert_eq!(Some(0), Row::total_serialized_size(0));
+        assert_eq!(None, Row::total_serialized_size(usize::MAX));
+    }
+
+    #[test]
+    fn row_count() {
+        type Row = OPRFIPAInputRow<BA8, BA3, BA20>;
+        let row_size = <Row as Serializable>::Size::USIZE;
+
+        assert_eq!(Ok(0), Row::row_count(0));
+        assert_eq!(Ok(3), Row::row_count(3 * row_size));
+        assert_eq!(
+            Err(LengthError {
+                expected: 4 * row_size,
+                actual: 3 * row_size + 1,
+            }),
+            Row::row_count(3 * row_size + 1)
+        );
+        assert!(Row::row_count(row_size - 1).is_err());
+    }
+
+    #[test]
+    fn versioned_serialization() {
+        type Row = OPRFIPAInputRow<BA8, BA3, BA20>;
+        let rows = (0..3_u32)
+            .map(|i| Row {
+                breakdown_key: Replicated::new(BA8::truncate_from(i), BA8::truncate_from(i + 1)),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let buf = Row::serialize_versioned(&rows);
+        assert_eq!(Row::SERIALIZATION_VERSION, buf[0]);
+        assert_eq!(Row::serialize_all(&rows), buf[1..]);
+        assert_eq!(rows, Row::deserialize_versioned(&buf).unwrap());
+
+        let mut unknown = buf.clone();
+        unknown[0] = Row::SERIALIZATION_VERSION + 1;
+        assert!(matches!(
+            Row::deserialize_versioned(&unknown),
+            Err(Error::UnsupportedVersion(v)) if v == Row::SERIALIZATION_VERSION + 1
+        ));
+        assert!(matches!(
+            Row::deserialize_versioned(&buf[..buf.len() - 1]),
+            Err(Error::LengthError(_))
+        ));
+        assert!(matches!(
+            Row::deserialize_versioned(&[]),
+            Err(Error::LengthError(_))
+        ));
+    }
+
+    fn breakdown_key_round_trip<BK>()
+    where
+        BK: SharedValue + U128Conversions,
+        Replicated<BK>: Serializable,
+        OPRFIPAInputRow<BK, BA3, BA20>: Serializable,
+    {
+        let max = (1_u128 << BK::BITS) - 1;
+        let rows = [0, 1, max / 2, max].map(|v| OPRFIPAInputRow::<BK, BA3, BA20> {
+            breakdown_key: Replicated::new(BK::truncate_from(v), BK::truncate_from(max - v)),
+            ..Default::default()
+        });
+
+        let buf = OPRFIPAInputRow::serialize_all(&rows);
+        let deserialized = buf
+            .chunks_exact(buf.len() / rows.len())
+            .map(|chunk| OPRFIPAInputRow::deserialize(GenericArray::from_slice(chunk)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(rows.to_vec(), deserialized);
+
+        let bk_size = <Replicated<BK> as Serializable>::Size::USIZE;
+        assert_eq!(2 * usize::try_from(BK::BITS.div_ceil(8)).unwrap(), bk_size);
+        let buf = Replicated::<BK>::serialize_all(rows.iter().map(|row| &row.breakdown_key));
+        assert_eq!(rows.len() * bk_size, buf.len());
+        let keys = Replicated::<BK>::from_byte_slice(&buf)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            rows.iter()
+                .map(|row| row.breakdown_key.clone())
+                .collect::<Vec<_>>(),
+            keys
+        );
+    }
+
+    #[test]
+    fn wide_breakdown_keys() {
+        breakdown_key_round_trip::<BA16>();
+        breakdown_key_round_trip::<BA20>();
+
+        // bits above the width of the breakdown key are rejected
+        assert!(Replicated::<BA20>::from_byte_slice(&[0xff; 6])
+            .next()
+            .unwrap()
+            .is_err());
+    }
 }
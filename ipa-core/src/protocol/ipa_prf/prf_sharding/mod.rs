@@ -1,4 +1,5 @@
 use std::{
+    collections::HashSet,
     convert::Infallible,
     iter::zip,
     num::NonZeroU32,
@@ -13,6 +14,8 @@ use futures::{
 use ipa_macros::Step;
 
 use super::boolean_ops::expand_shared_array_in_place;
+#[cfg(feature = "descriptive-gate")]
+use crate::protocol::ipa_prf::boolean_ops::comparison_and_subtraction_sequential::clamp_to_cap;
 use crate::{
     error::{Error, LengthError},
     ff::{
@@ -304,10 +307,46 @@ pub(crate) enum Step {
     Aggregate,
 }
 
+/// Steps of the per-user credit cap of [`attribute_cap_aggregate_with_credit_cap`]. They are not
+/// part of the IPA protocol yet, so the credit cap is only available with descriptive gates.
+#[cfg(feature = "descriptive-gate")]
+#[derive(Step)]
+pub(crate) enum CreditCapStep {
+    AddToCredit,
+    ClampToCap,
+    SelectWrappedCredit,
+    ComputeClampedContribution,
+}
+
 pub trait GroupingKey {
     fn get_grouping_key(&self) -> u64;
 }
 
+/// Checks that all rows of a given user are adjacent to one another, which is what
+/// [`attribute_cap_aggregate`] assumes. A user that shows up again after rows of another user
+/// is attributed as two separate users, silently producing wrong results.
+///
+/// This needs the grouping key in the clear, so it is only useful before inputs are shared or
+/// after the PRF is revealed.
+///
+/// ## Errors
+/// [`Error::UngroupedInput`] with the index of the first row that starts a second run of rows
+/// for the same user.
+pub fn check_user_grouping<R: GroupingKey>(rows: &[R]) -> Result<(), Error> {
+    let mut seen = HashSet::new();
+    let mut last = None;
+    for (row, key) in rows.iter().map(GroupingKey::get_grouping_key).enumerate() {
+        if last != Some(key) {
+            if !seen.insert(key) {
+                return Err(Error::UngroupedInput { row });
+            }
+            last = Some(key);
+        }
+    }
+
+    Ok(())
+}
+
 #[tracing::instrument(name = "histograms_ranges_sortkeys", skip_all)]
 /// This function does following computations per user
 /// 1. Compute histogram of users with row counts
@@ -411,6 +450,15 @@ where
 ///
 /// This circuit will compute attribution, and per-user capping.
 ///
+/// The per-user cap is `2^SS_BITS`. Running sums are computed with bit-wise (boolean) addition
+/// that reports the carry out of the top bit, and a user is marked saturated once that carry is
+/// set. The sum therefore never wraps around a modulus: contributions beyond the cap are clamped
+/// to the remaining difference, and everything after that is zeroed out.
+///
+/// Attribution, capping and aggregation run as a single pass over the rows of each user. Outputs
+/// of one phase feed the next one as Boolean shares, so no reshares or conversions happen between
+/// them.
+///
 /// The output of this circuit is the input to the next stage: Aggregation.
 ///
 /// # Errors
@@ -424,6 +472,108 @@ pub async fn attribute_cap_aggregate<'ctx, BK, TV, HV, TS, const SS_BITS: usize,
     attribution_window_seconds: Option<NonZeroU32>,
     histogram: &[usize],
 ) -> Result<Vec<Replicated<HV>>, Error>
+where
+    BK: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    TV: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    HV: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    TS: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    Boolean: FieldSimd<B>,
+    Replicated<Boolean, B>:
+        BooleanProtocols<UpgradedSemiHonestContext<'ctx, NotSharded, Boolean>, B>,
+    for<'a> Replicated<BK>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    for<'a> Replicated<TS>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    for<'a> Replicated<TV>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    BitDecomposed<Replicated<Boolean, AGG_CHUNK>>:
+        for<'a> TransposeFrom<&'a Vec<Replicated<BK>>, Error = LengthError>,
+    BitDecomposed<Replicated<Boolean, AGG_CHUNK>>:
+        for<'a> TransposeFrom<&'a Vec<Replicated<TV>>, Error = LengthError>,
+    Vec<BitDecomposed<Replicated<Boolean, B>>>: for<'a> TransposeFrom<
+        &'a [BitDecomposed<Replicated<Boolean, AGG_CHUNK>>],
+        Error = Infallible,
+    >,
+    Vec<Replicated<HV>>:
+        for<'a> TransposeFrom<&'a BitDecomposed<Replicated<Boolean, B>>, Error = LengthError>,
+{
+    attribute_cap_aggregate_inner::<BK, TV, HV, TS, SS_BITS, B>(
+        sh_ctx,
+        input_rows,
+        attribution_window_seconds,
+        None,
+        histogram,
+    )
+    .await
+}
+
+/// Same as [`attribute_cap_aggregate`], but the credit of each user is additionally clamped to
+/// `credit_cap`, which does not have to be a power of two. Every user starts with no credit. Each
+/// capped contribution is added to it, the sum is clamped to the cap and the contribution is
+/// replaced by how much the credit actually grew. A sum that wraps around is above the cap, so
+/// contributions that add up to more than `TV` can hold are clamped as well. Caps above
+/// `2^SS_BITS` have no effect.
+///
+/// Its steps are not part of the IPA protocol yet, so it is only available with descriptive
+/// gates.
+///
+/// # Errors
+/// Propagates errors from multiplications
+/// # Panics
+/// Propagates errors from multiplications
+#[cfg(feature = "descriptive-gate")]
+pub async fn attribute_cap_aggregate_with_credit_cap<
+    'ctx,
+    BK,
+    TV,
+    HV,
+    TS,
+    const SS_BITS: usize,
+    const B: usize,
+>(
+    sh_ctx: SemiHonestContext<'ctx>,
+    input_rows: Vec<PrfShardedIpaInputRow<BK, TV, TS>>,
+    attribution_window_seconds: Option<NonZeroU32>,
+    credit_cap: TV,
+    histogram: &[usize],
+) -> Result<Vec<Replicated<HV>>, Error>
+where
+    BK: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    TV: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    HV: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    TS: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    Boolean: FieldSimd<B>,
+    Replicated<Boolean, B>:
+        BooleanProtocols<UpgradedSemiHonestContext<'ctx, NotSharded, Boolean>, B>,
+    for<'a> Replicated<BK>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    for<'a> Replicated<TS>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    for<'a> Replicated<TV>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    BitDecomposed<Replicated<Boolean, AGG_CHUNK>>:
+        for<'a> TransposeFrom<&'a Vec<Replicated<BK>>, Error = LengthError>,
+    BitDecomposed<Replicated<Boolean, AGG_CHUNK>>:
+        for<'a> TransposeFrom<&'a Vec<Replicated<TV>>, Error = LengthError>,
+    Vec<BitDecomposed<Replicated<Boolean, B>>>: for<'a> TransposeFrom<
+        &'a [BitDecomposed<Replicated<Boolean, AGG_CHUNK>>],
+        Error = Infallible,
+    >,
+    Vec<Replicated<HV>>:
+        for<'a> TransposeFrom<&'a BitDecomposed<Replicated<Boolean, B>>, Error = LengthError>,
+{
+    attribute_cap_aggregate_inner::<BK, TV, HV, TS, SS_BITS, B>(
+        sh_ctx,
+        input_rows,
+        attribution_window_seconds,
+        Some(credit_cap),
+        histogram,
+    )
+    .await
+}
+
+/// `credit_cap` is always `None` without descriptive gates.
+async fn attribute_cap_aggregate_inner<'ctx, BK, TV, HV, TS, const SS_BITS: usize, const B: usize>(
+    sh_ctx: SemiHonestContext<'ctx>,
+    input_rows: Vec<PrfShardedIpaInputRow<BK, TV, TS>>,
+    attribution_window_seconds: Option<NonZeroU32>,
+    credit_cap: Option<TV>,
+    histogram: &[usize],
+) -> Result<Vec<Replicated<HV>>, Error>
 where
     BK: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
     TV: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
@@ -477,6 +627,7 @@ where
                     RecordId::from(record_id),
                     rows_for_user,
                     attribution_window_seconds,
+                    credit_cap,
                 )
             });
 
@@ -495,11 +646,64 @@ where
     .await
 }
 
+/// Last-touch attribution with a per-user cap, without an attribution window.
+///
+/// This is the common configuration of [`attribute_cap_aggregate`], which caps the credit of
+/// each user at `2^SS_BITS`. It computes the user histogram from `input_rows` itself. Input rows
+/// must be grouped by user and ordered by timestamp within each user.
+///
+/// # Errors
+/// Propagates errors from multiplications
+/// # Panics
+/// Propagates errors from multiplications
+pub async fn attribute_last_touch_capped<
+    'ctx,
+    BK,
+    TV,
+    HV,
+    TS,
+    const SS_BITS: usize,
+    const B: usize,
+>(
+    sh_ctx: SemiHonestContext<'ctx>,
+    mut input_rows: Vec<PrfShardedIpaInputRow<BK, TV, TS>>,
+) -> Result<Vec<Replicated<HV>>, Error>
+where
+    BK: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    TV: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    HV: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    TS: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    Boolean: FieldSimd<B>,
+    Replicated<Boolean, B>:
+        BooleanProtocols<UpgradedSemiHonestContext<'ctx, NotSharded, Boolean>, B>,
+    for<'a> Replicated<BK>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    for<'a> Replicated<TS>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    for<'a> Replicated<TV>: BooleanArrayMul<UpgradedSemiHonestContext<'a, NotSharded, Boolean>>,
+    BitDecomposed<Replicated<Boolean, AGG_CHUNK>>:
+        for<'a> TransposeFrom<&'a Vec<Replicated<BK>>, Error = LengthError>,
+    BitDecomposed<Replicated<Boolean, AGG_CHUNK>>:
+        for<'a> TransposeFrom<&'a Vec<Replicated<TV>>, Error = LengthError>,
+    Vec<BitDecomposed<Replicated<Boolean, B>>>: for<'a> TransposeFrom<
+        &'a [BitDecomposed<Replicated<Boolean, AGG_CHUNK>>],
+        Error = Infallible,
+    >,
+    Vec<Replicated<HV>>:
+        for<'a> TransposeFrom<&'a BitDecomposed<Replicated<Boolean, B>>, Error = LengthError>,
+{
+    if input_rows.is_empty() {
+        return Ok(vec![]);
+    }
+    let (histogram, _) = histograms_ranges_sortkeys(&mut input_rows);
+    attribute_cap_aggregate::<BK, TV, HV, TS, SS_BITS, B>(sh_ctx, input_rows, None, &histogram)
+        .await
+}
+
 async fn evaluate_per_user_attribution_circuit<BK, TV, TS, const SS_BITS: usize>(
     ctx_for_row_number: Vec<UpgradedSemiHonestContext<'_, NotSharded, Boolean>>,
     record_id: RecordId,
     rows_for_user: Vec<PrfShardedIpaInputRow<BK, TV, TS>>,
     attribution_window_seconds: Option<NonZeroU32>,
+    #[cfg_attr(not(feature = "descriptive-gate"), allow(unused_variables))] credit_cap: Option<TV>,
 ) -> Result<Vec<AttributionOutputs<Replicated<BK>, Replicated<TV>>>, Error>
 where
     BK: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
@@ -517,11 +721,26 @@ where
     let mut prev_row_inputs =
         initialize_new_device_attribution_variables::<BK, TV, TS, SS_BITS>(first_row);
 
+    #[cfg(feature = "descriptive-gate")]
+    let mut credit = credit_cap.map(|cap| (cap, Replicated::<TV>::ZERO));
+
     let mut output = Vec::with_capacity(rows_for_user.len() - 1);
     for (row, ctx) in zip(rows_for_user.iter().skip(1), ctx_for_row_number.into_iter()) {
-        let capped_attribution_outputs = prev_row_inputs
-            .compute_row_with_previous(ctx, record_id, row, attribution_window_seconds)
+        #[cfg_attr(not(feature = "descriptive-gate"), allow(unused_mut))]
+        let mut capped_attribution_outputs = prev_row_inputs
+            .compute_row_with_previous(ctx.clone(), record_id, row, attribution_window_seconds)
+            .await?;
+        #[cfg(feature = "descriptive-gate")]
+        if let Some((cap, credit)) = credit.as_mut() {
+            capped_attribution_outputs.capped_attributed_trigger_value = clamp_to_credit_cap(
+                ctx,
+                record_id,
+                credit,
+                *cap,
+                &capped_attribution_outputs.capped_attributed_trigger_value,
+            )
             .await?;
+        }
 
         output.push(capped_attribution_outputs);
     }
@@ -689,39 +908,69 @@ where
     Replicated<Boolean>: BooleanProtocols<C>,
 {
     if let Some(attribution_window_seconds) = attribution_window_seconds {
-        assert!(
-            TS::BITS <= ThirtyTwoBitStep::max_bit_depth(),
-            "ThirtyTwoBitStep is not large enough to accomodate this subtraction"
-        );
-        let time_delta_bits = integer_sub::<_, ThirtyTwoBitStep>(
-            ctx.narrow(&Step::ComputeTimeDelta),
-            record_id,
-            &trigger_event_timestamp.to_bits(),
-            &source_event_timestamp.to_bits(),
-        )
-        .await?;
-
-        let attribution_window_bits = BitDecomposed::decompose(TS::BITS, |i| {
-            Replicated::share_known_value(
-                &ctx,
-                Boolean::truncate_from((attribution_window_seconds.get() >> i) & 0x1),
-            )
-        });
-
-        let time_delta_gt_attribution_window = compare_gt::<_, ThirtyTwoBitStep, 1>(
-            ctx.narrow(&Step::CompareTimeDeltaToAttributionWindow),
+        within_attribution_window(
+            ctx,
             record_id,
-            &time_delta_bits,
-            &attribution_window_bits,
+            trigger_event_timestamp,
+            source_event_timestamp,
+            TS::truncate_from(attribution_window_seconds.get()),
         )
-        .await?;
-        Ok(time_delta_gt_attribution_window.not())
+        .await
     } else {
         // if there is no attribution window, then all trigger events are attributed
         Ok(Replicated::share_known_value(&ctx, Boolean::ONE))
     }
 }
 
+/// Returns a secret-shared bit indicating whether `trigger_ts - source_ts <= window`, i.e.
+/// whether the trigger event happened within the attribution window of the source event.
+/// Timestamps and the window are unsigned integers of `TS::BITS` bits. The trigger event must
+/// not precede the source event, otherwise the difference wraps around.
+///
+/// ## Errors
+/// Propagates errors from multiply
+///
+/// ## Panics
+/// If `TS::BITS` exceeds 32.
+pub async fn within_attribution_window<C, TS>(
+    ctx: C,
+    record_id: RecordId,
+    trigger_ts: &Replicated<TS>,
+    source_ts: &Replicated<TS>,
+    window: TS,
+) -> Result<Replicated<Boolean>, Error>
+where
+    C: Context,
+    TS: SharedValue + U128Conversions + CustomArray<Element = Boolean>,
+    Replicated<Boolean>: BooleanProtocols<C>,
+{
+    assert!(
+        TS::BITS <= ThirtyTwoBitStep::max_bit_depth(),
+        "ThirtyTwoBitStep is not large enough to accomodate this subtraction"
+    );
+    let time_delta_bits = integer_sub::<_, ThirtyTwoBitStep>(
+        ctx.narrow(&Step::ComputeTimeDelta),
+        record_id,
+        &trigger_ts.to_bits(),
+        &source_ts.to_bits(),
+    )
+    .await?;
+
+    let window = window.as_u128();
+    let attribution_window_bits = BitDecomposed::decompose(TS::BITS, |i| {
+        Replicated::share_known_value(&ctx, Boolean::truncate_from((window >> i) & 0x1))
+    });
+
+    let time_delta_gt_attribution_window = compare_gt::<_, ThirtyTwoBitStep, 1>(
+        ctx.narrow(&Step::CompareTimeDeltaToAttributionWindow),
+        record_id,
+        &time_delta_bits,
+        &attribution_window_bits,
+    )
+    .await?;
+    Ok(time_delta_gt_attribution_window.not())
+}
+
 ///
 /// To provide a differential privacy guarantee, we need to bound the maximum contribution from any given user to some cap.
 ///
@@ -776,18 +1025,86 @@ where
     .await
 }
 
+/// Adds a capped contribution to the credit of a user, clamping the credit to the credit cap of
+/// [`attribute_cap_aggregate_with_credit_cap`], and returns how much the credit grew:
+/// ```ignore
+/// new_credit = min(credit + contribution, cap)
+/// contribution = new_credit - credit
+/// credit = new_credit
+/// ```
+///
+/// ## Panics
+/// If `TV` is wider than 8 bits.
+#[cfg(feature = "descriptive-gate")]
+async fn clamp_to_credit_cap<C, TV>(
+    ctx: C,
+    record_id: RecordId,
+    credit: &mut Replicated<TV>,
+    cap: TV,
+    contribution: &Replicated<TV>,
+) -> Result<Replicated<TV>, Error>
+where
+    C: Context,
+    TV: SharedValue + CustomArray<Element = Boolean>,
+    Replicated<TV>: BooleanArrayMul<C>,
+    Replicated<Boolean>: BooleanProtocols<C>,
+{
+    assert!(
+        TV::BITS <= EightBitStep::max_bit_depth(),
+        "EightBitStep not large enough to accommodate this sum"
+    );
+    let (sum, wrapped) = integer_add::<_, EightBitStep, 1>(
+        ctx.narrow(&CreditCapStep::AddToCredit),
+        record_id,
+        &credit.to_bits(),
+        &contribution.to_bits(),
+    )
+    .await?;
+    let clamped = clamp_to_cap::<_, EightBitStep, TV>(
+        ctx.narrow(&CreditCapStep::ClampToCap),
+        record_id,
+        &sum.collect_bits(),
+        cap,
+    )
+    .await?;
+    // The credit never exceeds the cap, so if the sum wrapped around it was above the cap.
+    let new_credit = select(
+        ctx.narrow(&CreditCapStep::SelectWrappedCredit),
+        record_id,
+        &wrapped,
+        &Replicated::<TV>::share_known_value(&ctx, cap),
+        &clamped,
+    )
+    .await?;
+    let contribution = integer_sub::<_, EightBitStep>(
+        ctx.narrow(&CreditCapStep::ComputeClampedContribution),
+        record_id,
+        &new_credit.to_bits(),
+        &credit.to_bits(),
+    )
+    .await?
+    .collect_bits();
+    *credit = new_credit;
+
+    Ok(contribution)
+}
+
 #[cfg(all(test, unit_test))]
 pub mod tests {
     use std::num::NonZeroU32;
 
-    use super::{AttributionOutputs, PrfShardedIpaInputRow};
+    use super::{
+        check_user_grouping, within_attribution_window, AttributionOutputs, GroupingKey,
+        PrfShardedIpaInputRow,
+    };
     use crate::{
+        error::Error,
         ff::{
             boolean::Boolean,
             boolean_array::{BA16, BA20, BA3, BA5, BA8},
             CustomArray, Field, U128Conversions,
         },
-        protocol::ipa_prf::prf_sharding::attribute_cap_aggregate,
+        protocol::{context::Context, ipa_prf::prf_sharding::attribute_cap_aggregate, RecordId},
         rand::Rng,
         secret_sharing::{
             replicated::semi_honest::AdditiveShare as Replicated, IntoShares, SharedValue,
@@ -1133,4 +1450,299 @@ pub mod tests {
             );
         });
     }
+
+    #[test]
+    fn capping_does_not_wrap() {
+        // A single user whose trigger values add up to several multiples of the cap
+        const HISTOGRAM: [usize; 6] = [1, 1, 1, 1, 1, 1];
+
+        run(|| async move {
+            let world = TestWorld::default();
+
+            let records: Vec<PreShardedAndSortedOPRFTestInput<BA5, BA3, BA20>> = vec![
+                oprf_test_input(123, false, 9, 0),
+                oprf_test_input(123, true, 0, 7), // running-sum = 7
+                oprf_test_input(123, true, 0, 7), // running-sum = 14, 6 (mod 8)
+                oprf_test_input(123, true, 0, 7), // running-sum = 21, 5 (mod 8)
+                oprf_test_input(123, true, 0, 7), // running-sum = 28, 4 (mod 8)
+                oprf_test_input(123, true, 0, 7), // running-sum = 35, 3 (mod 8)
+            ];
+
+            let mut expected = [0_u128; 32];
+            expected[9] = 8; // per-user cap is 2^3
+
+            let result: Vec<_> = world
+                .semi_honest(records.into_iter(), |ctx, input_rows| async move {
+                    attribute_cap_aggregate::<BA5, BA3, BA8, BA20, 3, 32>(
+                        ctx, input_rows, None, &HISTOGRAM,
+                    )
+                    .await
+                    .unwrap()
+                })
+                .await
+                .reconstruct();
+            assert_eq!(
+                result
+                    .iter()
+                    .map(U128Conversions::as_u128)
+                    .collect::<Vec<_>>(),
+                &expected
+            );
+        });
+    }
+
+    #[cfg(feature = "descriptive-gate")]
+    #[test]
+    fn credit_cap_clamps() {
+        use super::attribute_cap_aggregate_with_credit_cap;
+
+        // Two users, whose trigger values wrap around in 3 bits when summed
+        const HISTOGRAM: [usize; 6] = [2, 2, 2, 1, 1, 1];
+
+        run(|| async move {
+            let world = TestWorld::default();
+
+            let records: Vec<PreShardedAndSortedOPRFTestInput<BA5, BA3, BA20>> = vec![
+                oprf_test_input(123, false, 9, 0),
+                oprf_test_input(123, true, 0, 3), // credit = 3
+                oprf_test_input(123, true, 0, 7), // credit = 10, 2 (mod 8), clamped to 5
+                oprf_test_input(123, true, 0, 7), // credit stays at 5
+                oprf_test_input(123, true, 0, 7),
+                oprf_test_input(123, true, 0, 7),
+                oprf_test_input(234, false, 12, 0),
+                oprf_test_input(234, true, 0, 2), // credit = 2
+                oprf_test_input(234, true, 0, 2), // credit = 4, below the cap
+            ];
+
+            let mut expected = [0_u128; 32];
+            expected[9] = 5;
+            expected[12] = 4;
+
+            let result: Vec<_> = world
+                .semi_honest(records.into_iter(), |ctx, input_rows| async move {
+                    attribute_cap_aggregate_with_credit_cap::<BA5, BA3, BA8, BA20, 3, 32>(
+                        ctx,
+                        input_rows,
+                        None,
+                        BA3::truncate_from(5_u128),
+                        &HISTOGRAM,
+                    )
+                    .await
+                    .unwrap()
+                })
+                .await
+                .reconstruct();
+            assert_eq!(
+                result
+                    .iter()
+                    .map(U128Conversions::as_u128)
+                    .collect::<Vec<_>>(),
+                &expected
+            );
+        });
+    }
+
+    #[test]
+    fn attribution_window_predicate() {
+        run(|| async move {
+            let world = TestWorld::default();
+
+            // (trigger timestamp, source timestamp, window, expected)
+            for (trigger, source, window, expected) in [
+                (10, 5, 5, true),
+                (10, 5, 4, false),
+                (7, 7, 0, true),
+                (100, 0, 99, false),
+                (1_000_000, 1, 999_999, true),
+                (1_048_575, 0, 1_048_574, false),
+            ] {
+                let timestamps = [trigger, source].map(BA20::truncate_from::<u32>);
+                let result = world
+                    .semi_honest(timestamps.into_iter(), |ctx, ts| async move {
+                        within_attribution_window(
+                            ctx.set_total_records(1),
+                            RecordId::FIRST,
+                            &ts[0],
+                            &ts[1],
+                            BA20::truncate_from::<u32>(window),
+                        )
+                        .await
+                        .unwrap()
+                    })
+                    .await
+                    .reconstruct();
+                assert_eq!(
+                    Boolean::from(expected),
+                    result,
+                    "{trigger} - {source} <= {window}"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn user_grouping() {
+        struct Row(u64);
+        impl GroupingKey for Row {
+            fn get_grouping_key(&self) -> u64 {
+                self.0
+            }
+        }
+
+        let rows = |keys: &[u64]| keys.iter().copied().map(Row).collect::<Vec<_>>();
+
+        check_user_grouping::<Row>(&[]).unwrap();
+        check_user_grouping(&rows(&[1, 1, 2, 3, 3, 3])).unwrap();
+        assert!(matches!(
+            check_user_grouping(&rows(&[1, 1, 2, 1, 3])),
+            Err(Error::UngroupedInput { row: 3 })
+        ));
+        assert!(matches!(
+            check_user_grouping(&rows(&[4, 5, 6, 6, 5])),
+            Err(Error::UngroupedInput { row: 4 })
+        ));
+    }
+
+    #[test]
+    fn matches_cleartext_oracle() {
+        use std::collections::HashMap;
+
+        use futures::future::join3;
+
+        use super::histograms_ranges_sortkeys;
+        use crate::test_fixture::ipa::{
+            build_attribution_input, expected_breakdown_totals, TestRawDataRecord,
+        };
+
+        let event = |user_id, timestamp, is_trigger_report, breakdown_key, trigger_value| {
+            TestRawDataRecord {
+                timestamp,
+                user_id,
+                is_trigger_report,
+                breakdown_key,
+                trigger_value,
+            }
+        };
+
+        run(|| async move {
+            let world = TestWorld::default();
+            let events = [
+                // trigger ahead of any source is not attributed
+                event(1, 1, true, 0, 5),
+                event(1, 2, false, 3, 0),
+                event(1, 4, true, 0, 2),
+                // the last source gets the credit, capped at 8
+                event(2, 1, false, 4, 0),
+                event(2, 2, false, 7, 0),
+                event(2, 3, true, 0, 6),
+                event(2, 5, true, 0, 7),
+                // a user with a single row does not contribute
+                event(3, 1, false, 1, 0),
+                event(4, 2, false, 3, 0),
+                event(4, 6, true, 0, 3),
+            ];
+
+            let mut rows = build_attribution_input::<BA5, BA3, BA20, _>(
+                &events,
+                &mut crate::rand::thread_rng(),
+            );
+            let (histogram, _) = histograms_ranges_sortkeys(&mut rows[0]);
+            let [r0, r1, r2] = rows;
+            let [c0, c1, c2] = world.contexts();
+            let (h1, h2, h3) = join3(
+                attribute_cap_aggregate::<BA5, BA3, BA16, BA20, 3, 32>(c0, r0, None, &histogram),
+                attribute_cap_aggregate::<BA5, BA3, BA16, BA20, 3, 32>(c1, r1, None, &histogram),
+                attribute_cap_aggregate::<BA5, BA3, BA16, BA20, 3, 32>(c2, r2, None, &histogram),
+            )
+            .await;
+
+            let result = [h1.unwrap(), h2.unwrap(), h3.unwrap()].reconstruct();
+            let actual = result
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.as_u128() != 0)
+                .map(|(bk, v)| {
+                    (
+                        u64::try_from(bk).unwrap(),
+                        u64::try_from(v.as_u128()).unwrap(),
+                    )
+                })
+                .collect::<HashMap<_, _>>();
+            assert_eq!(expected_breakdown_totals(&events, 8, None), actual);
+        });
+    }
+
+    #[test]
+    fn last_touch_capped_matches_cleartext_oracle() {
+        use std::collections::HashMap;
+
+        use futures::future::join3;
+
+        use super::attribute_last_touch_capped;
+        use crate::test_fixture::ipa::{
+            build_attribution_input, expected_breakdown_totals, TestRawDataRecord,
+        };
+
+        // 2^SS_BITS
+        const CAP: u32 = 8;
+
+        let event = |user_id, timestamp, is_trigger_report, breakdown_key, trigger_value| {
+            TestRawDataRecord {
+                timestamp,
+                user_id,
+                is_trigger_report,
+                breakdown_key,
+                trigger_value,
+            }
+        };
+
+        run(|| async move {
+            let world = TestWorld::default();
+            let events = [
+                // the cap is reached in the middle of the second trigger
+                event(1, 1, false, 3, 0),
+                event(1, 2, true, 0, 5),
+                event(1, 3, true, 0, 4),
+                // credit left after the first source is attributed to the second one
+                event(2, 1, false, 2, 0),
+                event(2, 2, true, 0, 3),
+                event(2, 3, false, 6, 0),
+                event(2, 4, true, 0, 6),
+                // trigger values add up to more than 3 bits can hold
+                event(3, 1, false, 1, 0),
+                event(3, 2, true, 0, 7),
+                event(3, 3, true, 0, 7),
+                event(3, 4, true, 0, 7),
+                // below the cap
+                event(4, 2, false, 3, 0),
+                event(4, 6, true, 0, 3),
+            ];
+
+            let [r0, r1, r2] = build_attribution_input::<BA5, BA3, BA20, _>(
+                &events,
+                &mut crate::rand::thread_rng(),
+            );
+            let [c0, c1, c2] = world.contexts();
+            let (h1, h2, h3) = join3(
+                attribute_last_touch_capped::<BA5, BA3, BA16, BA20, 3, 32>(c0, r0),
+                attribute_last_touch_capped::<BA5, BA3, BA16, BA20, 3, 32>(c1, r1),
+                attribute_last_touch_capped::<BA5, BA3, BA16, BA20, 3, 32>(c2, r2),
+            )
+            .await;
+
+            let result = [h1.unwrap(), h2.unwrap(), h3.unwrap()].reconstruct();
+            let actual = result
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.as_u128() != 0)
+                .map(|(bk, v)| {
+                    (
+                        u64::try_from(bk).unwrap(),
+                        u64::try_from(v.as_u128()).unwrap(),
+                    )
+                })
+                .collect::<HashMap<_, _>>();
+            assert_eq!(expected_breakdown_totals(&events, CAP, None), actual);
+        });
+    }
 }
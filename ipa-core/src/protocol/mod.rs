@@ -28,35 +28,38 @@ pub type Timestamp = Gf20Bit;
 /// Unique identifier of the MPC query requested by report collectors
 /// TODO(615): Generating this unique id may be tricky as it may involve communication between helpers and
 /// them collaborating on constructing this unique id. These details haven't been flushed out yet,
-/// so for now it is just an empty struct. Once we know more about it, we will make necessary
-/// amendments to it
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(into = "&'static str", try_from = "&str")]
-pub struct QueryId;
+/// so for now every query uses [`QueryId::DEFAULT`]. Once we know more about
+/// it, we will make necessary amendments to it
+///
+/// Record streams between helpers are keyed by query id, peer and gate, so gateways of concurrent
+/// queries that share the same transports must be created with distinct ids (see
+/// [`QueryId::new`]), otherwise their channels collide on the same gate. Single-query callers keep
+/// using the default id and do not need to change anything.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "&str")]
+pub struct QueryId {
+    id: u32,
+}
 
 impl Display for QueryId {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        // fail when query id becomes meaningful and change the display implementation
-        let _: QueryId = QueryId;
-        write!(f, "{self:?}")
+        write!(f, "{}", self.id)
     }
 }
 
 impl QueryId {
-    fn repr() -> &'static str {
-        "0"
-    }
-}
+    /// The default query id, used by helpers that run one query at a time.
+    pub const DEFAULT: Self = Self::new(0);
 
-impl AsRef<str> for QueryId {
-    fn as_ref(&self) -> &str {
-        QueryId::repr()
+    #[must_use]
+    pub const fn new(id: u32) -> Self {
+        Self { id }
     }
 }
 
-impl From<QueryId> for &'static str {
-    fn from(_: QueryId) -> Self {
-        QueryId::repr()
+impl From<QueryId> for String {
+    fn from(value: QueryId) -> Self {
+        value.to_string()
     }
 }
 
@@ -64,11 +67,10 @@ impl TryFrom<&str> for QueryId {
     type Error = Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        if value == QueryId::repr() {
-            Ok(QueryId)
-        } else {
-            Err(Error::path_parse_error(value))
-        }
+        value
+            .parse()
+            .map(QueryId::new)
+            .map_err(|_| Error::path_parse_error(value))
     }
 }
 
@@ -108,6 +110,27 @@ impl From<i32> for RecordId {
 
 impl RecordId {
     pub(crate) const FIRST: Self = Self(0);
+
+    /// Returns the record id that is `count` records ahead of this one.
+    ///
+    /// ## Errors
+    /// If the result does not fit into the record id range.
+    pub fn checked_add(self, count: usize) -> Result<Self, Error> {
+        u32::try_from(count)
+            .ok()
+            .and_then(|count| self.0.checked_add(count))
+            .map(Self)
+            .ok_or(Error::RecordIdOverflow { start: self, count })
+    }
+
+    /// Returns `count` consecutive record ids, starting from `start`.
+    ///
+    /// ## Errors
+    /// If the range extends past the maximum record id.
+    pub fn range(start: Self, count: usize) -> Result<impl Iterator<Item = Self>, Error> {
+        let end = start.checked_add(count)?;
+        Ok((start.0..end.0).map(Self))
+    }
 }
 
 impl From<RecordId> for u128 {
@@ -138,13 +161,13 @@ impl Add<usize> for RecordId {
     type Output = Self;
 
     fn add(self, rhs: usize) -> Self::Output {
-        RecordId::from(usize::try_from(self.0).unwrap() + rhs)
+        self.checked_add(rhs).unwrap()
     }
 }
 
 impl AddAssign<usize> for RecordId {
     fn add_assign(&mut self, rhs: usize) {
-        self.0 += u32::try_from(rhs).unwrap();
+        *self = self.checked_add(rhs).unwrap();
     }
 }
 
@@ -162,3 +185,34 @@ pub struct NoRecord;
 impl RecordBinding for NoRecord {}
 
 impl RecordBinding for RecordId {}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use crate::{error::Error, protocol::RecordId};
+
+    #[test]
+    fn record_id_range() {
+        let range = RecordId::range(RecordId::from(5_u32), 3).unwrap();
+        assert_eq!(vec![5_u32, 6, 7], range.map(u32::from).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn record_id_overflow() {
+        let start = RecordId::from(u32::MAX - 1);
+        assert_eq!(RecordId::from(u32::MAX), start.checked_add(1).unwrap());
+        assert!(matches!(
+            start.checked_add(2),
+            Err(Error::RecordIdOverflow { count: 2, .. })
+        ));
+        assert!(matches!(
+            RecordId::range(start, 5),
+            Err(Error::RecordIdOverflow { .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "RecordIdOverflow")]
+    fn record_id_add_overflow() {
+        let _ = RecordId::from(u32::MAX) + 1;
+    }
+}
@@ -4,7 +4,7 @@ use ipa_macros::Gate;
 use serde::Deserialize;
 
 use super::StepNarrow;
-use crate::helpers::{prss_protocol::PrssExchangeStep, query::QueryType};
+use crate::helpers::{prss_protocol::PrssExchangeStep, query::QueryType, AbortStep};
 
 #[derive(Gate, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize)]
 #[serde(from = "&str")]
@@ -32,6 +32,7 @@ impl Debug for Compact {
 const ROOT_STATE: u16 = 0;
 const QUERY_TYPE_OPRF_STATE: u16 = 65533;
 const PRSS_EXCHANGE_STATE: u16 = 65532;
+const ABORT_STATE: u16 = 65531;
 
 impl StepNarrow<QueryType> for Compact {
     fn narrow(&self, step: &QueryType) -> Self {
@@ -48,12 +49,19 @@ impl StepNarrow<PrssExchangeStep> for Compact {
     }
 }
 
+impl StepNarrow<AbortStep> for Compact {
+    fn narrow(&self, _step: &AbortStep) -> Self {
+        Self(ABORT_STATE)
+    }
+}
+
 // Reverse of `static_state_map` for `Compact::as_ref()`
 fn static_reverse_state_map(state: u16) -> &'static str {
     match state {
         ROOT_STATE => "run-0",
         QUERY_TYPE_OPRF_STATE => QueryType::OPRF_IPA_STR,
         PRSS_EXCHANGE_STATE => PrssExchangeStep.as_ref(),
+        ABORT_STATE => AbortStep.as_ref(),
         _ => panic!("cannot as_ref() from the invalid state {state}"),
     }
 }
@@ -65,6 +73,8 @@ fn static_deserialize_state_map(s: &str) -> u16 {
         return QUERY_TYPE_OPRF_STATE;
     } else if s == PrssExchangeStep.as_ref() {
         return PRSS_EXCHANGE_STATE;
+    } else if s == AbortStep.as_ref() {
+        return ABORT_STATE;
     }
 
     panic!("cannot deserialize from the invalid step \"{s}\"");
@@ -0,0 +1,181 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+
+/// Number of live [`StepCoverage`] recorders. Lets `narrow` skip taking the registry lock
+/// when nobody is collecting coverage.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+static RECORDERS: Mutex<Vec<Weak<Recorder>>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Default)]
+struct Recorder {
+    hit: Mutex<HashSet<String>>,
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        ACTIVE.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Records every step path that gets narrowed while it is alive.
+///
+/// Paths are stored with module prefixes removed, i.e. in the same form as
+/// `StepMetaData::path` built by `ipa-macros` from the steps file. This makes it possible to
+/// diff what a test run executed against the full step tree, see [`report_uncovered`].
+///
+/// Recorders are process-wide: steps narrowed by any thread, including concurrently running
+/// tests, are reported into every live recorder. That is fine for coverage, which only asks
+/// whether a step was hit at all.
+#[derive(Clone, Debug)]
+pub struct StepCoverage {
+    inner: Arc<Recorder>,
+}
+
+impl StepCoverage {
+    /// Starts collecting steps. Collection stops once the last clone of the returned value
+    /// is dropped.
+    ///
+    /// ## Panics
+    /// If the recorder registry lock is poisoned.
+    #[must_use]
+    pub fn start() -> Self {
+        let inner = Arc::new(Recorder::default());
+        let mut recorders = RECORDERS.lock().unwrap();
+        recorders.retain(|r| r.strong_count() > 0);
+        recorders.push(Arc::downgrade(&inner));
+        ACTIVE.fetch_add(1, Ordering::Relaxed);
+
+        Self { inner }
+    }
+
+    /// Returns `true` if `path` was narrowed while this recorder was active. The path must be
+    /// free of module prefixes. Gates narrowed under an extra prefix (for example the
+    /// per-execution step that `TestWorld` adds) also count as covering `path`.
+    ///
+    /// ## Panics
+    /// If the recorder lock is poisoned.
+    #[must_use]
+    pub fn is_covered(&self, path: &str) -> bool {
+        let suffix = format!("/{path}");
+        self.inner
+            .hit
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|hit| hit == path || hit.ends_with(&suffix))
+    }
+
+    /// All step paths recorded so far, sorted.
+    ///
+    /// ## Panics
+    /// If the recorder lock is poisoned.
+    #[must_use]
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = self
+            .inner
+            .hit
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+}
+
+/// Called by the gate on every `narrow`.
+pub(super) fn record(gate: &str) {
+    if ACTIVE.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+
+    let path = strip_modules(gate);
+    for recorder in RECORDERS.lock().unwrap().iter().filter_map(Weak::upgrade) {
+        recorder.hit.lock().unwrap().insert(path.clone());
+    }
+}
+
+/// Removes the module prefix from every component of a step path, so
+/// `protocol/a::Step::x/b::Step::y` becomes `x/y`. The root component is dropped.
+fn strip_modules(path: &str) -> String {
+    path.split('/')
+        .skip(1)
+        .map(|step| step.rsplit_once("::").map_or(step, |(_, name)| name))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns leaf step paths from `tree` that `coverage` never saw, without module prefixes.
+///
+/// `tree` is the step listing in the steps file format, one full path per entry with module
+/// prefixes, which is what `ipa-macros` builds the `StepMetaData` tree from. A step is a leaf
+/// if no other entry is narrowed from it.
+#[must_use]
+pub fn report_uncovered<'a, I>(tree: I, coverage: &StepCoverage) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let steps = tree.into_iter().collect::<Vec<_>>();
+    let parents = steps
+        .iter()
+        .filter_map(|step| step.rsplit_once('/').map(|(parent, _)| parent))
+        .collect::<HashSet<_>>();
+
+    steps
+        .into_iter()
+        .filter(|step| !parents.contains(step))
+        .map(|step| strip_modules(&format!("root/{step}")))
+        .filter(|path| !coverage.is_covered(path))
+        .collect()
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use super::{report_uncovered, StepCoverage};
+    use crate::{
+        ff::Fp31,
+        protocol::{basics::SecureMul, context::Context, RecordId},
+        rand::{thread_rng, Rng},
+        test_fixture::{Reconstruct, Runner, TestWorld},
+    };
+
+    #[tokio::test]
+    async fn records_executed_steps() {
+        let coverage = StepCoverage::start();
+        let world = TestWorld::default();
+        let mut rng = thread_rng();
+        let (a, b) = (rng.gen::<Fp31>(), rng.gen::<Fp31>());
+
+        let result = world
+            .semi_honest((a, b), |ctx, (a, b)| async move {
+                let ctx = ctx.narrow("coverage_toy").set_total_records(1);
+                a.multiply(&b, ctx, RecordId::FIRST).await.unwrap()
+            })
+            .await
+            .reconstruct();
+        assert_eq!(a * b, result);
+
+        assert!(coverage.is_covered("coverage_toy"));
+        assert!(!coverage.is_covered("coverage_never_run"));
+        assert!(coverage
+            .paths()
+            .iter()
+            .any(|path| path.split('/').any(|step| step == "coverage_toy")));
+
+        let tree = [
+            "toy::Step::coverage_toy",
+            "toy::Step::coverage_idle",
+            "toy::Step::coverage_idle/toy::Step::coverage_never_run",
+        ];
+        assert_eq!(
+            vec!["coverage_idle/coverage_never_run".to_string()],
+            report_uncovered(tree, &coverage)
+        );
+    }
+}
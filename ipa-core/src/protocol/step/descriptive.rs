@@ -1,4 +1,7 @@
-use std::fmt::{Debug, Display, Formatter};
+use std::{
+    fmt::{Debug, Display, Formatter},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use serde::Deserialize;
 
@@ -36,17 +39,46 @@ impl Display for Descriptive {
     }
 }
 
+/// Default for the maximum number of times a gate can be narrowed from the root, see
+/// [`set_max_narrow_depth`]. The deepest path in the IPA step tree is much shallower than that.
+pub const DEFAULT_MAX_NARROW_DEPTH: usize = 64;
+
+static MAX_NARROW_DEPTH: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_NARROW_DEPTH);
+
+/// Sets the maximum number of times a gate can be narrowed from the root, for every gate in this
+/// process. Exceeding it means that a protocol narrows in an unbounded recursion, so narrowing
+/// fails at that point and names the offending path, instead of letting the step tree grow
+/// until the process runs out of memory. Protocols that nest deeper than
+/// [`DEFAULT_MAX_NARROW_DEPTH`] on purpose can raise it.
+pub fn set_max_narrow_depth(depth: usize) {
+    MAX_NARROW_DEPTH.store(depth, Ordering::Relaxed);
+}
+
+/// Returns the limit set by [`set_max_narrow_depth`].
+#[must_use]
+pub fn max_narrow_depth() -> usize {
+    MAX_NARROW_DEPTH.load(Ordering::Relaxed)
+}
+
 impl<S: Step + ?Sized> StepNarrow<S> for Descriptive {
     /// Narrow the scope of the step identifier.
     /// # Panics
-    /// In a debug build, this checks that the same refine call isn't run twice and that the string
-    /// value of the step doesn't include '/' (which would lead to a bad outcome).
+    /// If the resulting gate is nested deeper than [`max_narrow_depth`]. In a debug build, this
+    /// also checks that the same refine call isn't run twice and that the string value of the
+    /// step doesn't include '/' (which would lead to a bad outcome).
     fn narrow(&self, step: &S) -> Self {
         #[cfg(debug_assertions)]
         {
             let s = String::from(step.as_ref());
             assert!(!s.contains('/'), "The string for a step cannot contain '/'");
         }
+        let max_depth = max_narrow_depth();
+        assert!(
+            self.id.matches('/').count() < max_depth,
+            "Narrowing {} by {} exceeds the maximum step depth of {max_depth}",
+            self.id,
+            step.as_ref(),
+        );
 
         let mut id = self.id.clone() + "/";
         #[cfg(all(feature = "step-trace", feature = "in-memory-infra"))]
@@ -58,6 +90,10 @@ impl<S: Step + ?Sized> StepNarrow<S> for Descriptive {
         {
             metrics::increment_counter!(STEP_NARROWED, STEP => id.clone());
         }
+        #[cfg(any(test, feature = "test-fixture"))]
+        {
+            super::coverage::record(&id);
+        }
 
         Self { id }
     }
@@ -91,3 +127,41 @@ impl Debug for Descriptive {
         write!(f, "step={}", self.id)
     }
 }
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use std::panic::catch_unwind;
+
+    use super::{max_narrow_depth, set_max_narrow_depth, Descriptive, DEFAULT_MAX_NARROW_DEPTH};
+    use crate::protocol::step::StepNarrow;
+
+    fn narrow_times(depth: usize) -> Descriptive {
+        (0..depth).fold(Descriptive::default(), |gate, _| gate.narrow("recurse"))
+    }
+
+    fn narrow_fails(depth: usize) -> String {
+        let err = catch_unwind(|| narrow_times(depth)).unwrap_err();
+        err.downcast_ref::<String>().unwrap().clone()
+    }
+
+    /// The limit is shared by all gates in the process, so this is the only test that changes
+    /// it. It is only ever raised above the default, which does not affect other tests.
+    #[test]
+    fn max_depth() {
+        assert_eq!(DEFAULT_MAX_NARROW_DEPTH, max_narrow_depth());
+        let gate = narrow_times(DEFAULT_MAX_NARROW_DEPTH);
+        assert_eq!(DEFAULT_MAX_NARROW_DEPTH, gate.as_ref().matches('/').count());
+        assert!(narrow_fails(DEFAULT_MAX_NARROW_DEPTH + 1)
+            .ends_with("/recurse by recurse exceeds the maximum step depth of 64"));
+
+        set_max_narrow_depth(2 * DEFAULT_MAX_NARROW_DEPTH);
+        let gate = narrow_times(2 * DEFAULT_MAX_NARROW_DEPTH);
+        assert_eq!(
+            2 * DEFAULT_MAX_NARROW_DEPTH,
+            gate.as_ref().matches('/').count()
+        );
+        assert!(narrow_fails(2 * DEFAULT_MAX_NARROW_DEPTH + 1)
+            .ends_with("exceeds the maximum step depth of 128"));
+        set_max_narrow_depth(DEFAULT_MAX_NARROW_DEPTH);
+    }
+}
@@ -1,12 +1,18 @@
 #[cfg(feature = "compact-gate")]
 mod compact;
+#[cfg(all(feature = "descriptive-gate", any(test, feature = "test-fixture")))]
+mod coverage;
 #[cfg(feature = "descriptive-gate")]
 mod descriptive;
 
 #[cfg(feature = "compact-gate")]
 pub use compact::Compact;
+#[cfg(all(feature = "descriptive-gate", any(test, feature = "test-fixture")))]
+pub use coverage::{report_uncovered, StepCoverage};
 #[cfg(feature = "descriptive-gate")]
-pub use descriptive::Descriptive;
+pub use descriptive::{
+    max_narrow_depth, set_max_narrow_depth, Descriptive, DEFAULT_MAX_NARROW_DEPTH,
+};
 use ipa_macros::Step;
 
 #[cfg(feature = "descriptive-gate")]
@@ -129,6 +135,31 @@ impl From<usize> for TwoHundredFiftySixBitOpStep {
     }
 }
 
+/// Distinguishes sub-protocols that run concurrently under the same parent context.
+/// See [`Context::fork`](crate::protocol::context::Context::fork).
+///
+/// Forks can happen under any gate, so they can't be listed in `steps.txt`. This step only
+/// exists with descriptive gates, and code that forks does not compile with compact gates.
+#[cfg(feature = "descriptive-gate")]
+#[derive(Step)]
+pub enum ForkStep {
+    #[dynamic(64)]
+    Branch(usize),
+}
+
+#[cfg(feature = "descriptive-gate")]
+impl ForkStep {
+    /// Number of branches a context can be forked into.
+    pub const MAX_BRANCHES: usize = 64;
+}
+
+#[cfg(feature = "descriptive-gate")]
+impl From<usize> for ForkStep {
+    fn from(v: usize) -> Self {
+        Self::Branch(v)
+    }
+}
+
 #[cfg(test)]
 #[derive(Step)]
 pub enum DefaultBitStep {
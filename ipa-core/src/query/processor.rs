@@ -136,7 +136,7 @@ impl Processor {
         transport: MpcTransportImpl,
         req: QueryConfig,
     ) -> Result<PrepareQuery, NewQueryError> {
-        let query_id = QueryId;
+        let query_id = QueryId::DEFAULT;
         let handle = self.queries.handle(query_id);
         handle.set_state(QueryState::Preparing(req))?;
         let guard = handle.remove_query_on_drop();
@@ -388,7 +388,10 @@ mod tests {
         // poll future once to trigger query status change
         let _qc = poll_immediate(&mut qc_future).await;
 
-        assert_eq!(QueryStatus::Preparing, p0.query_status(QueryId).unwrap());
+        assert_eq!(
+            QueryStatus::Preparing,
+            p0.query_status(QueryId::DEFAULT).unwrap()
+        );
         // unblock sends
         barrier.wait().await;
 
@@ -397,7 +400,7 @@ mod tests {
 
         assert_eq!(
             PrepareQuery {
-                query_id: QueryId,
+                query_id: QueryId::DEFAULT,
                 config: request,
                 roles: expected_assignment,
             },
@@ -405,7 +408,7 @@ mod tests {
         );
         assert_eq!(
             QueryStatus::AwaitingInputs,
-            p0.query_status(QueryId).unwrap()
+            p0.query_status(QueryId::DEFAULT).unwrap()
         );
     }
 
@@ -478,7 +481,7 @@ mod tests {
 
         fn prepare_query(identities: [HelperIdentity; 3]) -> PrepareQuery {
             PrepareQuery {
-                query_id: QueryId,
+                query_id: QueryId::DEFAULT,
                 config: test_multiply_config(),
                 roles: RoleAssignment::new(identities),
             }
@@ -493,13 +496,13 @@ mod tests {
             let processor = Processor::default();
 
             assert!(matches!(
-                processor.query_status(QueryId).unwrap_err(),
+                processor.query_status(QueryId::DEFAULT).unwrap_err(),
                 QueryStatusError::NoSuchQuery(_)
             ));
             processor.prepare(&transport, req).unwrap();
             assert_eq!(
                 QueryStatus::AwaitingInputs,
-                processor.query_status(QueryId).unwrap()
+                processor.query_status(QueryId::DEFAULT).unwrap()
             );
         }
 
@@ -5,7 +5,7 @@ use std::{
 };
 
 use crate::{
-    error::Error,
+    error::{Error, LengthError},
     ff::{boolean::Boolean, ArrayAccessRef, PrimeField},
     protocol::prss::{FromPrss, FromRandom, PrssIndex, SharedRandomness},
     secret_sharing::{
@@ -31,6 +31,27 @@ impl<S> BitDecomposed<S> {
         Self { bits }
     }
 
+    /// Create a new value from an iterator that must produce exactly `len` items, e.g. the bits
+    /// of a breakdown key of a known width. Use this for bits that come from untrusted sources,
+    /// so a value of the wrong width is rejected here rather than much later.
+    ///
+    /// # Errors
+    /// [`Error::LengthError`] if the iterator does not produce `len` items.
+    ///
+    /// # Panics
+    /// If `len` is greater than `Self::MAX`.
+    pub fn try_new<I: IntoIterator<Item = S>>(bits: I, len: usize) -> Result<Self, Error> {
+        let bits = bits.into_iter().collect::<Vec<_>>();
+        if bits.len() != len {
+            return Err(LengthError {
+                expected: len,
+                actual: bits.len(),
+            }
+            .into());
+        }
+        Ok(Self::new(bits))
+    }
+
     /// Decompose `count` values from context, using a counter from `[0, count)`.
     /// # Panics
     /// If `count` is greater than `Self::MAX`.
@@ -109,6 +130,27 @@ impl<S> BitDecomposed<S> {
         }
         (BitDecomposed::new(left), BitDecomposed::new(right))
     }
+
+    /// Appends the bits of `other` after the bits of `self`, so the least significant bits of
+    /// the result come from `self`. This is the inverse of [`Self::split_at`].
+    ///
+    /// Only the overall length is checked here. Protocols that consume the result, e.g.
+    /// `bool_and_8_bit`, still enforce their own width limits.
+    ///
+    /// # Panics
+    /// If the combined length exceeds `Self::MAX`.
+    #[must_use]
+    pub fn concat(mut self, other: Self) -> Self {
+        assert!(
+            self.len() + other.len() <= Self::MAX,
+            "Cannot concatenate {} and {} bits, at most {} are supported",
+            self.len(),
+            other.len(),
+            Self::MAX
+        );
+        self.bits.extend(other.bits);
+        self
+    }
 }
 
 impl BitDecomposed<Boolean> {
@@ -255,6 +297,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn concat() {
+        let low = BitDecomposed::new([0_u8, 1, 2]);
+        let high = BitDecomposed::new([3_u8, 4, 5, 6]);
+        let combined = low.clone().concat(high.clone());
+        assert_eq!(7, combined.len());
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], combined.bits);
+        assert_eq!((low, high), combined.split_at(3));
+    }
+
+    #[test]
+    fn try_new() {
+        assert_eq!(
+            BitDecomposed::new([1_u8, 0, 1]),
+            BitDecomposed::try_new([1_u8, 0, 1], 3).unwrap()
+        );
+        assert!(matches!(
+            BitDecomposed::try_new([1_u8, 0], 3),
+            Err(Error::LengthError(LengthError {
+                expected: 3,
+                actual: 2
+            }))
+        ));
+    }
+
     proptest! {
         #[test]
         fn arrayaccess_get_set(
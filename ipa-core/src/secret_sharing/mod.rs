@@ -130,18 +130,28 @@ where
     Standard: Distribution<V>,
 {
     fn share_with<R: Rng>(self, rng: &mut R) -> [AdditiveShare<V>; 3] {
-        let x1 = rng.gen::<V>();
-        let x2 = rng.gen::<V>();
-        let x3 = self - (x1 + x2);
-
-        [
-            AdditiveShare::new(x1, x2),
-            AdditiveShare::new(x2, x3),
-            AdditiveShare::new(x3, x1),
-        ]
+        share_from_parts(self, rng.gen(), rng.gen())
     }
 }
 
+/// Secret-shares `input` using the provided masks instead of sampling them. `x1` and `x2` become
+/// the first two additive shares, and the third one is computed so that all three add up to
+/// `input`.
+///
+/// This lets property tests generate the masks as part of their input, so they are shrunk along
+/// with the value being shared.
+#[cfg(any(test, feature = "test-fixture", feature = "cli"))]
+#[must_use]
+pub fn share_from_parts<V: SharedValue>(input: V, x1: V, x2: V) -> [AdditiveShare<V>; 3] {
+    let x3 = input - (x1 + x2);
+
+    [
+        AdditiveShare::new(x1, x2),
+        AdditiveShare::new(x2, x3),
+        AdditiveShare::new(x3, x1),
+    ]
+}
+
 #[cfg(any(test, feature = "test-fixture", feature = "cli"))]
 impl<V, const N: usize> IntoShares<AdditiveShare<V, N>> for [V; N]
 where
@@ -167,12 +177,15 @@ where
 
 #[cfg(all(test, unit_test))]
 mod tests {
+    use proptest::proptest;
+
     use crate::{
-        ff::{Field, Fp31},
+        ff::{Field, Fp31, U128Conversions},
         secret_sharing::{
-            replicated::{malicious, semi_honest},
-            Linear, LinearRefOps,
+            replicated::{malicious, semi_honest, ReplicatedSecretSharing},
+            share_from_parts, Linear, LinearRefOps,
         },
+        test_fixture::Reconstruct,
     };
 
     fn arithmetic<L: Linear<F> + PartialEq, F: Field>()
@@ -235,4 +248,21 @@ mod tests {
         arithmetic::<malicious::AdditiveShare<Fp31>, _>();
         trait_bounds::<malicious::AdditiveShare<Fp31>, _>();
     }
+
+    #[test]
+    fn share_from_parts_reconstructs() {
+        let [input, x1, x2] = [17_u8, 4, 29].map(Fp31::truncate_from);
+        let shares = share_from_parts(input, x1, x2);
+        assert_eq!(input, shares.reconstruct());
+        assert_eq!(semi_honest::AdditiveShare::new(x1, x2), shares[0]);
+    }
+
+    proptest! {
+        #[test]
+        fn share_from_parts_proptest(input: u8, x1: u8, x2: u8) {
+            let input = Fp31::truncate_from(input);
+            let shares = share_from_parts(input, Fp31::truncate_from(x1), Fp31::truncate_from(x2));
+            assert_eq!(input, shares.reconstruct());
+        }
+    }
 }
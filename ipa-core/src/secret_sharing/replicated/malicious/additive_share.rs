@@ -13,11 +13,15 @@ use generic_array::{ArrayLength, GenericArray};
 use typenum::Unsigned;
 
 use crate::{
-    ff::{Field, Gf2, Gf32Bit, PrimeField, Serializable, U128Conversions},
+    error::LengthError,
+    ff::{Field, Gf2, Gf32Bit, PrimeField, Serializable, SerializableExt, U128Conversions},
+    helpers::Role,
     protocol::prss::FromRandom,
     secret_sharing::{
-        replicated::semi_honest::AdditiveShare as SemiHonestAdditiveShare, BitDecomposed,
-        Linear as LinearSecretSharing, SecretSharing, SharedValue,
+        replicated::{
+            semi_honest::AdditiveShare as SemiHonestAdditiveShare, ReplicatedSecretSharing,
+        },
+        BitDecomposed, Linear as LinearSecretSharing, SecretSharing, SharedValue,
     },
     seq_join::seq_join,
 };
@@ -134,6 +138,52 @@ impl<V: SharedValue + ExtendableField> AdditiveShare<V> {
         &self.rx
     }
 
+    /// Computes `c - self` for a public constant `c`, without sharing `c` first. `r_share` is
+    /// this helper's share of the MAC key, it is needed to produce the `r * c` part of the MAC.
+    #[must_use]
+    pub fn public_minus(
+        &self,
+        role: Role,
+        c: V,
+        r_share: &SemiHonestAdditiveShare<V::ExtendedField>,
+    ) -> Self {
+        let (left, right) = (V::ZERO - self.x.left(), V::ZERO - self.x.right());
+        let x = match role {
+            Role::H1 => SemiHonestAdditiveShare::new(left + c, right),
+            Role::H2 => SemiHonestAdditiveShare::new(left, right),
+            Role::H3 => SemiHonestAdditiveShare::new(left, right + c),
+        };
+
+        Self {
+            x,
+            rx: r_share * c.to_extended() - &self.rx,
+        }
+    }
+
+    /// Computes `1 - self`, see [`Self::public_minus`].
+    #[must_use]
+    pub fn one_minus(
+        &self,
+        role: Role,
+        r_share: &SemiHonestAdditiveShare<V::ExtendedField>,
+    ) -> Self {
+        self.public_minus(role, V::ONE, r_share)
+    }
+
+    /// Blinds this share by adding `mask_x` to the value and `mask_rx` to the MAC.
+    ///
+    /// The MAC stays valid only if `mask_rx` is a sharing of `r * mask_x`. This is not checked:
+    /// it is up to the caller to supply consistent masks, e.g. both halves of a malicious
+    /// share of a random value. An inconsistent mask makes the next validation fail.
+    pub fn add_mask(
+        &mut self,
+        mask_x: &SemiHonestAdditiveShare<V>,
+        mask_rx: &SemiHonestAdditiveShare<V::ExtendedField>,
+    ) {
+        self.x += mask_x;
+        self.rx += mask_rx;
+    }
+
     pub const ZERO: Self = Self {
         x: SemiHonestAdditiveShare::ZERO,
         rx: SemiHonestAdditiveShare::ZERO,
@@ -292,6 +342,16 @@ pub enum ExtendableFieldDeserializationError<F: Serializable, EF: Serializable>
     ExtendedFieldError(EF::DeserializationError),
 }
 
+/// Hashes both `x` and `rx` shares. Just like its semi-honest counterpart, it must only be used
+/// in tests and tooling.
+#[cfg(any(test, feature = "test-fixture"))]
+impl<V: SharedValue + ExtendableField> std::hash::Hash for AdditiveShare<V> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::hash::Hash::hash(&self.x, state);
+        std::hash::Hash::hash(&self.rx, state);
+    }
+}
+
 /// todo serde macro for these collections so we can hide the crazy size calculations
 impl<V: SharedValue + ExtendableField> Serializable for AdditiveShare<V>
 where
@@ -334,6 +394,39 @@ where
     }
 }
 
+impl<V: SharedValue + ExtendableField> AdditiveShare<V>
+where
+    Self: Serializable,
+{
+    /// Deserialize a slice of bytes into an iterator of maliciously secure replicated shares.
+    pub fn from_byte_slice(
+        from: &[u8],
+    ) -> impl Iterator<Item = Result<Self, <Self as Serializable>::DeserializationError>> + '_ {
+        debug_assert!(from.len() % <Self as Serializable>::Size::USIZE == 0);
+
+        from.chunks(<Self as Serializable>::Size::USIZE)
+            .map(|chunk| Serializable::deserialize(GenericArray::from_slice(chunk)))
+    }
+
+    /// Same as [`from_byte_slice`], but checks that `from` holds a whole number of shares
+    /// before deserializing anything.
+    ///
+    /// [`from_byte_slice`]: Self::from_byte_slice
+    ///
+    /// ## Errors
+    /// If the length of `from` is not a multiple of the serialized share size. The error reports
+    /// lengths in bytes, expecting `from` to be padded to the next whole share.
+    pub fn try_from_byte_slice(
+        from: &[u8],
+    ) -> Result<
+        impl Iterator<Item = Result<Self, <Self as Serializable>::DeserializationError>> + '_,
+        LengthError,
+    > {
+        Self::row_count(from.len())?;
+        Ok(Self::from_byte_slice(from))
+    }
+}
+
 #[async_trait]
 impl<F: ExtendableField> Downgrade for AdditiveShare<F> {
     type Target = SemiHonestAdditiveShare<F>;
@@ -409,9 +502,14 @@ impl<T> ThisCodeIsAuthorizedToDowngradeFromMalicious<T> for UnauthorizedDowngrad
 
 #[cfg(all(test, unit_test))]
 mod tests {
+    use std::collections::HashSet;
+
+    use generic_array::GenericArray;
+
     use super::{AdditiveShare, Downgrade, ThisCodeIsAuthorizedToDowngradeFromMalicious};
     use crate::{
-        ff::{Field, Fp31, U128Conversions},
+        error::LengthError,
+        ff::{Field, Fp31, Serializable, U128Conversions},
         helpers::Role,
         rand::{thread_rng, Rng},
         secret_sharing::{
@@ -501,6 +599,79 @@ mod tests {
         );
     }
 
+    #[test]
+    fn one_minus() {
+        let mut rng = thread_rng();
+        let x = rng.gen::<Fp31>();
+        let c = rng.gen::<Fp31>();
+        let r = rng.gen::<Fp31>();
+
+        let x_shared = x.share_with(&mut rng);
+        let rx_shared = (r * x).share_with(&mut rng);
+        let r_shared = r.share_with(&mut rng);
+
+        let malicious_x = [Role::H1, Role::H2, Role::H3]
+            .map(|i| AdditiveShare::new(x_shared[i].clone(), rx_shared[i].clone()));
+
+        let check = |results: [AdditiveShare<Fp31>; 3], expected: Fp31| {
+            assert_eq!(
+                expected,
+                results
+                    .each_ref()
+                    .map(|m| m.x().access_without_downgrade().clone())
+                    .reconstruct()
+            );
+            assert_eq!(
+                expected * r,
+                results.each_ref().map(AdditiveShare::rx).reconstruct()
+            );
+        };
+
+        check(
+            [Role::H1, Role::H2, Role::H3].map(|i| malicious_x[i].one_minus(i, &r_shared[i])),
+            Fp31::ONE - x,
+        );
+        check(
+            [Role::H1, Role::H2, Role::H3].map(|i| malicious_x[i].public_minus(i, c, &r_shared[i])),
+            c - x,
+        );
+    }
+
+    #[test]
+    fn add_mask() {
+        let mut rng = thread_rng();
+        let (x, mask, r) = (rng.gen::<Fp31>(), rng.gen::<Fp31>(), rng.gen::<Fp31>());
+
+        let x_shared = x.share_with(&mut rng);
+        let rx_shared = (r * x).share_with(&mut rng);
+        let mask_shared = mask.share_with(&mut rng);
+        let r_mask_shared = (r * mask).share_with(&mut rng);
+
+        let malicious_x = [Role::H1, Role::H2, Role::H3]
+            .map(|i| AdditiveShare::new(x_shared[i].clone(), rx_shared[i].clone()));
+        let masked = [Role::H1, Role::H2, Role::H3].map(|i| {
+            let mut m = malicious_x[i].clone();
+            m.add_mask(&mask_shared[i], &r_mask_shared[i]);
+            m
+        });
+        let x_of = |shares: &[AdditiveShare<Fp31>; 3]| {
+            shares
+                .each_ref()
+                .map(|m| m.x().access_without_downgrade().clone())
+                .reconstruct()
+        };
+        assert_eq!(x + mask, x_of(&masked));
+        assert_eq!(
+            r * (x + mask),
+            masked.each_ref().map(AdditiveShare::rx).reconstruct()
+        );
+
+        let unmasked = [Role::H1, Role::H2, Role::H3].map(|i| {
+            masked[i].clone() - AdditiveShare::new(mask_shared[i].clone(), r_mask_shared[i].clone())
+        });
+        assert_eq!(malicious_x, unmasked);
+    }
+
     #[tokio::test]
     async fn downgrade() {
         let mut rng = thread_rng();
@@ -509,4 +680,63 @@ mod tests {
         let m = AdditiveShare::new(x.clone(), y);
         assert_eq!(x, Downgrade::downgrade(m).await.access_without_downgrade());
     }
+
+    #[test]
+    fn hash() {
+        let x = SemiHonestAdditiveShare::new(Fp31::truncate_from(1_u8), Fp31::truncate_from(2_u8));
+        let rx = SemiHonestAdditiveShare::new(Fp31::truncate_from(3_u8), Fp31::truncate_from(4_u8));
+        let other_rx =
+            SemiHonestAdditiveShare::new(Fp31::truncate_from(3_u8), Fp31::truncate_from(5_u8));
+
+        let mut semi_honest = HashSet::new();
+        assert!(semi_honest.insert(x.clone()));
+        assert!(!semi_honest.insert(x.clone()));
+        assert!(semi_honest.insert(rx.clone()));
+        assert_eq!(2, semi_honest.len());
+
+        let mut malicious = HashSet::new();
+        assert!(malicious.insert(AdditiveShare::new(x.clone(), rx.clone())));
+        assert!(!malicious.insert(AdditiveShare::new(x.clone(), rx)));
+        assert!(malicious.insert(AdditiveShare::new(x, other_rx)));
+        assert_eq!(2, malicious.len());
+    }
+
+    #[test]
+    fn from_byte_slice() {
+        let mut rng = thread_rng();
+        let shares = (0..5)
+            .map(|_| {
+                AdditiveShare::new(
+                    SemiHonestAdditiveShare::new(rng.gen::<Fp31>(), rng.gen()),
+                    SemiHonestAdditiveShare::new(rng.gen::<Fp31>(), rng.gen()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut bytes = Vec::new();
+        for share in &shares {
+            let mut buf = GenericArray::default();
+            share.serialize(&mut buf);
+            bytes.extend_from_slice(&buf);
+        }
+
+        let decoded = AdditiveShare::<Fp31>::from_byte_slice(&bytes)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(shares, decoded);
+
+        let decoded = AdditiveShare::<Fp31>::try_from_byte_slice(&bytes)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(shares, decoded);
+
+        let truncated = &bytes[..bytes.len() - 1];
+        let Err(LengthError { expected, actual }) =
+            AdditiveShare::<Fp31>::try_from_byte_slice(truncated)
+        else {
+            panic!("truncated input must be rejected");
+        };
+        assert_eq!((bytes.len(), truncated.len()), (expected, actual));
+    }
 }
@@ -8,6 +8,7 @@ use generic_array::{ArrayLength, GenericArray};
 use typenum::Unsigned;
 
 use crate::{
+    error::LengthError,
     ff::{ArrayAccess, ArrayAccessRef, Expand, Field, Serializable},
     secret_sharing::{
         replicated::ReplicatedSecretSharing, FieldSimd, Linear as LinearSecretSharing,
@@ -73,6 +74,32 @@ impl<V: SharedValue> AdditiveShare<V> {
     }
 }
 
+impl<F: Field> AdditiveShare<F> {
+    /// Increments this counter by a shared bit, i.e. a share of either 0 or 1. This is just local
+    /// addition of shares and needs no communication. It does not multiply anything by the bit,
+    /// so the result is only meaningful if `bit` is known to hold 0 or 1.
+    pub fn add_bit(&mut self, bit: &Self) {
+        *self += bit;
+    }
+
+    /// Same as [`Self::add_bit`], but increments every counter by the bit at the same position.
+    ///
+    /// ## Panics
+    /// If `counters` and `bits` have different lengths.
+    pub fn add_bits(counters: &mut [Self], bits: &[Self]) {
+        assert_eq!(
+            counters.len(),
+            bits.len(),
+            "Expected one bit per counter, got {} bits for {} counters",
+            bits.len(),
+            counters.len()
+        );
+        for (counter, bit) in counters.iter_mut().zip(bits) {
+            counter.add_bit(bit);
+        }
+    }
+}
+
 impl<V> ReplicatedSecretSharing<V> for AdditiveShare<V>
 where
     V: SharedValue + Vectorizable<1>,
@@ -356,6 +383,22 @@ where
     }
 }
 
+/// Hashes both left and right shares.
+///
+/// Secret shares are not meant to be hashed inside MPC protocols, as it is easy to leak information
+/// about them this way. This implementation exists for tests and tooling only, for example to
+/// deduplicate shares or use them as keys in a map.
+#[cfg(any(test, feature = "test-fixture"))]
+impl<V: SharedValue> std::hash::Hash for AdditiveShare<V> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let mut buf = GenericArray::default();
+        self.left().serialize(&mut buf);
+        state.write(&buf);
+        self.right().serialize(&mut buf);
+        state.write(&buf);
+    }
+}
+
 /// Implement `ArrayAccess` for `AdditiveShare` over `SharedValue` that implements `ArrayAccess`
 // You can think of S as a Boolean array type and V as Boolean.
 impl<S, V, A> ArrayAccess for AdditiveShare<S>
@@ -475,6 +518,35 @@ where
     }
 }
 
+impl<S> AdditiveShare<S>
+where
+    S: SharedValue + ArrayAccess,
+    <S as ArrayAccess>::Output: SharedValue,
+{
+    /// Reassembles a share of `S` from the shares of its individual bits, least significant bit
+    /// first. This is the inverse of iterating over the share with [`ArrayAccess::iter`]. Unlike
+    /// collecting into a share, it rejects input that does not have exactly `S::BITS` items.
+    ///
+    /// ## Errors
+    /// If `bits` does not contain exactly `S::BITS` shares.
+    ///
+    /// ## Panics
+    /// If `S::BITS` does not fit into `usize`.
+    pub fn try_from_bits(
+        bits: Vec<AdditiveShare<<S as ArrayAccess>::Output>>,
+    ) -> Result<Self, LengthError> {
+        let expected = usize::try_from(S::BITS).unwrap();
+        if bits.len() == expected {
+            Ok(bits.into_iter().collect())
+        } else {
+            Err(LengthError {
+                expected,
+                actual: bits.len(),
+            })
+        }
+    }
+}
+
 pub struct UnpackIter<S: SharedValue + Vectorizable<N>, const N: usize>(
     <<S as Vectorizable<N>>::Array as IntoIterator>::IntoIter,
     <<S as Vectorizable<N>>::Array as IntoIterator>::IntoIter,
@@ -500,7 +572,11 @@ mod tests {
     };
 
     use crate::{
-        ff::{Fp31, Fp32BitPrime, U128Conversions},
+        error::LengthError,
+        ff::{
+            boolean::Boolean, boolean_array::BA8, ArrayAccess, Fp31, Fp32BitPrime, U128Conversions,
+        },
+        rand::{thread_rng, Rng},
         secret_sharing::{
             replicated::{semi_honest::AdditiveShare, ReplicatedSecretSharing},
             SharedValue, StdArray, Vectorizable,
@@ -562,6 +638,31 @@ mod tests {
         assert_secret_shared_value(&res1, &res2, &res3, expected_output);
     }
 
+    #[test]
+    fn add_bit() {
+        let (mut c1, mut c2, mut c3) = secret_share(5, 7, 2);
+        for bit in [(0, 1, 0), (0, 0, 0)] {
+            let (b1, b2, b3) = secret_share(bit.0, bit.1, bit.2);
+            c1.add_bit(&b1);
+            c2.add_bit(&b2);
+            c3.add_bit(&b3);
+        }
+        assert_valid_secret_sharing(&c1, &c2, &c3);
+        assert_secret_shared_value(&c1, &c2, &c3, 15);
+
+        // every helper increments its shares of both counters at once
+        let (d1, d2, d3) = secret_share(3, 0, 6);
+        let (x1, x2, x3) = secret_share(1, 0, 0);
+        let (y1, y2, y3) = secret_share(0, 0, 0);
+        let mut counters = [vec![c1, d1], vec![c2, d2], vec![c3, d3]];
+        for (counters, bits) in counters.iter_mut().zip([[x1, y1], [x2, y2], [x3, y3]]) {
+            AdditiveShare::add_bits(counters, &bits);
+        }
+        let [h1, h2, h3] = counters;
+        assert_secret_shared_value(&h1[0], &h2[0], &h3[0], 16);
+        assert_secret_shared_value(&h1[1], &h2[1], &h3[1], 9);
+    }
+
     #[test]
     fn test_simple_addition() {
         addition_test_case((1, 0, 0), (1, 0, 0), 2);
@@ -629,6 +730,27 @@ mod tests {
         subtraction_test_case((1, 3, 5), (10, 0, 2), 28);
     }
 
+    #[test]
+    fn bits_round_trip() {
+        let mut rng = thread_rng();
+        let share = AdditiveShare::<BA8>::new(rng.gen(), rng.gen());
+        let bits = ArrayAccess::iter(&share).collect::<Vec<AdditiveShare<Boolean>>>();
+        assert_eq!(8, bits.len());
+        assert_eq!(
+            share,
+            AdditiveShare::<BA8>::try_from_bits(bits.clone()).unwrap()
+        );
+
+        let short = bits[..7].to_vec();
+        assert!(matches!(
+            AdditiveShare::<BA8>::try_from_bits(short),
+            Err(LengthError {
+                expected: 8,
+                actual: 7
+            })
+        ));
+    }
+
     fn mult_by_constant_test_case(a: (u8, u8, u8), c: u8, expected_output: u128) {
         let (a1, a2, a3) = secret_share(a.0, a.1, a.2);
 
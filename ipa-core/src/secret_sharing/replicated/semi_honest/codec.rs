@@ -0,0 +1,137 @@
+use generic_array::GenericArray;
+use typenum::Unsigned;
+
+use crate::{
+    error::Error,
+    ff::{Field, Serializable, SerializableExt, U128Conversions},
+    secret_sharing::replicated::{semi_honest::AdditiveShare, ReplicatedSecretSharing},
+};
+
+/// Encoding of replicated shares that leave the helper, for example when they are written to a
+/// file or handed over to tooling. Shares exchanged between helpers always use their
+/// [`Serializable`] form and do not go through this.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Codec {
+    /// A JSON array holding a `[left, right]` pair of decimal numbers per share. Easy to inspect,
+    /// but a small field like `Fp31` takes several times more space than it needs.
+    #[default]
+    Json,
+    /// Shares written back to back in their [`Serializable`] form, which is the minimal number of
+    /// bytes for the field. An `Fp31` share takes two bytes.
+    Compact,
+}
+
+impl Codec {
+    /// Encodes `shares` into a buffer.
+    ///
+    /// ## Panics
+    /// If JSON serialization fails, which it does not for arrays of integers.
+    #[must_use]
+    pub fn encode<V>(self, shares: &[AdditiveShare<V>]) -> Vec<u8>
+    where
+        V: Field + U128Conversions,
+        AdditiveShare<V>: Serializable,
+    {
+        match self {
+            Self::Json => serde_json::to_vec(
+                &shares
+                    .iter()
+                    .map(|s| [s.left().as_u128(), s.right().as_u128()])
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap(),
+            Self::Compact => AdditiveShare::<V>::serialize_all(shares),
+        }
+    }
+
+    /// Reads shares written by [`Self::encode`] with the same codec.
+    ///
+    /// ## Errors
+    /// If `buf` is not valid JSON, does not hold a whole number of shares, or holds values that
+    /// are out of range for `V`.
+    pub fn decode<V>(self, buf: &[u8]) -> Result<Vec<AdditiveShare<V>>, Error>
+    where
+        V: Field + U128Conversions,
+        AdditiveShare<V>: Serializable,
+    {
+        match self {
+            Self::Json => serde_json::from_slice::<Vec<[u128; 2]>>(buf)?
+                .into_iter()
+                .map(|pair| {
+                    let [left, right] = pair.map(|v| match V::try_from(v) {
+                        // prime fields reduce values that fit into their storage
+                        Ok(value) if value.as_u128() == v => Ok(value),
+                        _ => Err(Error::ParseError(
+                            format!("{v} is out of range for the field").into(),
+                        )),
+                    });
+                    Ok(AdditiveShare::new(left?, right?))
+                })
+                .collect(),
+            Self::Compact => {
+                let size = <AdditiveShare<V> as Serializable>::Size::USIZE;
+                let mut r = Vec::with_capacity(AdditiveShare::<V>::row_count(buf.len())?);
+                for chunk in buf.chunks_exact(size) {
+                    r.push(
+                        AdditiveShare::<V>::deserialize(GenericArray::from_slice(chunk))
+                            .map_err(|e| Error::ParseError(e.into()))?,
+                    );
+                }
+
+                Ok(r)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use super::Codec;
+    use crate::{
+        error::Error,
+        ff::Fp31,
+        rand::{thread_rng, Rng},
+        secret_sharing::replicated::{semi_honest::AdditiveShare, ReplicatedSecretSharing},
+    };
+
+    #[test]
+    fn fp31_compact_is_smaller() {
+        let mut rng = thread_rng();
+        let shares = (0..100)
+            .map(|_| AdditiveShare::<Fp31>::new(rng.gen(), rng.gen()))
+            .collect::<Vec<_>>();
+
+        let json = Codec::Json.encode(&shares);
+        let compact = Codec::Compact.encode(&shares);
+        assert_eq!(2 * shares.len(), compact.len());
+        assert!(
+            2 * compact.len() < json.len(),
+            "{} vs {}",
+            compact.len(),
+            json.len()
+        );
+
+        assert_eq!(shares, Codec::Json.decode::<Fp31>(&json).unwrap());
+        assert_eq!(shares, Codec::Compact.decode::<Fp31>(&compact).unwrap());
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(matches!(
+            Codec::Compact.decode::<Fp31>(&[1, 2, 3]),
+            Err(Error::LengthError(_))
+        ));
+        assert!(matches!(
+            Codec::Compact.decode::<Fp31>(&[1, 31]),
+            Err(Error::ParseError(_))
+        ));
+        assert!(matches!(
+            Codec::Json.decode::<Fp31>(b"[[1, 31]]"),
+            Err(Error::ParseError(_))
+        ));
+        assert!(matches!(
+            Codec::Json.decode::<Fp31>(b"[1, 2]"),
+            Err(Error::Serde(_))
+        ));
+    }
+}
@@ -1,3 +1,5 @@
 mod additive_share;
+mod codec;
 
 pub use additive_share::{ASIterator, AdditiveShare};
+pub use codec::Codec;
@@ -1,19 +1,24 @@
 use std::{collections::HashMap, num::NonZeroU32};
 
-use rand::{thread_rng, Rng};
+use rand::{
+    distributions::{Distribution, Standard},
+    thread_rng, Rng,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::protocol::ipa_prf::prf_sharding::GroupingKey;
 #[cfg(feature = "in-memory-infra")]
 use crate::{
-    ff::{PrimeField, Serializable},
+    ff::PrimeField,
     helpers::query::IpaQueryConfig,
     protocol::ipa_prf::OPRFIPAInputRow,
-    secret_sharing::{
-        replicated::{
-            malicious::ExtendableField, semi_honest, semi_honest::AdditiveShare as Replicated,
-        },
-        IntoShares,
+    secret_sharing::replicated::{
+        malicious::ExtendableField, semi_honest, semi_honest::AdditiveShare as Replicated,
     },
+};
+use crate::{
+    ff::{boolean::Boolean, ArrayAccess, Serializable, U128Conversions},
+    protocol::ipa_prf::prf_sharding::{AttributionOutputs, GroupingKey, PrfShardedIpaInputRow},
+    secret_sharing::{replicated::semi_honest::AdditiveShare, IntoShares, SharedValue},
     test_fixture::Reconstruct,
 };
 
@@ -28,7 +33,12 @@ pub enum IpaQueryStyle {
     Oprf,
 }
 
-#[derive(Debug, Clone, Ord, PartialEq, PartialOrd, Eq)]
+/// Plaintext input row, before it is secret-shared.
+///
+/// It can be serialized to and from JSON, so test vectors can be authored by hand, e.g.
+/// `{"timestamp": 0, "user_id": 1, "is_trigger_report": false, "breakdown_key": 3,
+/// "trigger_value": 0}`.
+#[derive(Debug, Clone, Ord, PartialEq, PartialOrd, Eq, Serialize, Deserialize)]
 pub struct TestRawDataRecord {
     pub timestamp: u64,
     pub user_id: u64,
@@ -104,6 +114,146 @@ pub fn ipa_in_the_clear(
     breakdowns
 }
 
+/// Cleartext oracle for attribution tests. Runs [`ipa_in_the_clear`] with last-touch
+/// attribution and the capping order used by the OPRF IPA protocol, and returns the non-zero
+/// totals keyed by breakdown key. Tests do not need to know the breakdown count up front to
+/// compare against it, and the result can be passed to [`validate_attribution_output`] as is.
+///
+/// ## Panics
+/// If a breakdown key does not fit into `u64`.
+#[must_use]
+pub fn expected_breakdown_totals(
+    inputs: &[TestRawDataRecord],
+    cap: u32,
+    attribution_window: Option<NonZeroU32>,
+) -> HashMap<u64, u64> {
+    let max_breakdown = inputs
+        .iter()
+        .map(|r| r.breakdown_key + 1)
+        .max()
+        .unwrap_or(0);
+    ipa_in_the_clear(
+        inputs,
+        cap,
+        attribution_window,
+        max_breakdown,
+        &CappingOrder::CapMostRecentFirst,
+    )
+    .into_iter()
+    .enumerate()
+    .filter(|&(_, total)| total != 0)
+    .map(|(bk, total)| (u64::try_from(bk).unwrap(), u64::from(total)))
+    .collect()
+}
+
+/// Secret-shares cleartext events into rows that [`attribute_cap_aggregate`] takes as input.
+///
+/// Rows are grouped by user and ordered by timestamp within each user, with source events
+/// ahead of triggers that share their timestamp. This is the order the OPRF stage of IPA
+/// produces, except that the user id stands in for the PRF of the match key.
+///
+/// [`attribute_cap_aggregate`]: crate::protocol::ipa_prf::prf_sharding::attribute_cap_aggregate
+pub fn build_attribution_input<BK, TV, TS, R>(
+    events: &[TestRawDataRecord],
+    rng: &mut R,
+) -> [Vec<PrfShardedIpaInputRow<BK, TV, TS>>; 3]
+where
+    BK: SharedValue + U128Conversions,
+    TV: SharedValue + U128Conversions,
+    TS: SharedValue + U128Conversions,
+    Standard: Distribution<BK> + Distribution<TV> + Distribution<TS>,
+    R: Rng,
+{
+    let mut events = events.to_vec();
+    events.sort_by_key(|e| (e.user_id, e.timestamp, e.is_trigger_report));
+
+    let mut rows = [Vec::new(), Vec::new(), Vec::new()];
+    for event in events {
+        let is_trigger_bit = Boolean::from(event.is_trigger_report).share_with(rng);
+        let breakdown_key = BK::truncate_from(event.breakdown_key).share_with(rng);
+        let trigger_value = TV::truncate_from(event.trigger_value).share_with(rng);
+        let timestamp = TS::truncate_from(event.timestamp).share_with(rng);
+        for (i, helper_rows) in rows.iter_mut().enumerate() {
+            helper_rows.push(PrfShardedIpaInputRow {
+                prf_of_match_key: event.user_id,
+                is_trigger_bit: is_trigger_bit[i].clone(),
+                breakdown_key: breakdown_key[i].clone(),
+                trigger_value: trigger_value[i].clone(),
+                timestamp: timestamp[i].clone(),
+                sort_key: AdditiveShare::ZERO,
+            });
+        }
+    }
+
+    rows
+}
+
+/// Reconstructs a row that attribution hands over to aggregation into the bits of its breakdown
+/// key, least significant first, and its capped trigger value.
+///
+/// ## Panics
+/// If the helpers hold inconsistent shares of either value.
+#[must_use]
+pub fn reconstruct_aggregate_row<BK, TV>(
+    rows: [&AttributionOutputs<AdditiveShare<BK>, AdditiveShare<TV>>; 3],
+) -> (Vec<Boolean>, TV)
+where
+    BK: SharedValue + ArrayAccess<Output = Boolean>,
+    TV: SharedValue,
+{
+    let breakdown_key = rows
+        .map(|row| &row.attributed_breakdown_key_bits)
+        .reconstruct();
+    let trigger_value = rows
+        .map(|row| &row.capped_attributed_trigger_value)
+        .reconstruct();
+
+    (breakdown_key.iter().collect(), trigger_value)
+}
+
+/// Checks a serialized IPA result against the expected total for every breakdown key.
+///
+/// `bytes` holds the outputs of all three helpers one after another, in `H1`, `H2`, `H3` order.
+/// Each output is a sequence of serialized shares, one per breakdown key. Breakdown keys that
+/// are missing from `expected` must have a zero total.
+///
+/// ## Panics
+/// If `bytes` can't be split into three outputs of the same size, if the shares fail to
+/// deserialize or if the reconstructed totals do not match `expected`.
+pub fn validate_attribution_output<HV>(bytes: &[u8], expected: &HashMap<u64, u64>)
+where
+    HV: SharedValue + U128Conversions,
+    AdditiveShare<HV>: Serializable,
+{
+    assert_eq!(
+        0,
+        bytes.len() % 3,
+        "result of {} bytes can't be split between 3 helpers",
+        bytes.len()
+    );
+    let (h1, rest) = bytes.split_at(bytes.len() / 3);
+    let (h2, h3) = rest.split_at(h1.len());
+    let totals = [h1, h2, h3]
+        .map(|output| {
+            AdditiveShare::<HV>::from_byte_slice(output)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        })
+        .reconstruct();
+
+    let breakdowns = u64::try_from(totals.len()).unwrap();
+    if let Some(key) = expected.keys().find(|&&key| key >= breakdowns) {
+        panic!("breakdown key {key} is not in the result of {breakdowns} breakdowns");
+    }
+    for (breakdown_key, total) in (0..breakdowns).zip(totals) {
+        assert_eq!(
+            u128::from(expected.get(&breakdown_key).copied().unwrap_or_default()),
+            total.as_u128(),
+            "total for breakdown key {breakdown_key}"
+        );
+    }
+}
+
 pub enum CappingOrder {
     CapOldestFirst,
     CapMostRecentFirst,
@@ -259,7 +409,10 @@ pub async fn test_oprf_ipa<F>(
 
 #[cfg(all(test, unit_test))]
 mod tests {
+    use generic_array::GenericArray;
+
     use super::*;
+    use crate::ff::boolean_array::{BA16, BA3, BA5};
 
     fn insert_sorted_test<I: IntoIterator<Item = u64>>(iter: I) -> Vec<TestRawDataRecord> {
         fn test_record(timestamp: u64, breakdown_key: u32) -> TestRawDataRecord {
@@ -337,4 +490,205 @@ mod tests {
             assert_ne!(counts6[i], 0);
         }
     }
+
+    #[test]
+    fn raw_record_json() {
+        let json = r#"{
+            "timestamp": 10,
+            "user_id": 42,
+            "is_trigger_report": true,
+            "breakdown_key": 0,
+            "trigger_value": 5
+        }"#;
+        let record: TestRawDataRecord = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            TestRawDataRecord {
+                timestamp: 10,
+                user_id: 42,
+                is_trigger_report: true,
+                breakdown_key: 0,
+                trigger_value: 5,
+            },
+            record
+        );
+
+        let value = serde_json::to_value(&record).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(json).unwrap(),
+            value
+        );
+        assert_eq!(record, serde_json::from_value(value).unwrap());
+    }
+
+    #[test]
+    fn attribution_input() {
+        use crate::{
+            ff::boolean_array::{BA20, BA3, BA8},
+            protocol::ipa_prf::prf_sharding::check_user_grouping,
+            test_fixture::Reconstruct,
+        };
+
+        let event = |user_id, timestamp, is_trigger_report, breakdown_key, trigger_value| {
+            TestRawDataRecord {
+                timestamp,
+                user_id,
+                is_trigger_report,
+                breakdown_key,
+                trigger_value,
+            }
+        };
+        // users are interleaved, user 2 has a source and a trigger with the same timestamp
+        let events = [
+            event(2, 5, true, 0, 4),
+            event(1, 0, false, 3, 0),
+            event(2, 5, false, 1, 0),
+            event(1, 7, true, 0, 2),
+        ];
+        let expected = [&events[1], &events[3], &events[2], &events[0]];
+
+        let rows = build_attribution_input::<BA8, BA3, BA20, _>(&events, &mut thread_rng());
+        for helper_rows in &rows {
+            assert_eq!(
+                vec![1, 1, 2, 2],
+                helper_rows
+                    .iter()
+                    .map(|r| r.prf_of_match_key)
+                    .collect::<Vec<_>>()
+            );
+            check_user_grouping(helper_rows).unwrap();
+        }
+
+        for (i, expected) in expected.into_iter().enumerate() {
+            let [r0, r1, r2] = rows.each_ref().map(|helper_rows| &helper_rows[i]);
+            assert_eq!(
+                expected.is_trigger_report,
+                bool::from(
+                    [&r0.is_trigger_bit, &r1.is_trigger_bit, &r2.is_trigger_bit].reconstruct()
+                )
+            );
+            assert_eq!(
+                u128::from(expected.breakdown_key),
+                [&r0.breakdown_key, &r1.breakdown_key, &r2.breakdown_key]
+                    .reconstruct()
+                    .as_u128()
+            );
+            assert_eq!(
+                u128::from(expected.trigger_value),
+                [&r0.trigger_value, &r1.trigger_value, &r2.trigger_value]
+                    .reconstruct()
+                    .as_u128()
+            );
+            assert_eq!(
+                u128::from(expected.timestamp),
+                [&r0.timestamp, &r1.timestamp, &r2.timestamp]
+                    .reconstruct()
+                    .as_u128()
+            );
+        }
+    }
+
+    #[test]
+    fn breakdown_totals() {
+        fn record(
+            user_id: u64,
+            timestamp: u64,
+            breakdown_key: u32,
+            trigger_value: u32,
+        ) -> TestRawDataRecord {
+            TestRawDataRecord {
+                timestamp,
+                user_id,
+                is_trigger_report: trigger_value > 0,
+                breakdown_key,
+                trigger_value,
+            }
+        }
+
+        let inputs = [
+            // both triggers go to breakdown 2, capped at 5 with the latest counted first
+            record(1, 0, 2, 0),
+            record(1, 10, 0, 3),
+            record(1, 20, 0, 4),
+            // last touch wins
+            record(2, 0, 1, 0),
+            record(2, 5, 3, 0),
+            record(2, 7, 0, 2),
+            // no source to attribute to
+            record(3, 3, 0, 6),
+        ];
+
+        assert_eq!(
+            HashMap::from([(2, 5), (3, 2)]),
+            expected_breakdown_totals(&inputs, 5, None)
+        );
+        // triggers of user 1 are too far from their source
+        assert_eq!(
+            HashMap::from([(3, 2)]),
+            expected_breakdown_totals(&inputs, 5, NonZeroU32::new(5))
+        );
+        assert!(expected_breakdown_totals(&[], 5, None).is_empty());
+    }
+
+    #[test]
+    fn aggregate_row() {
+        let mut rng = thread_rng();
+        let breakdown_key = BA5::truncate_from(0b10110_u128).share_with(&mut rng);
+        let trigger_value = BA3::truncate_from(6_u128).share_with(&mut rng);
+        let rows = [0, 1, 2].map(|i| AttributionOutputs {
+            attributed_breakdown_key_bits: breakdown_key[i].clone(),
+            capped_attributed_trigger_value: trigger_value[i].clone(),
+        });
+
+        let (bits, value) = reconstruct_aggregate_row(rows.each_ref());
+        assert_eq!(
+            [false, true, true, false, true].map(Boolean::from).to_vec(),
+            bits
+        );
+        assert_eq!(BA3::truncate_from(6_u128), value);
+    }
+
+    fn serialize_result(totals: &[u128]) -> Vec<u8> {
+        let shares = totals
+            .iter()
+            .map(|&v| BA16::truncate_from(v))
+            .share_with(&mut thread_rng());
+        let mut bytes = Vec::new();
+        for output in shares {
+            for share in output {
+                let mut buf = GenericArray::default();
+                share.serialize(&mut buf);
+                bytes.extend_from_slice(&buf);
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn validate_output() {
+        let bytes = serialize_result(&[3, 0, 5, 1]);
+        validate_attribution_output::<BA16>(&bytes, &HashMap::from([(0, 3), (2, 5), (3, 1)]));
+        validate_attribution_output::<BA16>(&[], &HashMap::new());
+    }
+
+    #[test]
+    fn validate_output_against_oracle() {
+        let inputs = [(0, true, 2, 0), (4, false, 0, 3), (9, false, 0, 4)].map(
+            |(timestamp, is_source, breakdown_key, trigger_value)| TestRawDataRecord {
+                timestamp,
+                user_id: 1,
+                is_trigger_report: !is_source,
+                breakdown_key,
+                trigger_value,
+            },
+        );
+        let expected = expected_breakdown_totals(&inputs, 5, None);
+        validate_attribution_output::<BA16>(&serialize_result(&[0, 0, 5]), &expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "total for breakdown key 1")]
+    fn validate_output_mismatch() {
+        let bytes = serialize_result(&[3, 4]);
+        validate_attribution_output::<BA16>(&bytes, &HashMap::from([(0, 3)]));
+    }
 }
@@ -21,18 +21,30 @@ use std::fmt::Debug;
 pub use app::TestApp;
 pub use event_gen::{Config as EventGeneratorConfig, EventGenerator};
 use futures::TryFuture;
-use rand::{distributions::Standard, prelude::Distribution, rngs::mock::StepRng};
+use generic_array::{sequence::GenericSequence, GenericArray};
+use rand::{
+    distributions::Standard,
+    prelude::Distribution,
+    rngs::{mock::StepRng, StdRng},
+    Rng, SeedableRng,
+};
 use rand_core::{CryptoRng, RngCore};
-pub use sharing::{get_bits, into_bits, Reconstruct, ReconstructArr};
+pub use sharing::{
+    assert_consistent_helper_set, assert_reveals, assert_shares_eq, bit_decompose_all,
+    bit_decompose_plain, get_bits, into_bits, lift_to_malicious, mac_shares, malicious_r_shares,
+    random_shared, reconstruct_bits, reconstruct_weighted, rotate_shares, share_bits, zip_shares,
+    Reconstruct, ReconstructArr,
+};
 #[cfg(feature = "in-memory-infra")]
 pub use world::{
     Distribute, Random as RandomInputDistribution, RoundRobin as RoundRobinInputDistribution,
     Runner, TestExecutionStep, TestWorld, TestWorldConfig, WithShards,
 };
 
+pub use crate::secret_sharing::share_from_parts;
 use crate::{
     ff::{Field, U128Conversions},
-    protocol::prss::Endpoint as PrssEndpoint,
+    protocol::prss::{Endpoint as PrssEndpoint, FromRandom},
     secret_sharing::{
         replicated::semi_honest::AdditiveShare as Replicated, IntoShares, SharedValue,
     },
@@ -56,6 +68,18 @@ pub fn make_participants<R: RngCore + CryptoRng>(r: &mut R) -> [PrssEndpoint; 3]
     [p1, p2, p3]
 }
 
+/// Generates a value the same way PRSS does, via [`FromRandom`], but from randomness derived
+/// deterministically from `seed` and `index`. Tests that depend on PRSS output can use it to
+/// get replayable values, e.g. `from_prss_seed::<Fp25519>(seed, record_id)`.
+#[must_use]
+pub fn from_prss_seed<T: FromRandom>(seed: u64, index: u64) -> T {
+    let mut key = [0u8; 32];
+    key[..8].copy_from_slice(&seed.to_le_bytes());
+    key[8..16].copy_from_slice(&index.to_le_bytes());
+    let mut rng = StdRng::from_seed(key);
+    T::from_random(GenericArray::generate(|_| rng.gen()))
+}
+
 pub type ReplicatedShares<T> = [Vec<Replicated<T>>; 3];
 
 /// Generate vector shares from vector of inputs for three participant
@@ -153,3 +177,16 @@ pub fn bits_to_value<F: Field + U128Conversions>(x: &[F]) -> u128 {
 pub fn bits_to_field<F: Field + U128Conversions>(x: &[F]) -> F {
     F::try_from(bits_to_value(x)).unwrap()
 }
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use crate::{ff::ec_prime_field::Fp25519, test_fixture::from_prss_seed};
+
+    #[test]
+    fn prss_seed_is_replayable() {
+        let a = from_prss_seed::<Fp25519>(42, 7);
+        assert_eq!(a, from_prss_seed::<Fp25519>(42, 7));
+        assert_ne!(a, from_prss_seed::<Fp25519>(42, 8));
+        assert_ne!(a, from_prss_seed::<Fp25519>(43, 7));
+    }
+}
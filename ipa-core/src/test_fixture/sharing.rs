@@ -1,14 +1,21 @@
-use std::{borrow::Borrow, iter::zip, ops::Deref};
+use std::{array, borrow::Borrow, iter::zip, ops::Deref};
+
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng, RngCore,
+};
 
 use crate::{
-    ff::{Field, PrimeField, U128Conversions},
+    error::Error,
+    ff::{boolean::Boolean, Field, PrimeField, U128Conversions},
+    helpers::Role,
     secret_sharing::{
         replicated::{
             malicious::{AdditiveShare as MaliciousReplicated, ExtendableField},
             semi_honest::AdditiveShare as Replicated,
             ReplicatedSecretSharing,
         },
-        BitDecomposed, SharedValue, Vectorizable,
+        BitDecomposed, IntoShares, SharedValue, Vectorizable,
     },
 };
 
@@ -29,6 +36,116 @@ pub fn get_bits<V: U128Conversions>(x: u32, num_bits: u32) -> BitDecomposed<V> {
     BitDecomposed::decompose(num_bits, |i| V::truncate_from((x >> i) & 1))
 }
 
+/// Decomposes a value into its `bits` least significant bits, least significant bit first.
+///
+/// ## Panics
+/// If `bits` exceeds 128.
+#[must_use]
+pub fn bit_decompose_plain<F: Field + U128Conversions>(value: F, bits: usize) -> Vec<bool> {
+    assert!(bits <= 128, "Up to 128 bits are supported, got {bits}");
+    let v = value.as_u128();
+    (0..bits).map(|i| (v >> i) & 1 == 1).collect()
+}
+
+/// Same as [`bit_decompose_plain`], but for every element of `values`, so tests can build
+/// batches of bit-decomposed inputs at once.
+///
+/// ## Panics
+/// When iterated, if `bits` exceeds 128.
+pub fn bit_decompose_all<F: Field + U128Conversions>(
+    values: &[F],
+    bits: usize,
+) -> impl Iterator<Item = Vec<bool>> + '_ {
+    values
+        .iter()
+        .map(move |&value| bit_decompose_plain(value, bits))
+}
+
+/// Secret-shares the `bits` least significant bits of a value as boolean shares. This allows
+/// tests for boolean protocols to build their inputs without running the conversion protocol.
+///
+/// ## Panics
+/// If `bits` exceeds 128.
+pub fn share_bits<F: Field + U128Conversions, R: Rng>(
+    value: F,
+    bits: usize,
+    rng: &mut R,
+) -> [BitDecomposed<Replicated<Boolean>>; 3] {
+    BitDecomposed::new(
+        bit_decompose_plain(value, bits)
+            .into_iter()
+            .map(Boolean::from),
+    )
+    .share_with(rng)
+}
+
+/// Samples a random secret and secret-shares it, for tests that only need some shared value and
+/// the secret it reconstructs to.
+pub fn random_shared<F, R>(rng: &mut R) -> (F, [Replicated<F>; 3])
+where
+    F: Field,
+    R: RngCore,
+    Standard: Distribution<F>,
+{
+    let secret = rng.gen::<F>();
+    (secret, secret.share_with(rng))
+}
+
+/// Reconstructs boolean shares position by position, so tests can compare the result against an
+/// expected `Vec<bool>` directly.
+///
+/// ## Errors
+/// If the shares at any position are not a valid replicated secret sharing.
+pub fn reconstruct_bits(shares: &[[Replicated<Boolean>; 3]]) -> Result<Vec<bool>, Error> {
+    shares
+        .iter()
+        .map(|[s0, s1, s2]| {
+            if s0.right() == s1.left() && s1.right() == s2.left() && s2.right() == s0.left() {
+                Ok(bool::from(s0.left() + s1.left() + s2.left()))
+            } else {
+                Err(Error::InconsistentShares)
+            }
+        })
+        .collect()
+}
+
+/// Checks that every triple of shares comes from a complete set of helpers, i.e. it has one
+/// share tagged with each of `H1`, `H2` and `H3`. When result shards are combined, this catches
+/// share columns that were mixed up or taken from the same helper twice, which would otherwise
+/// reconstruct to garbage.
+///
+/// ## Errors
+/// [`Error::InconsistentHelperSet`] for the first triple with a missing or duplicated role.
+pub fn assert_consistent_helper_set<S>(triples: &[[(Role, S); 3]]) -> Result<(), Error> {
+    for (row, triple) in triples.iter().enumerate() {
+        let roles = triple.each_ref().map(|(role, _)| *role);
+        let mut sorted = roles;
+        sorted.sort();
+        if &sorted != Role::all() {
+            return Err(Error::InconsistentHelperSet { row, roles });
+        }
+    }
+
+    Ok(())
+}
+
+/// Reconstructs a value where each helper's additive share is scaled by a coefficient before
+/// summing, e.g. Lagrange coefficients in interpolation tests. With all coefficients equal to
+/// one this is the same as [`Reconstruct::reconstruct`].
+///
+/// ## Panics
+/// If the given input is not a valid replicated secret share.
+#[must_use]
+pub fn reconstruct_weighted<F: Field>(shares: [(Replicated<F>, F); 3]) -> F {
+    let [(s0, c0), (s1, c1), (s2, c2)] = shares;
+
+    assert_eq!(s0.right(), s1.left());
+    assert_eq!(s1.right(), s2.left());
+    assert_eq!(s2.right(), s0.left());
+
+    c0 * s0.left() + c1 * s1.left() + c2 * s2.left()
+}
+
 /// A trait that is helpful for reconstruction of values in tests.
 pub trait Reconstruct<T> {
     /// Validates correctness of the secret sharing scheme.
@@ -178,6 +295,132 @@ impl Reconstruct<()> for [(); 3] {
     fn reconstruct(&self) {}
 }
 
+/// Relabels helpers cyclically, so that H1 gets the shares that H2 held, H2 gets the shares of H3
+/// and H3 gets the shares of H1. The result is still a valid sharing of the same secret.
+#[must_use]
+pub fn rotate_shares<F: SharedValue>(shares: [Replicated<F>; 3]) -> [Replicated<F>; 3] {
+    let [s0, s1, s2] = shares;
+    [s1, s2, s0]
+}
+
+/// Combines per-helper outputs, e.g. the ones returned by running a protocol in `TestWorld`,
+/// into per-record share triples that can be reconstructed.
+///
+/// ## Panics
+/// If helpers returned a different number of shares.
+#[must_use]
+pub fn zip_shares<F: SharedValue>(
+    h1: Vec<Replicated<F>>,
+    h2: Vec<Replicated<F>>,
+    h3: Vec<Replicated<F>>,
+) -> Vec<[Replicated<F>; 3]> {
+    assert!(
+        h1.len() == h2.len() && h2.len() == h3.len(),
+        "helpers returned different number of shares: {}, {} and {}",
+        h1.len(),
+        h2.len(),
+        h3.len()
+    );
+    zip(zip(h1, h2), h3)
+        .map(|((s1, s2), s3)| [s1, s2, s3])
+        .collect()
+}
+
+/// Reconstructs both sets of shares and asserts they hold the same values. On mismatch, the
+/// panic message names the first differing position along with both plaintext values.
+///
+/// ## Panics
+/// If shares are inconsistent, the vectors have different lengths or differ at some position.
+#[track_caller]
+pub fn assert_shares_eq<F: Field>(expected: &[[Replicated<F>; 3]], actual: &[[Replicated<F>; 3]]) {
+    assert_eq!(
+        expected.len(),
+        actual.len(),
+        "expected {} shared values, got {}",
+        expected.len(),
+        actual.len()
+    );
+    for (i, (expected, actual)) in zip(expected, actual).enumerate() {
+        let (expected, actual) = (expected.reconstruct(), actual.reconstruct());
+        assert_eq!(
+            expected, actual,
+            "shares differ at index {i}: expected {expected:?}, got {actual:?}"
+        );
+    }
+}
+
+/// Reconstructs `shares` and asserts that they hold `expected`, with both values in the panic
+/// message on mismatch.
+///
+/// ## Panics
+/// If shares are inconsistent or do not reconstruct to `expected`.
+#[track_caller]
+pub fn assert_reveals<F: Field>(shares: [&Replicated<F>; 3], expected: F) {
+    let actual = shares.reconstruct();
+    assert_eq!(
+        expected, actual,
+        "shares reveal {actual:?}, expected {expected:?}"
+    );
+}
+
+/// Pairs semi-honest shares of `x` with shares of `r*x`, producing malicious shares for each
+/// helper. Input is indexed by record and then by helper, output by helper and then by record,
+/// which is the shape malicious protocols take their inputs in.
+///
+/// ## Panics
+/// If `x_shares` and `rx_shares` have different lengths.
+#[must_use]
+pub fn lift_to_malicious<F: ExtendableField>(
+    x_shares: &[[Replicated<F>; 3]],
+    rx_shares: &[[Replicated<F::ExtendedField>; 3]],
+) -> [Vec<MaliciousReplicated<F>>; 3] {
+    assert_eq!(
+        x_shares.len(),
+        rx_shares.len(),
+        "x and r*x must have the same number of shares"
+    );
+
+    array::from_fn(|helper| {
+        zip(x_shares, rx_shares)
+            .map(|(x, rx)| MaliciousReplicated::new(x[helper].clone(), rx[helper].clone()))
+            .collect()
+    })
+}
+
+/// Computes shares of `r*x` from shares of `x` and a public `r`. Multiplying by a public
+/// constant is local, so every helper scales its own share and the result is a valid sharing
+/// of `r*x` without any fresh randomness. Input is indexed by record and then by helper, output
+/// by helper and then by record.
+#[must_use]
+pub fn mac_shares<F: Field>(x_shares: &[[Replicated<F>; 3]], r: F) -> [Vec<Replicated<F>>; 3] {
+    array::from_fn(|helper| x_shares.iter().map(|x| &x[helper] * r).collect())
+}
+
+/// Secret-shares `x_secrets` together with their MACs `r*x` and returns malicious share columns,
+/// one per helper, along with `r`. If `r` is not given, a random one is sampled, so tests that
+/// need the MAC key later (e.g. to validate the outputs) can get it from here.
+pub fn malicious_r_shares<F, R>(
+    x_secrets: &[F],
+    r: Option<F::ExtendedField>,
+    rng: &mut R,
+) -> ([Vec<MaliciousReplicated<F>>; 3], F::ExtendedField)
+where
+    F: ExtendableField,
+    R: RngCore,
+    Standard: Distribution<F> + Distribution<F::ExtendedField>,
+{
+    let r = r.unwrap_or_else(|| rng.gen());
+    let (x_shares, rx_shares): (
+        Vec<[Replicated<F>; 3]>,
+        Vec<[Replicated<F::ExtendedField>; 3]>,
+    ) = x_secrets
+        .iter()
+        .map(|&x| (x.share_with(rng), (r * x.to_extended()).share_with(rng)))
+        .unzip();
+
+    (lift_to_malicious(&x_shares, &rx_shares), r)
+}
+
 pub trait ValidateMalicious<F: ExtendableField> {
     fn validate(&self, r: F::ExtendedField);
 }
@@ -241,3 +484,271 @@ impl<F: ExtendableField> ValidateMalicious<F>
         [v0.clone(), v1.clone(), v2.clone()].validate(r);
     }
 }
+
+#[cfg(all(test, unit_test))]
+mod tests {
+    use crate::{
+        error::Error,
+        ff::{boolean::Boolean, Field, Fp31, U128Conversions},
+        helpers::Role,
+        rand::{thread_rng, Rng},
+        secret_sharing::{
+            replicated::{semi_honest::AdditiveShare as Replicated, ReplicatedSecretSharing},
+            share_from_parts, IntoShares,
+        },
+        test_fixture::{
+            assert_consistent_helper_set, assert_reveals, assert_shares_eq, bit_decompose_all,
+            bit_decompose_plain, bits_to_value, lift_to_malicious, mac_shares, malicious_r_shares,
+            random_shared, reconstruct_bits, reconstruct_weighted, rotate_shares, share_bits,
+            zip_shares, Reconstruct,
+        },
+    };
+
+    #[test]
+    fn bit_decompose() {
+        let value = Fp31::truncate_from(5_u128);
+        assert_eq!(
+            vec![true, false, true, false],
+            bit_decompose_plain(value, 4)
+        );
+
+        let shares = share_bits(value, 4, &mut thread_rng());
+        let bits = (0..4)
+            .map(|i| [&shares[0][i], &shares[1][i], &shares[2][i]].reconstruct())
+            .collect::<Vec<_>>();
+        assert_eq!([true, false, true, false].map(Boolean::from).to_vec(), bits);
+        assert_eq!(5, bits_to_value(&bits));
+    }
+
+    #[test]
+    fn reconstruct_bool_vec() {
+        let mut rng = thread_rng();
+        let expected = vec![true, false, true, true, false];
+        let mut shares = expected
+            .iter()
+            .map(|&b| Boolean::from(b).share_with(&mut rng))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, reconstruct_bits(&shares).unwrap());
+
+        let s = shares[2][1].clone();
+        shares[2][1] = Replicated::new(s.left() + Boolean::ONE, s.right());
+        assert!(matches!(
+            reconstruct_bits(&shares),
+            Err(Error::InconsistentShares)
+        ));
+    }
+
+    #[test]
+    fn consistent_helper_set() {
+        let mut rng = thread_rng();
+        let shares = (0..3)
+            .map(|_| {
+                let [s0, s1, s2] = rng.gen::<Fp31>().share_with(&mut rng);
+                [(Role::H2, s1), (Role::H1, s0), (Role::H3, s2)]
+            })
+            .collect::<Vec<_>>();
+        assert_consistent_helper_set(&shares).unwrap();
+
+        let mut mixed = shares;
+        mixed[1][2].0 = Role::H1;
+        assert!(matches!(
+            assert_consistent_helper_set(&mixed),
+            Err(Error::InconsistentHelperSet {
+                row: 1,
+                roles: [Role::H2, Role::H1, Role::H1],
+            })
+        ));
+    }
+
+    #[test]
+    fn weighted_reconstruction() {
+        let fp = |v: u8| Fp31::truncate_from(v);
+        let weighted = |shares: [Replicated<Fp31>; 3], coefficients: [Fp31; 3]| {
+            let [s0, s1, s2] = shares;
+            let [c0, c1, c2] = coefficients;
+            reconstruct_weighted([(s0, c0), (s1, c1), (s2, c2)])
+        };
+
+        let shares = share_from_parts(fp(20), fp(4), fp(9));
+        assert_eq!(
+            fp(4),
+            weighted(shares.clone(), [Fp31::ONE, Fp31::ZERO, Fp31::ZERO])
+        );
+        assert_eq!(fp(20), weighted(shares, [Fp31::ONE; 3]));
+
+        // Additive shares are evaluations of p(x) = 5 + 2x + 3x^2 at x = 1, 2, 3. Lagrange
+        // coefficients for interpolating p(0) from these points are [3, -3, 1].
+        let p = |x: u8| fp(5) + fp(2) * fp(x) + fp(3) * fp(x) * fp(x);
+        let shares = share_from_parts(p(1) + p(2) + p(3), p(1), p(2));
+        assert_eq!(
+            fp(5),
+            weighted(shares, [fp(3), Fp31::ZERO - fp(3), Fp31::ONE])
+        );
+    }
+
+    #[test]
+    fn random_shared_reconstructs() {
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            let (secret, shares) = random_shared::<Fp31, _>(&mut rng);
+            assert_eq!(secret, shares.reconstruct());
+        }
+    }
+
+    #[test]
+    fn lift_semi_honest_to_malicious() {
+        use super::ValidateMalicious;
+        use crate::secret_sharing::replicated::malicious::ThisCodeIsAuthorizedToDowngradeFromMalicious;
+
+        let mut rng = thread_rng();
+        let r = rng.gen::<Fp31>();
+        let x = (0..5).map(|_| rng.gen::<Fp31>()).collect::<Vec<_>>();
+        let x_shares: Vec<[Replicated<Fp31>; 3]> =
+            x.iter().map(|&x| x.share_with(&mut rng)).collect();
+        let rx_shares: Vec<[Replicated<Fp31>; 3]> =
+            x.iter().map(|&x| (r * x).share_with(&mut rng)).collect();
+
+        let lifted = lift_to_malicious(&x_shares, &rx_shares);
+        lifted.validate(r);
+        for (i, &x) in x.iter().enumerate() {
+            let [m0, m1, m2] = lifted.each_ref().map(|helper| &helper[i]);
+            assert_eq!(
+                x,
+                [m0, m1, m2]
+                    .map(|m| m.x().access_without_downgrade().clone())
+                    .reconstruct()
+            );
+            assert_eq!(r * x, [m0.rx(), m1.rx(), m2.rx()].reconstruct());
+        }
+    }
+
+    #[test]
+    fn rotation_preserves_secret() {
+        let mut rng = thread_rng();
+        let (secret, shares) = random_shared::<Fp31, _>(&mut rng);
+        let rotated = rotate_shares(shares.clone());
+        assert_eq!(secret, rotated.reconstruct());
+        assert_eq!(shares[1], rotated[0]);
+
+        // three rotations bring every share back to where it started
+        assert_eq!(shares, rotate_shares(rotate_shares(rotated)));
+    }
+
+    #[test]
+    fn zip_helper_outputs() {
+        let mut rng = thread_rng();
+        let values = (0..5).map(|_| rng.gen::<Fp31>()).collect::<Vec<_>>();
+        let shares = share_all(&values);
+        let [h1, h2, h3] =
+            [0, 1, 2].map(|i| shares.iter().map(|s| s[i].clone()).collect::<Vec<_>>());
+
+        let zipped = zip_shares(h1, h2, h3);
+        assert_eq!(shares, zipped);
+        assert_eq!(
+            values,
+            zipped
+                .iter()
+                .map(Reconstruct::reconstruct)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "helpers returned different number of shares: 2, 2 and 1")]
+    fn zip_length_mismatch() {
+        let mut rng = thread_rng();
+        let [s1, s2, s3] = Fp31::ONE.share_with(&mut rng);
+        let _ = zip_shares(vec![s1.clone(); 2], vec![s2; 2], vec![s3]);
+    }
+
+    fn share_all(values: &[Fp31]) -> Vec<[Replicated<Fp31>; 3]> {
+        let mut rng = thread_rng();
+        values.iter().map(|&v| v.share_with(&mut rng)).collect()
+    }
+
+    #[test]
+    fn bit_decompose_batch() {
+        let values = [1_u8, 2, 3].map(Fp31::truncate_from);
+        assert_eq!(
+            vec![vec![true, false], vec![false, true], vec![true, true]],
+            bit_decompose_all(&values, 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn shares_eq() {
+        let mut rng = thread_rng();
+        let values = (0..5).map(|_| rng.gen::<Fp31>()).collect::<Vec<_>>();
+
+        // different sharings of the same values are equal
+        assert_shares_eq(&share_all(&values), &share_all(&values));
+    }
+
+    #[test]
+    #[should_panic(expected = "shares differ at index 1: expected 3_mod31, got 4_mod31")]
+    fn shares_not_eq() {
+        let fp = |v: [u8; 3]| v.map(Fp31::truncate_from);
+        assert_shares_eq(&share_all(&fp([1, 3, 5])), &share_all(&fp([1, 4, 6])));
+    }
+
+    #[test]
+    fn reveals() {
+        let mut rng = thread_rng();
+        let (secret, shares) = random_shared::<Fp31, _>(&mut rng);
+        assert_reveals(shares.each_ref(), secret);
+    }
+
+    #[test]
+    #[should_panic(expected = "shares reveal 3_mod31, expected 4_mod31")]
+    fn reveals_mismatch() {
+        let shares = Fp31::truncate_from(3_u8).share_with(&mut thread_rng());
+        assert_reveals(shares.each_ref(), Fp31::truncate_from(4_u8));
+    }
+
+    #[test]
+    fn malicious_r_shares_macs() {
+        use crate::secret_sharing::replicated::malicious::ThisCodeIsAuthorizedToDowngradeFromMalicious;
+
+        let mut rng = thread_rng();
+        let x = (0..5).map(|_| rng.gen::<Fp31>()).collect::<Vec<_>>();
+        let (shares, r) = malicious_r_shares(&x, None, &mut rng);
+        for (i, &x) in x.iter().enumerate() {
+            let [m0, m1, m2] = shares.each_ref().map(|helper| &helper[i]);
+            assert_eq!(
+                x,
+                [m0, m1, m2]
+                    .map(|m| m.x().access_without_downgrade().clone())
+                    .reconstruct()
+            );
+            assert_eq!(r * x, [m0.rx(), m1.rx(), m2.rx()].reconstruct());
+        }
+
+        let given = Fp31::truncate_from(7_u128);
+        let (_, r) = malicious_r_shares(&x, Some(given), &mut rng);
+        assert_eq!(given, r);
+    }
+
+    #[test]
+    fn mac_shares_reconstruct() {
+        let mut rng = thread_rng();
+        let x = (0..5).map(|_| rng.gen::<Fp31>()).collect::<Vec<_>>();
+        let r = rng.gen::<Fp31>();
+        let x_shares = x
+            .iter()
+            .map(|&x| x.share_with(&mut rng))
+            .collect::<Vec<_>>();
+
+        let rx_shares = mac_shares(&x_shares, r);
+        for (i, &x) in x.iter().enumerate() {
+            assert_reveals(rx_shares.each_ref().map(|helper| &helper[i]), r * x);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "x and r*x must have the same number of shares")]
+    fn lift_length_mismatch() {
+        let mut rng = thread_rng();
+        let x_shares: Vec<[Replicated<Fp31>; 3]> = vec![Fp31::ONE.share_with(&mut rng)];
+        let _ = lift_to_malicious(&x_shares, &[]);
+    }
+}
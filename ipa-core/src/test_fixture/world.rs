@@ -207,6 +207,19 @@ impl TestWorld<NotSharded> {
     pub fn gateway(&self, role: Role) -> &Gateway {
         &self.shards[0].gateways[role]
     }
+
+    /// Returns the largest number of communication rounds any helper has made since this world
+    /// was created, see [`Gateway::rounds`]. Rounds add up across protocol runs, so use a fresh
+    /// world for every protocol that needs to be measured.
+    #[must_use]
+    pub fn round_count(&self) -> usize {
+        self.shards[0]
+            .gateways
+            .iter()
+            .map(Gateway::rounds)
+            .max()
+            .unwrap_or_default()
+    }
 }
 
 impl<S: ShardingScheme> Drop for TestWorld<S> {
@@ -592,7 +605,7 @@ impl<B: ShardBinding> ShardWorld<B> {
 
         let mut gateways = zip3_ref(&network.transports(), &transports).map(|(mpc, shard)| {
             Gateway::new(
-                QueryId,
+                QueryId::DEFAULT,
                 config.gateway_config,
                 config.role_assignment().clone(),
                 Transport::clone_ref(mpc),
@@ -704,13 +717,42 @@ mod tests {
     };
 
     use crate::{
-        ff::{boolean_array::BA3, U128Conversions},
-        protocol::{context::Context, prss::SharedRandomness},
+        ff::{boolean_array::BA3, Field, Fp31, U128Conversions},
+        protocol::{basics::SecureMul, context::Context, prss::SharedRandomness, RecordId},
         sharding::ShardConfiguration,
         test_executor::run,
         test_fixture::{world::WithShards, Reconstruct, Runner, TestWorld, TestWorldConfig},
     };
 
+    /// Multiplies a value by itself `depth` times, every multiplication depends on the previous one.
+    async fn multiplication_rounds(depth: usize) -> usize {
+        let world = TestWorld::default();
+        world
+            .semi_honest(Fp31::ONE, |ctx, a| async move {
+                let ctx = ctx.set_total_records(1);
+                let mut acc = a.clone();
+                for i in 0..depth {
+                    acc = acc
+                        .multiply(&a, ctx.narrow(&format!("mul{i}")), RecordId::FIRST)
+                        .await
+                        .unwrap();
+                }
+                acc
+            })
+            .await;
+
+        world.round_count()
+    }
+
+    #[test]
+    fn round_count() {
+        run(|| async {
+            assert_eq!(0, multiplication_rounds(0).await);
+            assert_eq!(1, multiplication_rounds(1).await);
+            assert_eq!(3, multiplication_rounds(3).await);
+        });
+    }
+
     #[test]
     fn two_shards() {
         run(|| async {
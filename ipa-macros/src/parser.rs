@@ -1,6 +1,7 @@
 use std::{
-    collections::{HashMap, VecDeque},
-    io::Read,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    io::{self, Read, Write},
     path::PathBuf,
 };
 
@@ -13,14 +14,38 @@ pub(crate) const STEPS_FILE_NAME: &str = "steps.txt";
 #[derive(Clone, Debug)]
 pub(crate) struct StepMetaData {
     pub id: u16,
-    pub depth: u8,
+    pub depth: u16,
     pub module: String,
     pub name: String,
     pub path: String,
 }
 
+// Steps are ordered by name first, so that siblings are traversed alphabetically. Ids are unique,
+// so they only serve as a tie breaker that keeps the ordering consistent with equality.
+impl PartialEq for StepMetaData {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for StepMetaData {}
+
+impl PartialOrd for StepMetaData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StepMetaData {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name
+            .cmp(&other.name)
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
 impl StepMetaData {
-    pub fn new(id: u16, depth: u8, module: String, name: String, path: String) -> Self {
+    pub fn new(id: u16, depth: u16, module: String, name: String, path: String) -> Self {
         Self {
             id,
             depth,
@@ -39,28 +64,39 @@ pub(crate) fn ipa_state_transition_map() -> Node<StepMetaData> {
     let steps = read_steps_file(STEPS_FILE_NAME)
         .into_iter()
         .enumerate()
-        .map(|(i, path)| {
-            let id = u16::try_from(i + 1).unwrap();
-            let path_list = path
-                .split('/')
-                .map(split_step_module_and_name)
-                .collect::<Vec<_>>();
-            let depth = u8::try_from(path_list.len()).unwrap();
-            let (module, name) = path_list.last().unwrap();
-            // `path` is used to construct the AsRef implementation.
-            // strip the module parts from all steps to reduce the memory footprint.
-            let path = path_list
-                .iter()
-                .map(|(_, name)| name.to_owned())
-                .collect::<Vec<_>>()
-                .join("/");
-            StepMetaData::new(id, depth, module.to_owned(), name.to_owned(), path)
-        })
+        .map(|(i, path)| parse_step(u16::try_from(i + 1).unwrap(), &path))
         .collect::<Vec<_>>();
 
     construct_tree(steps)
 }
 
+/// Parses a single line of the steps file into the metadata of the last step in it.
+///
+/// # Panics
+/// If the path is more than `u16::MAX` steps deep.
+pub(crate) fn parse_step(id: u16, path: &str) -> StepMetaData {
+    let path_list = path
+        .split('/')
+        .map(split_step_module_and_name)
+        .collect::<Vec<_>>();
+    let depth = u16::try_from(path_list.len()).unwrap_or_else(|_| {
+        panic!(
+            "step {path} is {} levels deep, at most {} are supported",
+            path_list.len(),
+            u16::MAX
+        )
+    });
+    let (module, name) = path_list.last().unwrap();
+    // `path` is used to construct the AsRef implementation.
+    // strip the module parts from all steps to reduce the memory footprint.
+    let path = path_list
+        .iter()
+        .map(|(_, name)| name.to_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    StepMetaData::new(id, depth, module.to_owned(), name.to_owned(), path)
+}
+
 /// Reads the steps file and returns a vector of strings, where each string represents a line in the file.
 pub(crate) fn read_steps_file(file_path: &str) -> Vec<String> {
     // construct the path to the steps file saved in STEPS_FILE_PATH relative to this crate's root.
@@ -141,7 +177,7 @@ pub(crate) fn group_by_modules(
 ) -> HashMap<String, Vec<Node<StepMetaData>>> {
     let mut result: HashMap<String, Vec<Node<StepMetaData>>> = HashMap::new();
     let mut queue = VecDeque::new();
-    queue.extend(root.get_children());
+    queue.extend(root.get_children_sorted());
 
     while let Some(current) = queue.pop_front() {
         if let Some(node) = result.get_mut(&current.module) {
@@ -149,8 +185,321 @@ pub(crate) fn group_by_modules(
         } else {
             result.insert(current.module.clone(), vec![current.clone()]);
         }
-        queue.extend(current.get_children());
+        queue.extend(current.get_children_sorted());
     }
 
     result
 }
+
+/// Differences between two step trees, see [`diff_trees`]. Paths are written the same way as
+/// lines of the steps file and every list is sorted.
+#[allow(dead_code)]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct TreeDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Pairs of old and new paths of a step that is still there, but narrowed from a different
+    /// parent.
+    pub reparented: Vec<(String, String)>,
+}
+
+impl TreeDiff {
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.reparented.is_empty()
+    }
+}
+
+/// Compares two step trees, e.g. built from the old and new versions of the steps file.
+///
+/// A step counts as re-parented if it was removed from exactly one place and added to exactly
+/// one other place. If a step moves around more than that, there is no telling which old path
+/// became which new one, so its paths are reported as plain additions and removals.
+#[allow(dead_code)]
+pub(crate) fn diff_trees(old: &Node<StepMetaData>, new: &Node<StepMetaData>) -> TreeDiff {
+    fn collect_paths(node: &Node<StepMetaData>, prefix: &str, out: &mut BTreeSet<String>) {
+        for child in node.get_children_sorted() {
+            let step = format!("{}::{}", child.module, child.name);
+            let path = if prefix.is_empty() {
+                step
+            } else {
+                format!("{prefix}/{step}")
+            };
+            collect_paths(&child, &path, out);
+            out.insert(path);
+        }
+    }
+
+    fn by_step(paths: &[&String]) -> BTreeMap<String, Vec<String>> {
+        let mut result: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for path in paths {
+            let step = path.rsplit('/').next().unwrap().to_owned();
+            result.entry(step).or_default().push((*path).clone());
+        }
+        result
+    }
+
+    let (mut old_paths, mut new_paths) = (BTreeSet::new(), BTreeSet::new());
+    collect_paths(old, "", &mut old_paths);
+    collect_paths(new, "", &mut new_paths);
+
+    let removed = by_step(&old_paths.difference(&new_paths).collect::<Vec<_>>());
+    let added = by_step(&new_paths.difference(&old_paths).collect::<Vec<_>>());
+
+    let mut diff = TreeDiff::default();
+    for (step, old) in &removed {
+        match added.get(step) {
+            Some(new) if old.len() == 1 && new.len() == 1 => {
+                diff.reparented.push((old[0].clone(), new[0].clone()));
+            }
+            _ => diff.removed.extend(old.iter().cloned()),
+        }
+    }
+    for (step, new) in added {
+        if !matches!(removed.get(&step), Some(old) if old.len() == 1 && new.len() == 1) {
+            diff.added.extend(new);
+        }
+    }
+    diff.added.sort();
+    diff.removed.sort();
+
+    diff
+}
+
+/// Version of the binary step tree format written by [`write_binary_tree`].
+const BINARY_FORMAT_VERSION: u8 = 1;
+
+/// Writes the step tree in a compact binary form that [`read_binary_tree`] can load without
+/// parsing the steps file again.
+///
+/// The format starts with a version byte, followed by a table of module paths, because many
+/// steps share the same module. Then come the steps in the order the steps file lists them,
+/// each one as its id, depth, index into the module table and name. Paths are not stored,
+/// since they are made of the names of the step and its ancestors. Integers are little-endian,
+/// strings are prefixed with their length as `u16`.
+///
+/// # Errors
+/// If writing to `w` fails or if the tree has more than `u16::MAX` modules or steps.
+#[allow(dead_code)]
+pub(crate) fn write_binary_tree<W: Write>(root: &Node<StepMetaData>, mut w: W) -> io::Result<()> {
+    fn collect(node: &Node<StepMetaData>, out: &mut Vec<Node<StepMetaData>>) {
+        for child in node.get_children() {
+            out.push(child.clone());
+            collect(&child, out);
+        }
+    }
+
+    let mut steps = Vec::new();
+    collect(root, &mut steps);
+    let mut modules = BTreeMap::new();
+    for step in &steps {
+        let next = modules.len();
+        modules.entry(step.module.as_str()).or_insert(next);
+    }
+    let mut module_table = vec![""; modules.len()];
+    for (&module, &index) in &modules {
+        module_table[index] = module;
+    }
+
+    w.write_all(&[BINARY_FORMAT_VERSION])?;
+    write_u16(&mut w, module_table.len())?;
+    for module in module_table {
+        write_str(&mut w, module)?;
+    }
+    write_u16(&mut w, steps.len())?;
+    for step in &steps {
+        w.write_all(&step.id.to_le_bytes())?;
+        w.write_all(&step.depth.to_le_bytes())?;
+        write_u16(&mut w, modules[step.module.as_str()])?;
+        write_str(&mut w, &step.name)?;
+    }
+
+    Ok(())
+}
+
+/// Loads a step tree written by [`write_binary_tree`].
+///
+/// # Errors
+/// If reading from `r` fails or if the data is not a valid step tree.
+#[allow(dead_code)]
+pub(crate) fn read_binary_tree<R: Read>(mut r: R) -> io::Result<Node<StepMetaData>> {
+    let mut version = [0u8; 1];
+    r.read_exact(&mut version)?;
+    if version[0] != BINARY_FORMAT_VERSION {
+        return Err(invalid_data(format!(
+            "unsupported step tree format version {}",
+            version[0]
+        )));
+    }
+
+    let modules = (0..read_u16(&mut r)?)
+        .map(|_| read_str(&mut r))
+        .collect::<io::Result<Vec<_>>>()?;
+    let count = read_u16(&mut r)?;
+    let mut steps = Vec::with_capacity(usize::from(count));
+    // names of the ancestors of the current step, used to restore its path.
+    let mut names: Vec<String> = Vec::new();
+    for _ in 0..count {
+        let id = read_u16(&mut r)?;
+        let depth = read_u16(&mut r)?;
+        let module = modules
+            .get(usize::from(read_u16(&mut r)?))
+            .ok_or_else(|| invalid_data(format!("step {id} refers to an unknown module")))?;
+        let name = read_str(&mut r)?;
+        if depth == 0 || usize::from(depth) > names.len() + 1 {
+            return Err(invalid_data(format!("step {id} has invalid depth {depth}")));
+        }
+        names.truncate(usize::from(depth) - 1);
+        names.push(name.clone());
+        steps.push(StepMetaData::new(
+            id,
+            depth,
+            module.clone(),
+            name,
+            names.join("/"),
+        ));
+    }
+
+    Ok(construct_tree(steps))
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn write_u16<W: Write>(w: &mut W, v: usize) -> io::Result<()> {
+    let v = u16::try_from(v)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("{v} > u16::MAX")))?;
+    w.write_all(&v.to_le_bytes())
+}
+
+fn write_str<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    write_u16(w, s.len())?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_u16<R: Read>(r: &mut R) -> io::Result<u16> {
+    let mut buf = [0u8; 2];
+    r.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_str<R: Read>(r: &mut R) -> io::Result<String> {
+    let mut buf = vec![0u8; usize::from(read_u16(r)?)];
+    r.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|e| invalid_data(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::{
+            construct_tree, diff_trees, parse_step, read_binary_tree, write_binary_tree,
+            StepMetaData, TreeDiff,
+        },
+        tree::Node,
+    };
+
+    fn build_tree(lines: &[&str]) -> Node<StepMetaData> {
+        let steps = lines
+            .iter()
+            .enumerate()
+            .map(|(i, path)| parse_step(u16::try_from(i + 1).unwrap(), path))
+            .collect::<Vec<_>>();
+        construct_tree(steps)
+    }
+
+    #[test]
+    fn diff() {
+        let old = build_tree(&[
+            "m::A::a",
+            "m::A::a/m::B::b",
+            "m::A::a/m::B::b/m::D::d",
+            "m::A::a/m::C::c",
+            "m::E::e",
+        ]);
+        let new = build_tree(&[
+            "m::A::a",
+            "m::A::a/m::B::b",
+            "m::A::a/m::F::f",
+            "m::E::e",
+            "m::E::e/m::D::d",
+        ]);
+
+        assert_eq!(
+            TreeDiff {
+                added: vec!["m::A::a/m::F::f".to_owned()],
+                removed: vec!["m::A::a/m::C::c".to_owned()],
+                reparented: vec![(
+                    "m::A::a/m::B::b/m::D::d".to_owned(),
+                    "m::E::e/m::D::d".to_owned()
+                )],
+            },
+            diff_trees(&old, &new)
+        );
+        assert!(diff_trees(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        fn flatten(node: &Node<StepMetaData>, out: &mut Vec<(u16, u16, String, String, String)>) {
+            for child in node.get_children() {
+                out.push((
+                    child.id,
+                    child.depth,
+                    child.module.clone(),
+                    child.name.clone(),
+                    child.path.clone(),
+                ));
+                flatten(&child, out);
+            }
+        }
+
+        let tree = build_tree(&[
+            "m::A::a",
+            "m::A::a/m::B::b",
+            "m::A::a/m::B::b/n::D::d",
+            "m::A::a/m::C::c",
+            "m::E::e",
+            "m::E::e/n::D::d",
+        ]);
+        let mut buf = Vec::new();
+        write_binary_tree(&tree, &mut buf).unwrap();
+        let loaded = read_binary_tree(buf.as_slice()).unwrap();
+
+        let (mut expected, mut actual) = (Vec::new(), Vec::new());
+        flatten(&tree, &mut expected);
+        flatten(&loaded, &mut actual);
+        assert_eq!(6, expected.len());
+        assert_eq!(expected, actual);
+        assert!(diff_trees(&tree, &loaded).is_empty());
+
+        // truncated input is rejected
+        assert!(read_binary_tree(&buf[..buf.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn deep_path() {
+        const DEPTH: usize = 300;
+        let steps = (1..=DEPTH)
+            .map(|depth| {
+                let path = (0..depth)
+                    .map(|i| format!("protocol::Step::s{i}"))
+                    .collect::<Vec<_>>()
+                    .join("/");
+                parse_step(u16::try_from(depth).unwrap(), &path)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(300, steps.last().unwrap().depth);
+
+        let mut node = construct_tree(steps);
+        for depth in 1..=DEPTH {
+            let children = node.get_children();
+            assert_eq!(1, children.len());
+            node = children[0].clone();
+            assert_eq!(depth, usize::from(node.depth));
+            assert_eq!(format!("s{}", depth - 1), node.name);
+        }
+        assert!(node.get_children().is_empty());
+    }
+}
@@ -70,6 +70,18 @@ impl<T> Node<T> {
             .collect::<Vec<_>>()
     }
 
+    /// Same as [`Node::get_children`], but the children are ordered by their values rather than
+    /// by insertion order. Code generation should walk the tree using this method, so that its
+    /// output does not depend on how steps are ordered within a parent in the steps file.
+    pub fn get_children_sorted(&self) -> Vec<Node<T>>
+    where
+        T: Ord,
+    {
+        let mut children = self.get_children();
+        children.sort_by(|a, b| a.value.cmp(&b.value));
+        children
+    }
+
     pub fn get_parent(&self) -> Option<Node<T>> {
         if self.parent.strong.borrow().is_some() {
             return Some(Node {
@@ -131,7 +143,7 @@ mod tests {
 
     use crate::tree::Node;
 
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
     struct TestData(u8);
 
     #[test]
@@ -145,6 +157,28 @@ mod tests {
         assert_eq!(children[1].value.0, 2);
     }
 
+    #[test]
+    fn children_sorted() {
+        let a = Node::new(TestData(0));
+        a.add_child(TestData(2));
+        a.add_child(TestData(3));
+        a.add_child(TestData(1));
+
+        let b = Node::new(TestData(0));
+        b.add_child(TestData(1));
+        b.add_child(TestData(3));
+        b.add_child(TestData(2));
+
+        let values = |node: &Node<TestData>| {
+            node.get_children_sorted()
+                .iter()
+                .map(|c| c.value.0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![1, 2, 3], values(&a));
+        assert_eq!(values(&a), values(&b));
+    }
+
     #[test]
     fn parent() {
         let root = Node::new(TestData(0));